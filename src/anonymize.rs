@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use crate::{
+    enums::{ClientQueryType, ClientResponseType, FsdMessageType, SharedStateType},
+    errors::FsdError,
+    messages::SERVER_CALLSIGN,
+};
+
+/// The text written in place of a real name by [`FsdMessageType::anonymize`]
+pub const REDACTED_NAME: &str = "REDACTED";
+
+/// The address written in place of a public IP address by [`FsdMessageType::anonymize`]
+pub const REDACTED_IP_ADDRESS: &str = "0.0.0.0";
+
+/// Assigns pseudonyms to callsigns, so that a callsign is replaced by the same pseudonym throughout a session.
+///
+/// A pseudonym keeps the facility suffix of an ATC callsign, so `EGLL_N_TWR` might become `ATC1_TWR`, and pilot
+/// callsigns become `ACFT1`, `ACFT2` and so on. `SERVER`, broadcast recipients starting with `*` and special
+/// frequencies starting with `@` are never replaced.
+#[derive(Debug, Clone, Default)]
+pub struct CallsignMap {
+    rewrite: bool,
+    pseudonyms: HashMap<String, String>,
+    atc_count: usize,
+    aircraft_count: usize,
+}
+
+impl CallsignMap {
+    /// A map which leaves callsigns as they are, for anonymizing only personal details
+    pub fn keep_callsigns() -> Self {
+        CallsignMap::default()
+    }
+
+    /// A map which replaces every callsign with a pseudonym
+    pub fn pseudonymize() -> Self {
+        CallsignMap {
+            rewrite: true,
+            ..CallsignMap::default()
+        }
+    }
+
+    /// The pseudonym for a callsign, assigning a new one if the callsign has not been seen before
+    pub fn pseudonym(&mut self, callsign: &str) -> String {
+        if !self.rewrite
+            || callsign.is_empty()
+            || callsign.eq_ignore_ascii_case(SERVER_CALLSIGN)
+            || callsign.starts_with(['*', '@'])
+        {
+            return callsign.to_string();
+        }
+        let callsign = callsign.to_uppercase();
+        if let Some(pseudonym) = self.pseudonyms.get(&callsign) {
+            return pseudonym.clone();
+        }
+        let pseudonym = match callsign.rsplit_once('_') {
+            Some((_, suffix)) if Self::is_facility(suffix) => {
+                self.atc_count += 1;
+                format!("ATC{}_{}", self.atc_count, suffix)
+            }
+            _ => {
+                self.aircraft_count += 1;
+                format!("ACFT{}", self.aircraft_count)
+            }
+        };
+        self.pseudonyms.insert(callsign, pseudonym.clone());
+        pseudonym
+    }
+
+    fn is_facility(suffix: &str) -> bool {
+        matches!(
+            suffix,
+            "OBS" | "DEL" | "GND" | "TWR" | "APP" | "DEP" | "CTR" | "FSS" | "ATIS"
+        )
+    }
+
+    fn rewrite(&mut self, callsign: &mut String) {
+        *callsign = self.pseudonym(callsign);
+    }
+}
+
+impl FsdMessageType {
+    /// Removes personal details from the message so that a captured session can be shared, leaving it otherwise
+    /// intact so that it still parses.
+    ///
+    /// CIDs, passwords and client GUIDs are blanked, real names are replaced with [`REDACTED_NAME`] and public IP
+    /// addresses with [`REDACTED_IP_ADDRESS`]. Callsigns, wherever they appear outside free text, are replaced
+    /// according to `map`. Free text, such as text messages and flight plan remarks, is left untouched.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, CallsignMap};
+    /// let session = [
+    ///     "$IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890",
+    ///     "#APBAW123:SERVER:1234567:hunter2:1:101:9:Joe Bloggs EGGW",
+    ///     "#AAEGLL_N_TWR:SERVER:Jane Doe:7654321:secret:5:101",
+    ///     "$CREGLL_N_TWR:BAW123:RN:Jane Doe:EGLL.ese:5",
+    ///     "$CQEGLL_N_TWR:@94835:BC:BAW123:4721",
+    ///     "#TMEGLL_N_TWR:BAW123:Contact Heathrow Director",
+    ///     "#DPBAW123:1234567",
+    /// ];
+    /// let mut map = CallsignMap::pseudonymize();
+    /// let anonymized: Vec<String> = session
+    ///     .iter()
+    ///     .map(|line| {
+    ///         let mut message = parse_message(line).unwrap();
+    ///         message.anonymize(&mut map);
+    ///         message.to_string()
+    ///     })
+    ///     .collect();
+    /// for line in &anonymized {
+    ///     assert!(parse_message(line).is_ok(), "{line}");
+    ///     for original in ["1234567", "7654321", "hunter2", "secret", "Joe Bloggs", "Jane Doe", "BAW123", "EGLL_N_TWR"] {
+    ///         assert!(!line.contains(original), "{line}");
+    ///     }
+    /// }
+    /// assert_eq!("#APACFT1:SERVER:::1:101:9:REDACTED", anonymized[1]);
+    /// assert_eq!("$CQATC1_TWR:@94835:BC:ACFT1:4721", anonymized[4]);
+    /// assert_eq!("#TMATC1_TWR:ACFT1:Contact Heathrow Director", anonymized[5]);
+    /// ```
+    pub fn anonymize(&mut self, map: &mut CallsignMap) {
+        match self {
+            FsdMessageType::AtcRegisterMessage(m) => {
+                m.real_name = REDACTED_NAME.to_string();
+                m.cid.clear();
+                m.password.clear();
+            }
+            FsdMessageType::PilotRegisterMessage(m) => {
+                m.cid.clear();
+                m.password.clear();
+                if m.real_name.is_some() {
+                    m.real_name = Some(REDACTED_NAME.to_string());
+                }
+            }
+            FsdMessageType::AtcDeregisterMessage(m) => m.cid.clear(),
+            FsdMessageType::PilotDeregisterMessage(m) => m.cid.clear(),
+            FsdMessageType::InitialClientHandshakeMessage(m) => {
+                m.cid.clear();
+                m.guid.clear();
+            }
+            FsdMessageType::ClientQueryResponseMessage(m) => match &mut m.response_type {
+                ClientResponseType::RealName(name, _, _) => *name = REDACTED_NAME.to_string(),
+                ClientResponseType::PublicIP(address) => *address = REDACTED_IP_ADDRESS.to_string(),
+                _ => {}
+            },
+            _ => {}
+        }
+        for callsign in self.callsigns_mut() {
+            map.rewrite(callsign);
+        }
+    }
+
+    /// Every field of the message which holds a callsign, outside free text
+    pub(crate) fn callsigns_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            FsdMessageType::AtcRegisterMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::PilotRegisterMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::AtcDeregisterMessage(m) => vec![&mut m.from],
+            FsdMessageType::PilotDeregisterMessage(m) => vec![&mut m.from],
+            FsdMessageType::AtcPositionUpdateMessage(m) => vec![&mut m.callsign],
+            FsdMessageType::AtcSecondaryVisCentreMessage(m) => vec![&mut m.callsign],
+            FsdMessageType::PilotPositionUpdateMessage(m) => vec![&mut m.callsign],
+            FsdMessageType::AuthenticationChallengeMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::AuthenticationResponseMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::TextMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::FrequencyMessage(m) => vec![&mut m.from],
+            FsdMessageType::ChangeServerMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::InitialServerHandshakeMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::InitialClientHandshakeMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::SendFastPositionUpdatesMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::VelocityPositionStoppedMessage(m) => vec![&mut m.from],
+            FsdMessageType::VelocityPositionSlowMessage(m) => vec![&mut m.from],
+            FsdMessageType::VelocityPositionFastMessage(m) => vec![&mut m.from],
+            FsdMessageType::KillMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::MetarRequestMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::MetarResponseMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::PingMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::PongMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::PlaneInfoRequestMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::PlaneInfoResponseMessage(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::FsdErrorMessage(m) => {
+                let mut callsigns = vec![&mut m.from, &mut m.to];
+                if let FsdError::NoSuchCallsign(subject) | FsdError::NoFlightPlan(subject) =
+                    &mut m.error_type
+                {
+                    callsigns.push(subject);
+                }
+                callsigns
+            }
+            FsdMessageType::FlightPlanMessage(m) => vec![&mut m.callsign, &mut m.to],
+            FsdMessageType::FlightPlanAmendmentMessage(m) => {
+                vec![&mut m.from, &mut m.to, &mut m.callsign]
+            }
+            FsdMessageType::ClientQueryMessage(m) => {
+                let mut callsigns = vec![&mut m.from, &mut m.to];
+                match &mut m.query_type {
+                    ClientQueryType::AcceptHandoff(aircraft, atc) => {
+                        callsigns.push(aircraft);
+                        callsigns.push(atc);
+                    }
+                    ClientQueryType::IsValidATC(subject)
+                    | ClientQueryType::FlightPlan(subject)
+                    | ClientQueryType::WhoHas(subject)
+                    | ClientQueryType::InitiateTrack(subject)
+                    | ClientQueryType::DropTrack(subject)
+                    | ClientQueryType::SetFinalAltitude(subject, _)
+                    | ClientQueryType::SetTempAltitude(subject, _)
+                    | ClientQueryType::SetBeaconCode(subject, _)
+                    | ClientQueryType::SetScratchpad(subject, _)
+                    | ClientQueryType::SetVoiceType(subject, _)
+                    | ClientQueryType::SetGlobalData(subject, _) => callsigns.push(subject),
+                    _ => {}
+                }
+                callsigns
+            }
+            FsdMessageType::ClientQueryResponseMessage(m) => {
+                let mut callsigns = vec![&mut m.from, &mut m.to];
+                if let ClientResponseType::IsValidATC(subject, _) = &mut m.response_type {
+                    callsigns.push(subject);
+                }
+                callsigns
+            }
+            FsdMessageType::HandoffOfferMessage(m) => {
+                vec![&mut m.from, &mut m.to, &mut m.aircraft]
+            }
+            FsdMessageType::HandoffAcceptMessage(m) => {
+                vec![&mut m.from, &mut m.to, &mut m.aircraft]
+            }
+            FsdMessageType::SharedStateMessage(m) => {
+                let mut callsigns = vec![&mut m.from, &mut m.to];
+                match &mut m.shared_state_type {
+                    SharedStateType::IHave(subject)
+                    | SharedStateType::ScratchPad(subject, _)
+                    | SharedStateType::TempAltitude(subject, _)
+                    | SharedStateType::FinalAltitude(subject, _)
+                    | SharedStateType::VoiceType(subject, _)
+                    | SharedStateType::BeaconCode(subject, _)
+                    | SharedStateType::HandoffCancel(subject)
+                    | SharedStateType::GlobalData(subject, _) => callsigns.push(subject),
+                    _ => {}
+                }
+                callsigns
+            }
+            FsdMessageType::ServerHeartbeat(m) => vec![&mut m.from, &mut m.to],
+            FsdMessageType::FSInnPlaneInformationRequestMessage
+            | FsdMessageType::FSInnPlaneInformationResponseMessage => Vec::new(),
+        }
+    }
+}
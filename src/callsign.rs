@@ -0,0 +1,101 @@
+use crate::{enums::FsdMessageType, errors::FsdMessageParseError};
+
+/// Rules a network applies to the callsigns clients may register with.
+///
+/// The default policy follows common VATSIM rules: 2 to 12 characters, made up of ASCII letters, digits, `_` and `-`,
+/// with ATC callsigns required to start with a letter.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, CallsignPolicy};
+/// let policy = CallsignPolicy::default();
+/// assert!(policy.validate("BAW123").is_ok());
+/// assert!(policy.validate("N123AB").is_ok());
+/// assert!(policy.validate_atc("EGLL_N_TWR").is_ok());
+///
+/// assert!(policy.validate("B").is_err());
+/// assert!(policy.validate("BAW123456789X").is_err());
+/// assert!(policy.validate("BAW 123").is_err());
+/// assert!(policy.validate_atc("1EGLL_TWR").is_err());
+///
+/// let registration = parse_message("#AAEGLL:SERVER:Joe Bloggs:1234567:password:5:100").unwrap();
+/// assert!(policy.validate_registration(&registration).is_ok());
+/// let registration = parse_message("#AA1EGLL:SERVER:Joe Bloggs:1234567:password:5:100").unwrap();
+/// assert!(policy.validate_registration(&registration).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallsignPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    /// Characters allowed in addition to ASCII letters and digits
+    pub allowed_punctuation: Vec<char>,
+    pub atc_may_start_with_digit: bool,
+}
+
+impl Default for CallsignPolicy {
+    fn default() -> Self {
+        CallsignPolicy {
+            min_length: 2,
+            max_length: 12,
+            allowed_punctuation: vec!['_', '-'],
+            atc_may_start_with_digit: false,
+        }
+    }
+}
+
+impl CallsignPolicy {
+    fn invalid(callsign: &str, reason: impl Into<String>) -> FsdMessageParseError {
+        FsdMessageParseError::InvalidCallsign(callsign.to_string(), reason.into())
+    }
+
+    /// Checks a callsign against the rules which apply to every client
+    pub fn validate(&self, callsign: &str) -> Result<(), FsdMessageParseError> {
+        let length = callsign.chars().count();
+        if length < self.min_length {
+            return Err(Self::invalid(
+                callsign,
+                format!("shorter than {} characters", self.min_length),
+            ));
+        }
+        if length > self.max_length {
+            return Err(Self::invalid(
+                callsign,
+                format!("longer than {} characters", self.max_length),
+            ));
+        }
+        if let Some(c) = callsign
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && !self.allowed_punctuation.contains(c))
+        {
+            return Err(Self::invalid(
+                callsign,
+                format!("'{}' is not an allowed character", c),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks a callsign against the rules which apply to ATC clients
+    pub fn validate_atc(&self, callsign: &str) -> Result<(), FsdMessageParseError> {
+        self.validate(callsign)?;
+        if !self.atc_may_start_with_digit && callsign.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(Self::invalid(
+                callsign,
+                "ATC callsigns must not start with a digit",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the callsign of an `#AA` or `#AP` registration. Any other message is accepted.
+    pub fn validate_registration(
+        &self,
+        message: &FsdMessageType,
+    ) -> Result<(), FsdMessageParseError> {
+        match message {
+            FsdMessageType::AtcRegisterMessage(m) => self.validate_atc(&m.from),
+            FsdMessageType::PilotRegisterMessage(m) => self.validate(&m.from),
+            _ => Ok(()),
+        }
+    }
+}
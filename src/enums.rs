@@ -1,12 +1,16 @@
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, str::FromStr, time::SystemTime};
 
 use bevy_reflect::Reflect;
 
 use crate::messages::*;
-use crate::structs::{RadioFrequency, TransponderCode};
-use crate::{aircraft_config::AircraftConfig, errors::FsdMessageParseError};
+use crate::structs::{Level, RadioFrequency, TransponderCode};
+use crate::{aircraft_config::AircraftConfig, errors::FsdMessageParseError, util};
 
+const DISPLAY_SAFE_MAX_FIELD_LEN: usize = 256;
+
+/// A feature a client advertises in its `CAPS` response
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ClientCapability {
     Version,
     ATCInfo,
@@ -47,7 +51,7 @@ impl FromStr for ClientCapability {
 }
 impl Display for ClientCapability {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match *self {
+        match self {
             ClientCapability::ACConfig => write!(f, "ACCONFIG"),
             ClientCapability::ATCInfo => write!(f, "ATCINFO"),
             ClientCapability::ModelDesc => write!(f, "MODELDESC"),
@@ -66,7 +70,79 @@ impl Display for ClientCapability {
     }
 }
 
+/// One entry of a `CAPS` response: a capability, and whether it was advertised as supported (`=1`) or unsupported
+/// (`=0`). Entries are kept in the order they were sent, so that the response can be re-emitted as received.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, AdvertisedCapability, ClientCapability, ClientResponseType, FsdMessageType};
+/// let caps = "$CREGKK_APP:EGLL_N_TWR:CAPS:VERSION=1:NEWCAP=1:ATCINFO=1:STEALTH=0";
+/// let FsdMessageType::ClientQueryResponseMessage(response) = parse_message(caps).unwrap() else {
+///     panic!("expected a client query response");
+/// };
+/// let ClientResponseType::Capabilities(ref capabilities) = response.response_type else {
+///     panic!("expected a capabilities response");
+/// };
+/// assert_eq!(
+///     vec![
+///         AdvertisedCapability::Known(ClientCapability::Version, true),
+///         AdvertisedCapability::Unknown("NEWCAP".to_string(), true),
+///         AdvertisedCapability::Known(ClientCapability::ATCInfo, true),
+///         AdvertisedCapability::Known(ClientCapability::Stealth, false),
+///     ],
+///     *capabilities
+/// );
+/// assert!(!capabilities[3].supported());
+/// assert_eq!(Some(ClientCapability::Stealth), capabilities[3].capability());
+/// assert_eq!(caps, response.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data"))]
+pub enum AdvertisedCapability {
+    /// A capability recognised by this crate, and whether it is supported
+    Known(ClientCapability, bool),
+    /// A capability not recognised by this crate, kept by name so that it can be re-emitted verbatim, and whether it
+    /// is supported
+    Unknown(String, bool),
+}
+
+impl AdvertisedCapability {
+    /// Whether the capability was advertised as supported (`=1`)
+    pub fn supported(&self) -> bool {
+        match *self {
+            AdvertisedCapability::Known(_, supported)
+            | AdvertisedCapability::Unknown(_, supported) => supported,
+        }
+    }
+
+    /// The capability advertised, if the crate recognises it
+    pub fn capability(&self) -> Option<ClientCapability> {
+        match *self {
+            AdvertisedCapability::Known(capability, _) => Some(capability),
+            AdvertisedCapability::Unknown(..) => None,
+        }
+    }
+}
+
+impl From<ClientCapability> for AdvertisedCapability {
+    fn from(capability: ClientCapability) -> Self {
+        AdvertisedCapability::Known(capability, true)
+    }
+}
+
+impl Display for AdvertisedCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = if self.supported() { 1 } else { 0 };
+        match self {
+            AdvertisedCapability::Known(capability, _) => write!(f, "{}={}", capability, value),
+            AdvertisedCapability::Unknown(name, _) => write!(f, "{}={}", name, value),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AtcRating {
     Observer = 1,
     S1,
@@ -81,12 +157,20 @@ pub enum AtcRating {
     Supervisor,
     Administrator,
 }
-impl FromStr for AtcRating {
-    type Err = FsdMessageParseError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let rating_u8 =
-            u8::from_str(s).map_err(|_| FsdMessageParseError::InvalidRating(s.to_string()))?;
-        match rating_u8 {
+impl AtcRating {
+    /// Converts a numeric rating, as used on the wire, into an [`AtcRating`]
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{errors::FsdMessageParseError, AtcRating};
+    /// for value in 1..=12 {
+    ///     assert_eq!(value, AtcRating::from_u8(value).unwrap().as_u8());
+    /// }
+    /// assert_eq!(AtcRating::C1, AtcRating::from_u8(5).unwrap());
+    /// assert!(matches!(AtcRating::from_u8(13), Err(FsdMessageParseError::InvalidRating(_))));
+    /// ```
+    pub fn from_u8(rating: u8) -> Result<AtcRating, FsdMessageParseError> {
+        match rating {
             1 => Ok(AtcRating::Observer),
             2 => Ok(AtcRating::S1),
             3 => Ok(AtcRating::S2),
@@ -99,38 +183,82 @@ impl FromStr for AtcRating {
             10 => Ok(AtcRating::I3),
             11 => Ok(AtcRating::Supervisor),
             12 => Ok(AtcRating::Administrator),
-            _ => Err(FsdMessageParseError::InvalidRating(s.to_string())),
+            _ => Err(FsdMessageParseError::InvalidRating(rating.to_string())),
         }
     }
+
+    /// The numeric rating used on the wire
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+impl FromStr for AtcRating {
+    type Err = FsdMessageParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rating_u8 =
+            u8::from_str(s).map_err(|_| FsdMessageParseError::InvalidRating(s.to_string()))?;
+        AtcRating::from_u8(rating_u8)
+            .map_err(|_| FsdMessageParseError::InvalidRating(s.to_string()))
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PilotRating {
     Student = 1,
     VFR,
     IFR,
     Instructor,
     Supervisor,
+    /// A rating that could not be read, which [`ParseMode::Lenient`] substitutes in pilot position updates rather
+    /// than dropping the update. It is written as `0`, and `0` is read back as [`PilotRating::Unknown`].
+    Unknown = 0,
 }
 
-impl FromStr for PilotRating {
-    type Err = FsdMessageParseError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let rating_u8 =
-            u8::from_str(s).map_err(|_| FsdMessageParseError::InvalidRating(s.to_string()))?;
-        match rating_u8 {
+impl PilotRating {
+    /// Converts a numeric rating, as used on the wire, into a [`PilotRating`]
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{errors::FsdMessageParseError, PilotRating};
+    /// for value in 0..=5 {
+    ///     assert_eq!(value, PilotRating::from_u8(value).unwrap().as_u8());
+    /// }
+    /// assert_eq!(PilotRating::IFR, PilotRating::from_u8(3).unwrap());
+    /// assert_eq!(PilotRating::Unknown, "0".parse().unwrap());
+    /// assert!(matches!(PilotRating::from_u8(6), Err(FsdMessageParseError::InvalidRating(_))));
+    /// ```
+    pub fn from_u8(rating: u8) -> Result<PilotRating, FsdMessageParseError> {
+        match rating {
+            0 => Ok(PilotRating::Unknown),
             1 => Ok(PilotRating::Student),
             2 => Ok(PilotRating::VFR),
             3 => Ok(PilotRating::IFR),
             4 => Ok(PilotRating::Instructor),
             5 => Ok(PilotRating::Supervisor),
-            _ => Err(FsdMessageParseError::InvalidRating(s.to_string())),
+            _ => Err(FsdMessageParseError::InvalidRating(rating.to_string())),
         }
     }
+
+    /// The numeric rating used on the wire
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl FromStr for PilotRating {
+    type Err = FsdMessageParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rating_u8 =
+            u8::from_str(s).map_err(|_| FsdMessageParseError::InvalidRating(s.to_string()))?;
+        PilotRating::from_u8(rating_u8)
+            .map_err(|_| FsdMessageParseError::InvalidRating(s.to_string()))
+    }
 }
 
 /// Represents a version of the FSD protocol
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProtocolRevision {
     /// Used on legacy FSD servers. If the FSD server is a privately run one, it is most likely using this version
     Classic = 9,
@@ -154,10 +282,28 @@ impl FromStr for ProtocolRevision {
     }
 }
 
+/// Represents the flight simulator used by a pilot client
+///
+/// Parses from either its numeric wire value or, case-insensitively, its variant name. Numeric values which the
+/// crate does not recognise are kept as [`SimulatorType::Unknown`], so that a client using a new simulator can still
+/// register, and are written back out unchanged.
 ///
-#[derive(Debug, Clone, Copy)]
+/// # Example
+/// ```
+/// use fsd_interface::SimulatorType;
+/// assert_eq!(SimulatorType::XPlane11, "16".parse().unwrap());
+/// assert_eq!(SimulatorType::XPlane11, "xplane11".parse().unwrap());
+/// assert_eq!(SimulatorType::P3D, "P3D".parse().unwrap());
+/// assert_eq!(SimulatorType::Unknown(11), "11".parse().unwrap());
+/// assert_eq!(SimulatorType::Unknown(42), SimulatorType::from(42));
+/// assert_eq!(42, SimulatorType::Unknown(42).as_u8());
+/// assert!("Concorde".parse::<SimulatorType>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data"))]
 pub enum SimulatorType {
-    MSFS95 = 1,
+    MSFS95,
     MSFS98,
     MSCFS,
     MSFS2000,
@@ -166,39 +312,75 @@ pub enum SimulatorType {
     MSCFS3,
     MSFS2004,
     MSFSX,
-    XPlane8 = 12,
+    XPlane8,
     XPlane9,
     XPlane10,
-    XPlane11 = 16,
-    FlightGear = 25,
-    P3D = 30,
+    XPlane11,
+    FlightGear,
+    P3D,
+    /// A simulator not recognised by this crate, holding its numeric wire value
+    Unknown(u8),
+}
+
+impl SimulatorType {
+    /// Every simulator the crate recognises, with its numeric wire value and its name. The conversions in both
+    /// directions are derived from this table.
+    const KNOWN: [(SimulatorType, u8, &'static str); 15] = [
+        (SimulatorType::MSFS95, 1, "MSFS95"),
+        (SimulatorType::MSFS98, 2, "MSFS98"),
+        (SimulatorType::MSCFS, 3, "MSCFS"),
+        (SimulatorType::MSFS2000, 4, "MSFS2000"),
+        (SimulatorType::MSCFS2, 5, "MSCFS2"),
+        (SimulatorType::MSFS2002, 6, "MSFS2002"),
+        (SimulatorType::MSCFS3, 7, "MSCFS3"),
+        (SimulatorType::MSFS2004, 8, "MSFS2004"),
+        (SimulatorType::MSFSX, 9, "MSFSX"),
+        (SimulatorType::XPlane8, 12, "XPlane8"),
+        (SimulatorType::XPlane9, 13, "XPlane9"),
+        (SimulatorType::XPlane10, 14, "XPlane10"),
+        (SimulatorType::XPlane11, 16, "XPlane11"),
+        (SimulatorType::FlightGear, 25, "FlightGear"),
+        (SimulatorType::P3D, 30, "P3D"),
+    ];
+
+    /// The numeric value used on the wire
+    pub fn as_u8(&self) -> u8 {
+        match *self {
+            SimulatorType::Unknown(value) => value,
+            simulator => Self::KNOWN
+                .iter()
+                .find(|(known, ..)| *known == simulator)
+                .map(|(_, value, _)| *value)
+                .expect("every simulator other than Unknown is listed in SimulatorType::KNOWN"),
+        }
+    }
+}
+
+impl From<u8> for SimulatorType {
+    fn from(value: u8) -> Self {
+        SimulatorType::KNOWN
+            .iter()
+            .find(|(_, known, _)| *known == value)
+            .map_or(SimulatorType::Unknown(value), |(simulator, ..)| *simulator)
+    }
 }
 
 impl FromStr for SimulatorType {
     type Err = FsdMessageParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "1" => Ok(SimulatorType::MSFS95),
-            "2" => Ok(SimulatorType::MSFS98),
-            "3" => Ok(SimulatorType::MSCFS),
-            "4" => Ok(SimulatorType::MSFS2000),
-            "5" => Ok(SimulatorType::MSCFS2),
-            "6" => Ok(SimulatorType::MSFS2002),
-            "7" => Ok(SimulatorType::MSCFS3),
-            "8" => Ok(SimulatorType::MSFS2004),
-            "9" => Ok(SimulatorType::MSFSX),
-            "12" => Ok(SimulatorType::XPlane8),
-            "13" => Ok(SimulatorType::XPlane9),
-            "14" => Ok(SimulatorType::XPlane10),
-            "16" => Ok(SimulatorType::XPlane11),
-            "25" => Ok(SimulatorType::FlightGear),
-            "30" => Ok(SimulatorType::P3D),
-            _ => Err(FsdMessageParseError::InvalidSimulatorType(s.to_string())),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+        if let Ok(value) = s.parse::<u8>() {
+            return Ok(SimulatorType::from(value));
+        }
+        SimulatorType::KNOWN
+            .iter()
+            .find(|(_, _, name)| name.eq_ignore_ascii_case(s))
+            .map(|(simulator, ..)| *simulator)
+            .ok_or_else(|| FsdMessageParseError::InvalidSimulatorType(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FlightRules {
     DVFR,
     SVFR,
@@ -231,7 +413,53 @@ impl Display for FlightRules {
     }
 }
 
+/// The type of facility an ATC client is controlling
+///
+/// Parses from either its numeric wire value or, case-insensitively, its callsign suffix
+/// (`OBS`, `FSS`, `DEL`, `GND`, `TWR`, `APP` or `CTR`).
+///
+/// # Example
+/// ```
+/// use fsd_interface::AtcType;
+/// assert_eq!(AtcType::Tower, "4".parse().unwrap());
+/// assert_eq!(AtcType::Tower, "TWR".parse().unwrap());
+/// assert_eq!(AtcType::Approach, "app".parse().unwrap());
+/// assert!("TMA".parse::<AtcType>().is_err());
+/// ```
+///
+/// # Ordering
+///
+/// Facility types are ordered by the airspace they cover, as controller lists are conventionally sorted, rather
+/// than by their wire value: `Observer < Delivery < Ground < Tower < Approach < Centre < FlightServiceStation`.
+/// Observers control nothing so come first, and flight service stations, which provide services over or beyond
+/// centre airspace, come last.
+///
+/// ```
+/// use fsd_interface::{messages::AtcPositionUpdateMessage, AtcRating, AtcType};
+/// let mut types = vec![
+///     AtcType::Tower, AtcType::FlightServiceStation, AtcType::Observer, AtcType::Centre,
+///     AtcType::Delivery, AtcType::Approach, AtcType::Ground,
+/// ];
+/// types.sort();
+/// assert_eq!(
+///     vec![
+///         AtcType::Observer, AtcType::Delivery, AtcType::Ground, AtcType::Tower,
+///         AtcType::Approach, AtcType::Centre, AtcType::FlightServiceStation,
+///     ],
+///     types
+/// );
+///
+/// let mut controllers = vec![
+///     AtcPositionUpdateMessage::new("EGTT_CTR", vec![], AtcType::Centre, 300, AtcRating::C1, 51.47, -0.45, 0),
+///     AtcPositionUpdateMessage::new("EGLL_DEL", vec![], AtcType::Delivery, 20, AtcRating::S1, 51.47, -0.45, 83),
+///     AtcPositionUpdateMessage::new("EGLL_N_TWR", vec![], AtcType::Tower, 30, AtcRating::S2, 51.47, -0.45, 83),
+/// ];
+/// controllers.sort_by_key(|controller| controller.atc_type);
+/// let callsigns: Vec<_> = controllers.iter().map(|controller| controller.callsign.as_str()).collect();
+/// assert_eq!(vec!["EGLL_DEL", "EGLL_N_TWR", "EGTT_CTR"], callsigns);
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AtcType {
     Observer,
     FlightServiceStation,
@@ -242,6 +470,32 @@ pub enum AtcType {
     Centre,
 }
 
+impl AtcType {
+    fn coverage_order(&self) -> u8 {
+        match self {
+            AtcType::Observer => 0,
+            AtcType::Delivery => 1,
+            AtcType::Ground => 2,
+            AtcType::Tower => 3,
+            AtcType::Approach => 4,
+            AtcType::Centre => 5,
+            AtcType::FlightServiceStation => 6,
+        }
+    }
+}
+
+impl Ord for AtcType {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.coverage_order().cmp(&other.coverage_order())
+    }
+}
+
+impl PartialOrd for AtcType {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl FromStr for AtcType {
     type Err = FsdMessageParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -253,12 +507,67 @@ impl FromStr for AtcType {
             "4" => Ok(AtcType::Tower),
             "5" => Ok(AtcType::Approach),
             "6" => Ok(AtcType::Centre),
-            _ => Err(FsdMessageParseError::InvalidAtcType(s.to_string())),
+            _ => match s.to_uppercase().as_str() {
+                "OBS" => Ok(AtcType::Observer),
+                "FSS" => Ok(AtcType::FlightServiceStation),
+                "DEL" => Ok(AtcType::Delivery),
+                "GND" => Ok(AtcType::Ground),
+                "TWR" => Ok(AtcType::Tower),
+                "APP" => Ok(AtcType::Approach),
+                "CTR" => Ok(AtcType::Centre),
+                _ => Err(FsdMessageParseError::InvalidAtcType(s.to_string())),
+            },
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// A non-fatal problem with the contents of a flight plan, as reported by [`crate::FlightPlan::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlightPlanWarning {
+    /// No cruise level was filed
+    MissingCruiseLevel,
+    /// The filed cruise level (in feet) is above anything a civil aircraft can reach
+    ImplausibleCruiseLevel(u32),
+    /// The filed cruise level (in feet) is not one the flight rules allow: VFR above FL195, or IFR below 1000 ft
+    CruiseLevelOutsideFlightRules(FlightRules, u32),
+    /// The departure and destination aerodromes are the same. Legitimate for local flights, but often a mistake
+    DepartureIsDestination(String),
+    /// No time enroute was filed
+    MissingEnrouteTime,
+    /// No fuel endurance was filed
+    MissingFuelTime,
+    /// The fuel endurance is shorter than the time enroute
+    InsufficientFuel,
+}
+
+/// The convention an aircraft type field is written in, as detected by [`crate::FlightPlan::format_kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlightPlanFormat {
+    /// ICAO equipment and surveillance codes after the wake category, such as `B738/M-SDE2E3FGIJ1RWXY/LB1`
+    Icao,
+    /// A legacy FAA equipment suffix, such as `B738/L` or `H/B744/L`
+    Faa,
+    /// No equipment information, such as a bare `B738`, or a form not recognised
+    Unknown,
+}
+
+/// The mode a pilot's transponder is operating in
+///
+/// Parses from either its wire value (`S`, `N` or `Y`) or, case-insensitively, `standby`, `modec` or `ident`.
+///
+/// # Example
+/// ```
+/// use fsd_interface::TransponderMode;
+/// assert!(matches!("S".parse(), Ok(TransponderMode::Standby)));
+/// assert!(matches!("standby".parse(), Ok(TransponderMode::Standby)));
+/// assert!(matches!("N".parse(), Ok(TransponderMode::ModeC)));
+/// assert!(matches!("ModeC".parse(), Ok(TransponderMode::ModeC)));
+/// assert!(matches!("Y".parse(), Ok(TransponderMode::Ident)));
+/// assert!(matches!("ident".parse(), Ok(TransponderMode::Ident)));
+/// assert!("X".parse::<TransponderMode>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransponderMode {
     Standby,
     ModeC,
@@ -271,7 +580,12 @@ impl FromStr for TransponderMode {
             "S" => Ok(TransponderMode::Standby),
             "N" => Ok(TransponderMode::ModeC),
             "Y" => Ok(TransponderMode::Ident),
-            _ => Err(FsdMessageParseError::InvalidTransponderMode(s.to_string())),
+            _ => match s.to_lowercase().as_str() {
+                "standby" => Ok(TransponderMode::Standby),
+                "modec" => Ok(TransponderMode::ModeC),
+                "ident" => Ok(TransponderMode::Ident),
+                _ => Err(FsdMessageParseError::InvalidTransponderMode(s.to_string())),
+            },
         }
     }
 }
@@ -285,7 +599,527 @@ impl Display for TransponderMode {
     }
 }
 
-#[derive(Debug)]
+/// Controls how tolerant the parser is of messages that don't strictly conform to the protocol
+///
+/// [`parse_message_with_mode`][crate::parse_message_with_mode] passes the mode on to the parsers of the individual
+/// messages which take one, such as [`FlightPlanMessage::parse`]. Their [`TryFrom`] impls parse strictly.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message_with_mode, ClientQueryType, FsdMessageType, ParseMode, PilotRating};
+/// let message = "$CQEGKK_APP:EGLL_N_TWR:XY:BAW123:42";
+///
+/// let parsed = parse_message_with_mode(message, ParseMode::Lenient).unwrap();
+/// let FsdMessageType::ClientQueryMessage(query) = parsed else {
+///     panic!("expected a client query");
+/// };
+/// let ClientQueryType::Unknown { ref token, ref fields } = query.query_type else {
+///     panic!("expected an unknown query type");
+/// };
+/// assert_eq!("XY", token);
+/// assert_eq!(vec!["BAW123", "42"], *fields);
+/// assert_eq!(message, query.to_string());
+///
+/// assert!(parse_message_with_mode(message, ParseMode::Strict).is_err());
+///
+/// // A flight plan missing its alternate, remarks and route, and one with a colon in its route
+/// let short = "$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0";
+/// let FsdMessageType::FlightPlanMessage(plan) = parse_message_with_mode(short, ParseMode::Lenient).unwrap() else {
+///     panic!("expected a flight plan");
+/// };
+/// assert_eq!("LIRF", plan.flight_plan.destination);
+/// assert!(plan.flight_plan.route.is_empty());
+/// assert!(parse_message_with_mode(short, ParseMode::Strict).is_err());
+///
+/// let long = "$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR:UL612";
+/// let FsdMessageType::FlightPlanMessage(plan) = parse_message_with_mode(long, ParseMode::Lenient).unwrap() else {
+///     panic!("expected a flight plan");
+/// };
+/// assert_eq!("MIMFO Y312 DVR:UL612", plan.flight_plan.route);
+/// assert!(parse_message_with_mode(long, ParseMode::Strict).is_err());
+///
+/// // A flight plan must always get as far as its destination
+/// assert!(parse_message_with_mode("$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000", ParseMode::Lenient).is_err());
+///
+/// // A pilot position update with a missing or invalid rating
+/// let position = "@N:BAW123:7000::51.47000:-0.45000:83:0:4290769920:-8";
+/// let FsdMessageType::PilotPositionUpdateMessage(update) = parse_message_with_mode(position, ParseMode::Lenient).unwrap() else {
+///     panic!("expected a pilot position update");
+/// };
+/// assert_eq!(PilotRating::Unknown, update.rating);
+/// assert_eq!("BAW123", update.callsign);
+/// assert!(parse_message_with_mode(position, ParseMode::Strict).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Rejects anything the crate does not recognise, flight plans without exactly the expected number of fields,
+    /// and client handshakes whose client ID is not exactly 4 lowercase hex digits
+    Strict,
+    /// Preserves unrecognised client query and shared state types so that they can be forwarded verbatim. Flight
+    /// plans which stop short after their destination have the missing fields left empty, and any extra fields are
+    /// taken to be part of the route. Pilot position updates with a missing or invalid rating are read with
+    /// [`PilotRating::Unknown`], and ATC position updates with a malformed elevation are read with an elevation of 0.
+    /// Client IDs in uppercase or with fewer than 4 hex digits are accepted.
+    #[default]
+    Lenient,
+}
+
+/// The reply expected to a request, as given by [`FsdMessageType::expects_reply`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReplyKind {
+    /// A `$PO` answering a `$PI`
+    Pong,
+    /// A `$ZR` answering a `$ZC`
+    AuthenticationResponse,
+    /// A `$AR` answering a `$AX`
+    MetarResponse,
+    /// A `#SB ... PI` answering a `#SB ... PIR`
+    PlaneInfoResponse,
+    /// A `$CR ... C?` answering a `$CQ ... C?`
+    Com1Freq,
+    /// The `$CR ... ATIS` lines answering a `$CQ ... ATIS`
+    Atis,
+    /// A `$CR ... RN` answering a `$CQ ... RN`
+    RealName,
+    /// A `$CR ... CAPS` answering a `$CQ ... CAPS`
+    Capabilities,
+    /// A `$CR ... IP` answering a `$CQ ... IP`
+    PublicIP,
+    /// A `$CR ... ATC` answering a `$CQ ... ATC`
+    IsValidATC,
+    /// A `$CQ ... ACC` carrying a config, answering a `$CQ ... ACC` request
+    AircraftConfiguration,
+}
+
+/// The broad group a message type belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageCategory {
+    /// Connecting, registering, authenticating, keeping the connection alive and disconnecting
+    Session,
+    /// Pilot and ATC position updates
+    Position,
+    /// Private, broadcast and frequency text messages
+    Text,
+    FlightPlan,
+    /// Client queries, their responses and `#SB` plane information
+    Query,
+    /// Handoffs and shared state between ATC clients
+    Coordination,
+    Weather,
+    /// Errors reported by the server
+    Error,
+}
+
+/// Describes one type of FSD message, as listed in [`PREFIX_TABLE`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageKind {
+    /// The name of the corresponding [`FsdMessageType`] variant
+    pub name: &'static str,
+    pub category: MessageCategory,
+    /// The fewest colon-delimited fields, including the one carrying the prefix, that a valid message can have
+    pub min_fields: usize,
+}
+
+/// Every message type the crate recognises, keyed by the prefix it starts with on the wire.
+///
+/// This allows tools to classify a line cheaply, without parsing it, for instance to count position updates.
+/// Prefixes are not unique: `#TM` carries both text messages and frequency messages (addressed to a frequency
+/// starting with `@`), and `#SB` carries the plane information messages, told apart by their third field.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, MessageCategory, PREFIX_TABLE};
+/// let lines = [
+///     "#AAEGLL_N_TWR:SERVER:Joe Bloggs:1234567:password:5:101",
+///     "#APBAW123:SERVER:1234567:password:1:101:9:Joe Bloggs",
+///     "#DAEGLL_N_TWR:1234567",
+///     "#DPBAW123:1234567",
+///     "%EGLL_N_TWR:18500:4:50:5:51.47000:-0.45000:0",
+///     "'EGLL_N_TWR:0:51.47000:-0.45000",
+///     "@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8",
+///     "$ZCSERVER:BAW123:6b8ec0a3b1b6a1a2",
+///     "$ZRBAW123:SERVER:6b8ec0a3b1b6a1a2",
+///     "#TMEGLL_N_TWR:BAW123:Contact Heathrow Director",
+///     "#TMEGLL_N_TWR:@18500:Frequency change approved",
+///     "$XXSERVER:BAW123:uk.vatsim.net",
+///     "$DISERVER:CLIENT:VATSIM FSD V3.43:de1e",
+///     "$IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890",
+///     "$SFSERVER:BAW123:1",
+///     "#STBAW123:51.4700000:-0.4500000:83.00:83.00:4290769920:0.0000",
+///     "#SLBAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100",
+///     "^BAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100",
+///     "$!!SERVER:BAW123:Spamming",
+///     "$AXBAW123:SERVER:METAR:EGLL",
+///     "$ARSERVER:BAW123:METAR:EGLL 141250Z 24012KT 9999 FEW035 17/09 Q1021",
+///     "$PIEGLL_N_TWR:BAW123:1234567",
+///     "$POBAW123:EGLL_N_TWR:1234567",
+///     "#SBEZY38UB:BAW123:PIR",
+///     "#SBBAW123:EZY38UB:PI:GEN:EQUIPMENT=B738:AIRLINE=BAW",
+///     "$ERSERVER:BAW123:007:N123:No such callsign",
+///     "$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR",
+///     "$AMEGLL_N_TWR:SERVER:BAW123:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR",
+///     "#SBEZY38UB:BAW123:FSIPIR",
+///     "#SBBAW123:EZY38UB:FSIPI",
+///     "#DLSERVER:*",
+///     "$CQEHAM_GND:@94835:WH:KLM167",
+///     "$CREGKK_APP:EGLL_N_TWR:CAPS:ATCINFO=1",
+///     "$HOEGLL_N_TWR:EGLL_S_TWR:BAW123",
+///     "$HAEGLL_S_TWR:EGLL_N_TWR:BAW123",
+///     "#PCEGLL_N_TWR:EGLL_S_TWR:CCP:ID:BAW123",
+/// ];
+/// for line in lines {
+///     let kind = parse_message(line).unwrap().kind();
+///     assert!(
+///         PREFIX_TABLE.iter().any(|(prefix, entry)| *entry == kind && line.starts_with(prefix)),
+///         "{}",
+///         line
+///     );
+///     assert!(line.split(':').count() >= kind.min_fields, "{}", line);
+/// }
+/// assert_eq!(lines.len(), PREFIX_TABLE.len());
+///
+/// let lines = ["@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8", "$CQEHAM_GND:@94835:WH:KLM167"];
+/// let position_updates = lines
+///     .iter()
+///     .filter(|line| {
+///         PREFIX_TABLE
+///             .iter()
+///             .any(|(prefix, kind)| kind.category == MessageCategory::Position && line.starts_with(prefix))
+///     })
+///     .count();
+/// assert_eq!(1, position_updates);
+/// ```
+pub const PREFIX_TABLE: &[(&str, MessageKind)] = &[
+    (
+        "#AA",
+        MessageKind {
+            name: "AtcRegisterMessage",
+            category: MessageCategory::Session,
+            min_fields: 7,
+        },
+    ),
+    (
+        "#AP",
+        MessageKind {
+            name: "PilotRegisterMessage",
+            category: MessageCategory::Session,
+            min_fields: 7,
+        },
+    ),
+    (
+        "#DA",
+        MessageKind {
+            name: "AtcDeregisterMessage",
+            category: MessageCategory::Session,
+            min_fields: 2,
+        },
+    ),
+    (
+        "#DP",
+        MessageKind {
+            name: "PilotDeregisterMessage",
+            category: MessageCategory::Session,
+            min_fields: 2,
+        },
+    ),
+    (
+        "%",
+        MessageKind {
+            name: "AtcPositionUpdateMessage",
+            category: MessageCategory::Position,
+            min_fields: 7,
+        },
+    ),
+    (
+        "'",
+        MessageKind {
+            name: "AtcSecondaryVisCentreMessage",
+            category: MessageCategory::Position,
+            min_fields: 4,
+        },
+    ),
+    (
+        "@",
+        MessageKind {
+            name: "PilotPositionUpdateMessage",
+            category: MessageCategory::Position,
+            min_fields: 10,
+        },
+    ),
+    (
+        "$ZC",
+        MessageKind {
+            name: "AuthenticationChallengeMessage",
+            category: MessageCategory::Session,
+            min_fields: 3,
+        },
+    ),
+    (
+        "$ZR",
+        MessageKind {
+            name: "AuthenticationResponseMessage",
+            category: MessageCategory::Session,
+            min_fields: 3,
+        },
+    ),
+    (
+        "#TM",
+        MessageKind {
+            name: "TextMessage",
+            category: MessageCategory::Text,
+            min_fields: 3,
+        },
+    ),
+    (
+        "#TM",
+        MessageKind {
+            name: "FrequencyMessage",
+            category: MessageCategory::Text,
+            min_fields: 3,
+        },
+    ),
+    (
+        "$XX",
+        MessageKind {
+            name: "ChangeServerMessage",
+            category: MessageCategory::Session,
+            min_fields: 3,
+        },
+    ),
+    (
+        "$DI",
+        MessageKind {
+            name: "InitialServerHandshakeMessage",
+            category: MessageCategory::Session,
+            min_fields: 3,
+        },
+    ),
+    (
+        "$ID",
+        MessageKind {
+            name: "InitialClientHandshakeMessage",
+            category: MessageCategory::Session,
+            min_fields: 8,
+        },
+    ),
+    (
+        "$SF",
+        MessageKind {
+            name: "SendFastPositionUpdatesMessage",
+            category: MessageCategory::Position,
+            min_fields: 3,
+        },
+    ),
+    (
+        "#ST",
+        MessageKind {
+            name: "VelocityPositionStoppedMessage",
+            category: MessageCategory::Position,
+            min_fields: 6,
+        },
+    ),
+    (
+        "#SL",
+        MessageKind {
+            name: "VelocityPositionSlowMessage",
+            category: MessageCategory::Position,
+            min_fields: 12,
+        },
+    ),
+    (
+        "^",
+        MessageKind {
+            name: "VelocityPositionFastMessage",
+            category: MessageCategory::Position,
+            min_fields: 12,
+        },
+    ),
+    (
+        "$!!",
+        MessageKind {
+            name: "KillMessage",
+            category: MessageCategory::Session,
+            min_fields: 2,
+        },
+    ),
+    (
+        "$AX",
+        MessageKind {
+            name: "MetarRequestMessage",
+            category: MessageCategory::Weather,
+            min_fields: 4,
+        },
+    ),
+    (
+        "$AR",
+        MessageKind {
+            name: "MetarResponseMessage",
+            category: MessageCategory::Weather,
+            min_fields: 4,
+        },
+    ),
+    (
+        "$PI",
+        MessageKind {
+            name: "PingMessage",
+            category: MessageCategory::Session,
+            min_fields: 3,
+        },
+    ),
+    (
+        "$PO",
+        MessageKind {
+            name: "PongMessage",
+            category: MessageCategory::Session,
+            min_fields: 3,
+        },
+    ),
+    (
+        "#SB",
+        MessageKind {
+            name: "PlaneInfoRequestMessage",
+            category: MessageCategory::Query,
+            min_fields: 3,
+        },
+    ),
+    (
+        "#SB",
+        MessageKind {
+            name: "PlaneInfoResponseMessage",
+            category: MessageCategory::Query,
+            min_fields: 5,
+        },
+    ),
+    (
+        "$ER",
+        MessageKind {
+            name: "FsdErrorMessage",
+            category: MessageCategory::Error,
+            min_fields: 5,
+        },
+    ),
+    (
+        "$FP",
+        MessageKind {
+            name: "FlightPlanMessage",
+            category: MessageCategory::FlightPlan,
+            min_fields: 17,
+        },
+    ),
+    (
+        "$AM",
+        MessageKind {
+            name: "FlightPlanAmendmentMessage",
+            category: MessageCategory::FlightPlan,
+            min_fields: 18,
+        },
+    ),
+    (
+        "#SB",
+        MessageKind {
+            name: "FSInnPlaneInformationRequestMessage",
+            category: MessageCategory::Query,
+            min_fields: 3,
+        },
+    ),
+    (
+        "#SB",
+        MessageKind {
+            name: "FSInnPlaneInformationResponseMessage",
+            category: MessageCategory::Query,
+            min_fields: 3,
+        },
+    ),
+    (
+        "#DL",
+        MessageKind {
+            name: "ServerHeartbeat",
+            category: MessageCategory::Session,
+            min_fields: 2,
+        },
+    ),
+    (
+        "$CQ",
+        MessageKind {
+            name: "ClientQueryMessage",
+            category: MessageCategory::Query,
+            min_fields: 3,
+        },
+    ),
+    (
+        "$CR",
+        MessageKind {
+            name: "ClientQueryResponseMessage",
+            category: MessageCategory::Query,
+            min_fields: 4,
+        },
+    ),
+    (
+        "$HO",
+        MessageKind {
+            name: "HandoffOfferMessage",
+            category: MessageCategory::Coordination,
+            min_fields: 3,
+        },
+    ),
+    (
+        "$HA",
+        MessageKind {
+            name: "HandoffAcceptMessage",
+            category: MessageCategory::Coordination,
+            min_fields: 3,
+        },
+    ),
+    (
+        "#PC",
+        MessageKind {
+            name: "SharedStateMessage",
+            category: MessageCategory::Coordination,
+            min_fields: 4,
+        },
+    ),
+];
+
+/// A parsed FSD message.
+///
+/// With the `serde` feature, every message type can be serialised field by field. The message is tagged with its
+/// variant name in a `type` field, and enums carrying data within a message are tagged the same way with their
+/// contents in `data`. To record messages as their wire strings instead, see the `fsd_wire` module.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use fsd_interface::{parse_message, FsdMessageType};
+/// let message = parse_message("$CQEHAM_GND:@94835:WH:KLM167").unwrap();
+/// let json = serde_json::to_value(&message).unwrap();
+/// assert_eq!("ClientQueryMessage", json["type"]);
+/// assert_eq!("WhoHas", json["query_type"]["type"]);
+/// assert_eq!("KLM167", json["query_type"]["data"]);
+/// assert_eq!(message, serde_json::from_value(json).unwrap());
+///
+/// let message = parse_message("@N:BAW123:7700:1:51.47000:-0.45000:83:0:4290769920:-8").unwrap();
+/// let json = serde_json::to_string(&message).unwrap();
+/// assert_eq!(message.to_string(), serde_json::from_str::<FsdMessageType>(&json).unwrap().to_string());
+///
+/// // Transponder codes are written as their 4 digits
+/// let json = serde_json::to_value(&message).unwrap();
+/// assert_eq!("7700", json["transponder_code"]);
+///
+/// // Fields are checked in the same way as when parsing
+/// let message = parse_message("%EGLL_TWR:18500:4:100:5:51.47000:-0.46000:0").unwrap();
+/// let mut json = serde_json::to_value(&message).unwrap();
+/// assert_eq!(serde_json::json!([118, 500]), json["frequencies"][0]);
+/// json["frequencies"][0] = serde_json::json!([108, 0]);
+/// assert!(serde_json::from_value::<FsdMessageType>(json).is_err());
+///
+/// let mut json = serde_json::to_value(parse_message("@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8").unwrap()).unwrap();
+/// json["transponder_code"] = serde_json::json!("7800");
+/// assert!(serde_json::from_value::<FsdMessageType>(json).is_err());
+/// # }
+/// ```
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum FsdMessageType {
     AtcRegisterMessage(AtcRegisterMessage),
     PilotRegisterMessage(PilotRegisterMessage),
@@ -317,7 +1151,7 @@ pub enum FsdMessageType {
     FlightPlanAmendmentMessage(FlightPlanAmendmentMessage),
     FSInnPlaneInformationRequestMessage,
     FSInnPlaneInformationResponseMessage,
-    ServerHeartbeat,
+    ServerHeartbeat(ServerHeartbeatMessage),
     ClientQueryMessage(ClientQueryMessage),
     ClientQueryResponseMessage(ClientQueryResponseMessage),
     HandoffOfferMessage(HandoffOfferMessage),
@@ -326,7 +1160,419 @@ pub enum FsdMessageType {
 }
 
 impl FsdMessageType {
-    pub(crate) fn identify(message: &str) -> Result<FsdMessageType, FsdMessageParseError> {
+    /// Whether the message is one of the high volume, periodically repeated messages (position updates and
+    /// `#SB` plane information), which a busy server may deprioritise or drop because a fresher one will follow.
+    ///
+    /// Handshakes, registrations, text, flight plans and every other message return `false` and should never be dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::parse_message;
+    /// let classification = [
+    ///     ("@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8", true),
+    ///     ("^BAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100", true),
+    ///     ("#SLBAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100", true),
+    ///     ("%EGLL_N_TWR:18500:4:50:5:51.47000:-0.45000:0", true),
+    ///     ("#SBBAW123:EZY38UB:PI:GEN:EQUIPMENT=B738:AIRLINE=BAW", true),
+    ///     ("#SBEZY38UB:BAW123:PIR", true),
+    ///     ("$IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890", false),
+    ///     ("#APBAW123:SERVER:1234567:password:1:101:9:Joe Bloggs", false),
+    ///     ("#TMEGLL_N_TWR:BAW123:Contact Heathrow Director", false),
+    ///     ("$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR", false),
+    ///     ("$CQEHAM_GND:@94835:WH:KLM167", false),
+    ///     ("#DPBAW123:1234567", false),
+    /// ];
+    /// for (line, expected) in classification {
+    ///     assert_eq!(expected, parse_message(line).unwrap().is_high_frequency(), "{}", line);
+    /// }
+    /// ```
+    pub fn is_high_frequency(&self) -> bool {
+        matches!(
+            self,
+            FsdMessageType::PilotPositionUpdateMessage(_)
+                | FsdMessageType::VelocityPositionFastMessage(_)
+                | FsdMessageType::VelocityPositionSlowMessage(_)
+                | FsdMessageType::VelocityPositionStoppedMessage(_)
+                | FsdMessageType::AtcPositionUpdateMessage(_)
+                | FsdMessageType::AtcSecondaryVisCentreMessage(_)
+                | FsdMessageType::PlaneInfoRequestMessage(_)
+                | FsdMessageType::PlaneInfoResponseMessage(_)
+                | FsdMessageType::FSInnPlaneInformationRequestMessage
+                | FsdMessageType::FSInnPlaneInformationResponseMessage
+        )
+    }
+
+    /// The entry in [`PREFIX_TABLE`] describing this type of message
+    pub fn kind(&self) -> MessageKind {
+        let name = match self {
+            FsdMessageType::AtcRegisterMessage(_) => "AtcRegisterMessage",
+            FsdMessageType::PilotRegisterMessage(_) => "PilotRegisterMessage",
+            FsdMessageType::AtcDeregisterMessage(_) => "AtcDeregisterMessage",
+            FsdMessageType::PilotDeregisterMessage(_) => "PilotDeregisterMessage",
+            FsdMessageType::AtcPositionUpdateMessage(_) => "AtcPositionUpdateMessage",
+            FsdMessageType::AtcSecondaryVisCentreMessage(_) => "AtcSecondaryVisCentreMessage",
+            FsdMessageType::PilotPositionUpdateMessage(_) => "PilotPositionUpdateMessage",
+            FsdMessageType::AuthenticationChallengeMessage(_) => "AuthenticationChallengeMessage",
+            FsdMessageType::AuthenticationResponseMessage(_) => "AuthenticationResponseMessage",
+            FsdMessageType::TextMessage(_) => "TextMessage",
+            FsdMessageType::FrequencyMessage(_) => "FrequencyMessage",
+            FsdMessageType::ChangeServerMessage(_) => "ChangeServerMessage",
+            FsdMessageType::InitialServerHandshakeMessage(_) => "InitialServerHandshakeMessage",
+            FsdMessageType::InitialClientHandshakeMessage(_) => "InitialClientHandshakeMessage",
+            FsdMessageType::SendFastPositionUpdatesMessage(_) => "SendFastPositionUpdatesMessage",
+            FsdMessageType::VelocityPositionStoppedMessage(_) => "VelocityPositionStoppedMessage",
+            FsdMessageType::VelocityPositionSlowMessage(_) => "VelocityPositionSlowMessage",
+            FsdMessageType::VelocityPositionFastMessage(_) => "VelocityPositionFastMessage",
+            FsdMessageType::KillMessage(_) => "KillMessage",
+            FsdMessageType::MetarRequestMessage(_) => "MetarRequestMessage",
+            FsdMessageType::MetarResponseMessage(_) => "MetarResponseMessage",
+            FsdMessageType::PingMessage(_) => "PingMessage",
+            FsdMessageType::PongMessage(_) => "PongMessage",
+            FsdMessageType::PlaneInfoRequestMessage(_) => "PlaneInfoRequestMessage",
+            FsdMessageType::PlaneInfoResponseMessage(_) => "PlaneInfoResponseMessage",
+            FsdMessageType::FsdErrorMessage(_) => "FsdErrorMessage",
+            FsdMessageType::FlightPlanMessage(_) => "FlightPlanMessage",
+            FsdMessageType::FlightPlanAmendmentMessage(_) => "FlightPlanAmendmentMessage",
+            FsdMessageType::FSInnPlaneInformationRequestMessage => {
+                "FSInnPlaneInformationRequestMessage"
+            }
+            FsdMessageType::FSInnPlaneInformationResponseMessage => {
+                "FSInnPlaneInformationResponseMessage"
+            }
+            FsdMessageType::ServerHeartbeat(_) => "ServerHeartbeat",
+            FsdMessageType::ClientQueryMessage(_) => "ClientQueryMessage",
+            FsdMessageType::ClientQueryResponseMessage(_) => "ClientQueryResponseMessage",
+            FsdMessageType::HandoffOfferMessage(_) => "HandoffOfferMessage",
+            FsdMessageType::HandoffAcceptMessage(_) => "HandoffAcceptMessage",
+            FsdMessageType::SharedStateMessage(_) => "SharedStateMessage",
+        };
+        PREFIX_TABLE
+            .iter()
+            .find(|(_, kind)| kind.name == name)
+            .map(|(_, kind)| *kind)
+            .expect("every message type is listed in PREFIX_TABLE")
+    }
+
+    /// The callsign of the client, or `SERVER`, that sent the message.
+    ///
+    /// `None` for the FSInn plane information variants, which carry no data.
+    pub fn sender(&self) -> Option<&str> {
+        match self {
+            FsdMessageType::AtcRegisterMessage(m) => Some(&m.from),
+            FsdMessageType::PilotRegisterMessage(m) => Some(&m.from),
+            FsdMessageType::AtcDeregisterMessage(m) => Some(&m.from),
+            FsdMessageType::PilotDeregisterMessage(m) => Some(&m.from),
+            FsdMessageType::AtcPositionUpdateMessage(m) => Some(&m.callsign),
+            FsdMessageType::AtcSecondaryVisCentreMessage(m) => Some(&m.callsign),
+            FsdMessageType::PilotPositionUpdateMessage(m) => Some(&m.callsign),
+            FsdMessageType::AuthenticationChallengeMessage(m) => Some(&m.from),
+            FsdMessageType::AuthenticationResponseMessage(m) => Some(&m.from),
+            FsdMessageType::TextMessage(m) => Some(&m.from),
+            FsdMessageType::FrequencyMessage(m) => Some(&m.from),
+            FsdMessageType::ChangeServerMessage(m) => Some(&m.from),
+            FsdMessageType::InitialServerHandshakeMessage(m) => Some(&m.from),
+            FsdMessageType::InitialClientHandshakeMessage(m) => Some(&m.from),
+            FsdMessageType::SendFastPositionUpdatesMessage(m) => Some(&m.from),
+            FsdMessageType::VelocityPositionStoppedMessage(m) => Some(&m.from),
+            FsdMessageType::VelocityPositionSlowMessage(m) => Some(&m.from),
+            FsdMessageType::VelocityPositionFastMessage(m) => Some(&m.from),
+            FsdMessageType::KillMessage(m) => Some(&m.from),
+            FsdMessageType::MetarRequestMessage(m) => Some(&m.from),
+            FsdMessageType::MetarResponseMessage(m) => Some(&m.from),
+            FsdMessageType::PingMessage(m) => Some(&m.from),
+            FsdMessageType::PongMessage(m) => Some(&m.from),
+            FsdMessageType::PlaneInfoRequestMessage(m) => Some(&m.from),
+            FsdMessageType::PlaneInfoResponseMessage(m) => Some(&m.from),
+            FsdMessageType::FsdErrorMessage(m) => Some(&m.from),
+            FsdMessageType::FlightPlanMessage(m) => Some(&m.callsign),
+            FsdMessageType::FlightPlanAmendmentMessage(m) => Some(&m.from),
+            FsdMessageType::ClientQueryMessage(m) => Some(&m.from),
+            FsdMessageType::ClientQueryResponseMessage(m) => Some(&m.from),
+            FsdMessageType::HandoffOfferMessage(m) => Some(&m.from),
+            FsdMessageType::HandoffAcceptMessage(m) => Some(&m.from),
+            FsdMessageType::SharedStateMessage(m) => Some(&m.from),
+            FsdMessageType::ServerHeartbeat(m) => Some(&m.from),
+            FsdMessageType::FSInnPlaneInformationRequestMessage
+            | FsdMessageType::FSInnPlaneInformationResponseMessage => None,
+        }
+    }
+
+    /// The callsign the message is addressed to, which may also be `SERVER`, a broadcast recipient such as `*` or
+    /// a special frequency such as `@94835`.
+    ///
+    /// `None` for messages without a single recipient: deregistrations, position updates, frequency messages
+    /// (see [`FrequencyMessage::to`]) and the variants which carry no data.
+    pub fn recipient(&self) -> Option<&str> {
+        match self {
+            FsdMessageType::AtcRegisterMessage(m) => Some(&m.to),
+            FsdMessageType::PilotRegisterMessage(m) => Some(&m.to),
+            FsdMessageType::AuthenticationChallengeMessage(m) => Some(&m.to),
+            FsdMessageType::AuthenticationResponseMessage(m) => Some(&m.to),
+            FsdMessageType::TextMessage(m) => Some(&m.to),
+            FsdMessageType::ChangeServerMessage(m) => Some(&m.to),
+            FsdMessageType::InitialServerHandshakeMessage(m) => Some(&m.to),
+            FsdMessageType::InitialClientHandshakeMessage(m) => Some(&m.to),
+            FsdMessageType::SendFastPositionUpdatesMessage(m) => Some(&m.to),
+            FsdMessageType::KillMessage(m) => Some(&m.to),
+            FsdMessageType::MetarRequestMessage(m) => Some(&m.to),
+            FsdMessageType::MetarResponseMessage(m) => Some(&m.to),
+            FsdMessageType::PingMessage(m) => Some(&m.to),
+            FsdMessageType::PongMessage(m) => Some(&m.to),
+            FsdMessageType::PlaneInfoRequestMessage(m) => Some(&m.to),
+            FsdMessageType::PlaneInfoResponseMessage(m) => Some(&m.to),
+            FsdMessageType::FsdErrorMessage(m) => Some(&m.to),
+            FsdMessageType::FlightPlanMessage(m) => Some(&m.to),
+            FsdMessageType::FlightPlanAmendmentMessage(m) => Some(&m.to),
+            FsdMessageType::ClientQueryMessage(m) => Some(&m.to),
+            FsdMessageType::ClientQueryResponseMessage(m) => Some(&m.to),
+            FsdMessageType::HandoffOfferMessage(m) => Some(&m.to),
+            FsdMessageType::HandoffAcceptMessage(m) => Some(&m.to),
+            FsdMessageType::SharedStateMessage(m) => Some(&m.to),
+            FsdMessageType::ServerHeartbeat(m) => Some(&m.to),
+            FsdMessageType::AtcDeregisterMessage(_)
+            | FsdMessageType::PilotDeregisterMessage(_)
+            | FsdMessageType::AtcPositionUpdateMessage(_)
+            | FsdMessageType::AtcSecondaryVisCentreMessage(_)
+            | FsdMessageType::PilotPositionUpdateMessage(_)
+            | FsdMessageType::FrequencyMessage(_)
+            | FsdMessageType::VelocityPositionStoppedMessage(_)
+            | FsdMessageType::VelocityPositionSlowMessage(_)
+            | FsdMessageType::VelocityPositionFastMessage(_)
+            | FsdMessageType::FSInnPlaneInformationRequestMessage
+            | FsdMessageType::FSInnPlaneInformationResponseMessage => None,
+        }
+    }
+
+    /// Whether the message was sent by the server, going by its sender being [`SERVER_CALLSIGN`] in any case
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::parse_message;
+    /// let handshake = parse_message("$DISERVER:CLIENT:VATSIM FSD V3.43:de1e").unwrap();
+    /// assert!(handshake.is_from_server());
+    /// assert!(!handshake.is_to_server());
+    ///
+    /// let registration = parse_message("#APBAW123:server:1234567:password:1:101:9:Joe Bloggs").unwrap();
+    /// assert!(!registration.is_from_server());
+    /// assert!(registration.is_to_server());
+    ///
+    /// let text = parse_message("#TMEGLL_N_TWR:BAW123:Contact Heathrow Director").unwrap();
+    /// assert!(!text.is_from_server());
+    /// assert!(!text.is_to_server());
+    /// ```
+    pub fn is_from_server(&self) -> bool {
+        self.sender()
+            .is_some_and(|sender| sender.eq_ignore_ascii_case(SERVER_CALLSIGN))
+    }
+
+    /// Whether the message is addressed to the server, going by its recipient being [`SERVER_CALLSIGN`] in any case
+    pub fn is_to_server(&self) -> bool {
+        self.recipient()
+            .is_some_and(|recipient| recipient.eq_ignore_ascii_case(SERVER_CALLSIGN))
+    }
+
+    /// The kind of reply the recipient is expected to send to this message, if any. See
+    /// [`PendingRequests`][crate::PendingRequests] to track replies that have not yet arrived.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, ReplyKind};
+    /// assert_eq!(Some(ReplyKind::Pong), parse_message("$PISERVER:BAW123:1234").unwrap().expects_reply());
+    /// assert_eq!(Some(ReplyKind::Capabilities), parse_message("$CQEGLL_N_TWR:BAW123:CAPS").unwrap().expects_reply());
+    /// assert_eq!(None, parse_message("$CQEGLL_N_TWR:@94835:WH:BAW123").unwrap().expects_reply());
+    /// assert_eq!(None, parse_message("$POBAW123:SERVER:1234").unwrap().expects_reply());
+    /// ```
+    pub fn expects_reply(&self) -> Option<ReplyKind> {
+        match self {
+            FsdMessageType::PingMessage(_) => Some(ReplyKind::Pong),
+            FsdMessageType::AuthenticationChallengeMessage(_) => {
+                Some(ReplyKind::AuthenticationResponse)
+            }
+            FsdMessageType::MetarRequestMessage(_) => Some(ReplyKind::MetarResponse),
+            FsdMessageType::PlaneInfoRequestMessage(_) => Some(ReplyKind::PlaneInfoResponse),
+            FsdMessageType::ClientQueryMessage(m) => match m.query_type {
+                ClientQueryType::Com1Freq => Some(ReplyKind::Com1Freq),
+                ClientQueryType::ATIS => Some(ReplyKind::Atis),
+                ClientQueryType::RealName => Some(ReplyKind::RealName),
+                ClientQueryType::Capabilities => Some(ReplyKind::Capabilities),
+                ClientQueryType::PublicIP => Some(ReplyKind::PublicIP),
+                ClientQueryType::IsValidATC(_) => Some(ReplyKind::IsValidATC),
+                ClientQueryType::AircraftConfigurationRequest => {
+                    Some(ReplyKind::AircraftConfiguration)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The kind of reply this message is, if it answers a message for which [`FsdMessageType::expects_reply`] is
+    /// [`Some`]
+    pub fn reply_kind(&self) -> Option<ReplyKind> {
+        match self {
+            FsdMessageType::PongMessage(_) => Some(ReplyKind::Pong),
+            FsdMessageType::AuthenticationResponseMessage(_) => {
+                Some(ReplyKind::AuthenticationResponse)
+            }
+            FsdMessageType::MetarResponseMessage(_) => Some(ReplyKind::MetarResponse),
+            FsdMessageType::PlaneInfoResponseMessage(_) => Some(ReplyKind::PlaneInfoResponse),
+            FsdMessageType::ClientQueryResponseMessage(m) => match m.response_type {
+                ClientResponseType::Com1Freq(_) => Some(ReplyKind::Com1Freq),
+                ClientResponseType::ATIS(_) => Some(ReplyKind::Atis),
+                ClientResponseType::RealName(..) => Some(ReplyKind::RealName),
+                ClientResponseType::Capabilities(_) => Some(ReplyKind::Capabilities),
+                ClientResponseType::PublicIP(_) => Some(ReplyKind::PublicIP),
+                ClientResponseType::IsValidATC(..) => Some(ReplyKind::IsValidATC),
+            },
+            FsdMessageType::ClientQueryMessage(m) => match m.query_type {
+                ClientQueryType::AircraftConfigurationResponse(_) => {
+                    Some(ReplyKind::AircraftConfiguration)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Uppercases every callsign in the message, wherever it appears outside free text: the sender and recipient,
+    /// and the aircraft or controller a query, response, shared state update or error refers to.
+    ///
+    /// The parsers already uppercase the callsigns they read, but fields set directly may not be.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, ClientQueryType, FsdMessageType, SharedStateType};
+    /// let mut message = parse_message("$CQegll_n_twr:@94835:HT:baw123:egll_s_twr").unwrap();
+    /// if let FsdMessageType::ClientQueryMessage(query) = &mut message {
+    ///     query.from = String::from("egll_n_twr");
+    ///     query.query_type = ClientQueryType::AcceptHandoff(String::from("baw123"), String::from("Egll_S_Twr"));
+    /// }
+    /// message.normalize_callsigns();
+    /// assert_eq!("$CQEGLL_N_TWR:@94835:HT:BAW123:EGLL_S_TWR", message.to_string());
+    ///
+    /// let mut message = parse_message("#PCEGLL_N_TWR:EGLL_S_TWR:CCP:SC:BAW123:hdg 270").unwrap();
+    /// if let FsdMessageType::SharedStateMessage(state) = &mut message {
+    ///     state.to = String::from("egll_s_twr");
+    ///     state.shared_state_type = SharedStateType::ScratchPad(String::from("baw123"), String::from("hdg 270"));
+    /// }
+    /// message.normalize_callsigns();
+    /// // The scratchpad contents are free text, and are left alone
+    /// assert_eq!("#PCEGLL_N_TWR:EGLL_S_TWR:CCP:SC:BAW123:hdg 270", message.to_string());
+    /// ```
+    pub fn normalize_callsigns(&mut self) {
+        for callsign in self.callsigns_mut() {
+            *callsign = callsign.to_uppercase();
+        }
+    }
+
+    /// Returns the message as a [`DeregisterMessage`] if it is an ATC or pilot deregistration
+    pub fn as_deregister(&self) -> Option<&dyn DeregisterMessage> {
+        match self {
+            FsdMessageType::AtcDeregisterMessage(m) => Some(m),
+            FsdMessageType::PilotDeregisterMessage(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Formats the message for logs and other human-facing output, rather than for sending to a server.
+    ///
+    /// Control characters (such as newlines that a client managed to smuggle into a text message) are escaped,
+    /// and any colon-delimited field longer than 256 characters is cut short with an ellipsis. Use the
+    /// [`Display`] implementation to obtain the wire form.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::TextMessage, parse_message, FsdMessageType};
+    /// let message = FsdMessageType::TextMessage(TextMessage::new("BAW123", "EGLL_N_TWR", "ready\nfor departure"));
+    /// assert_eq!("#TMBAW123:EGLL_N_TWR:ready\\nfor departure", message.to_display_safe());
+    ///
+    /// let remarks = "X".repeat(10 * 1024);
+    /// let line = format!("$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:{remarks}:MIMFO Y312 DVR");
+    /// let message = parse_message(&line).unwrap();
+    /// let safe = message.to_display_safe();
+    /// assert!(safe.len() < 512);
+    /// assert!(safe.contains(&format!(":{}…:", "X".repeat(256))));
+    /// assert!(safe.ends_with(":MIMFO Y312 DVR"));
+    /// assert_eq!(line, message.to_string());
+    /// ```
+    pub fn to_display_safe(&self) -> String {
+        let wire = self.to_string();
+        let mut safe = String::with_capacity(wire.len().min(1024));
+        let mut fields = wire.split(':').peekable();
+        while let Some(field) = fields.next() {
+            for (i, c) in field.chars().enumerate() {
+                if i == DISPLAY_SAFE_MAX_FIELD_LEN {
+                    safe.push('…');
+                    break;
+                }
+                if c.is_control() {
+                    safe.extend(c.escape_default());
+                } else {
+                    safe.push(c);
+                }
+            }
+            if fields.peek().is_some() {
+                safe.push(':');
+            }
+        }
+        safe
+    }
+
+    pub(crate) fn identify_bytes(
+        message: &[u8],
+        mode: ParseMode,
+    ) -> Result<FsdMessageType, FsdMessageParseError> {
+        if let Ok(message) = std::str::from_utf8(message) {
+            return FsdMessageType::identify(message, mode);
+        }
+
+        // Free text fields are the only place a non-UTF-8 byte can legitimately turn up, so only those are
+        // decoded lossily, as Windows-1252
+        let lenient_fields = if message.starts_with(b"#AA") {
+            Some(2..3)
+        } else if message.starts_with(b"#AP") {
+            Some(7..usize::MAX)
+        } else if message.starts_with(b"$FP") {
+            Some(15..16)
+        } else if message.starts_with(b"$AM") {
+            Some(16..17)
+        } else {
+            None
+        };
+        let mut decoded = String::with_capacity(message.len());
+        for (i, field) in message.split(|&b| b == b':').enumerate() {
+            if i > 0 {
+                decoded.push(':');
+            }
+            match std::str::from_utf8(field) {
+                Ok(field) => decoded.push_str(field),
+                Err(_)
+                    if lenient_fields
+                        .as_ref()
+                        .is_some_and(|range| range.contains(&i)) =>
+                {
+                    decoded.push_str(&util::decode_windows_1252(field))
+                }
+                Err(_) => return Err(FsdMessageParseError::InvalidEncoding(i)),
+            }
+        }
+        FsdMessageType::identify(&decoded, mode)
+    }
+
+    pub(crate) fn identify(
+        message: &str,
+        mode: ParseMode,
+    ) -> Result<FsdMessageType, FsdMessageParseError> {
+        FsdMessageType::identify_with_protocol(message, mode, ProtocolRevision::Vatsim2022)
+    }
+
+    /// Parses a message as [`identify`][FsdMessageType::identify] does, reading pilot position updates as sent by
+    /// a client using `protocol`
+    pub(crate) fn identify_with_protocol(
+        message: &str,
+        mode: ParseMode,
+        protocol: ProtocolRevision,
+    ) -> Result<FsdMessageType, FsdMessageParseError> {
         let fields: Vec<&str> = message.split(':').collect();
         if fields.len() < 2 {
             return Err(FsdMessageParseError::UnknownMessageType(
@@ -348,7 +1594,7 @@ impl FsdMessageType {
         }
         if fields[0].starts_with('%') {
             return Ok(Self::AtcPositionUpdateMessage(
-                fields.as_slice().try_into()?,
+                AtcPositionUpdateMessage::parse(&fields, mode)?,
             ));
         }
         if fields[0].starts_with('\'') {
@@ -358,7 +1604,7 @@ impl FsdMessageType {
         }
         if fields[0].starts_with('@') {
             return Ok(Self::PilotPositionUpdateMessage(
-                fields.as_slice().try_into()?,
+                PilotPositionUpdateMessage::parse_with_protocol(&fields, protocol, mode)?,
             ));
         }
         if fields[0].starts_with("$ZC") {
@@ -391,11 +1637,13 @@ impl FsdMessageType {
             return Ok(Self::ChangeServerMessage(fields.as_slice().try_into()?));
         }
         if fields[0].starts_with("$FP") {
-            return Ok(Self::FlightPlanMessage(fields.as_slice().try_into()?));
+            return Ok(Self::FlightPlanMessage(FlightPlanMessage::parse(
+                &fields, mode,
+            )?));
         }
         if fields[0].starts_with("$AM") {
             return Ok(Self::FlightPlanAmendmentMessage(
-                fields.as_slice().try_into()?,
+                FlightPlanAmendmentMessage::parse(&fields, mode)?,
             ));
         }
         if fields[0].starts_with("$DI") {
@@ -404,6 +1652,17 @@ impl FsdMessageType {
             ));
         }
         if fields[0].starts_with("$ID") {
+            if mode == ParseMode::Strict
+                && fields.get(2).is_some_and(|client_id| {
+                    client_id.len() != 4
+                        || !client_id
+                            .chars()
+                            .all(|c| c.is_ascii_digit() || c.is_ascii_lowercase())
+                })
+            {
+                // The ID would not be written back out as it was sent
+                return Err(FsdMessageParseError::InvalidClientID(fields[2].to_string()));
+            }
             return Ok(Self::InitialClientHandshakeMessage(
                 fields.as_slice().try_into()?,
             ));
@@ -419,7 +1678,7 @@ impl FsdMessageType {
             ));
         }
         if fields[0].starts_with("#DL") {
-            return Ok(Self::ServerHeartbeat);
+            return Ok(Self::ServerHeartbeat(fields.as_slice().try_into()?));
         }
         if fields[0].starts_with("#SL") {
             return Ok(Self::VelocityPositionSlowMessage(
@@ -427,7 +1686,13 @@ impl FsdMessageType {
             ));
         }
         if fields[0].starts_with("#PC") {
-            return Ok(Self::SharedStateMessage(fields.as_slice().try_into()?));
+            let shared_state: SharedStateMessage = fields.as_slice().try_into()?;
+            if let (ParseMode::Strict, SharedStateType::Unknown { token, .. }) =
+                (mode, &shared_state.shared_state_type)
+            {
+                return Err(FsdMessageParseError::InvalidSharedStateType(token.clone()));
+            }
+            return Ok(Self::SharedStateMessage(shared_state));
         }
         if fields[0].starts_with('^') {
             return Ok(Self::VelocityPositionFastMessage(
@@ -444,7 +1709,13 @@ impl FsdMessageType {
             return Ok(Self::MetarResponseMessage(fields.as_slice().try_into()?));
         }
         if fields[0].starts_with("$CQ") {
-            return Ok(Self::ClientQueryMessage(fields.as_slice().try_into()?));
+            let client_query: ClientQueryMessage = fields.as_slice().try_into()?;
+            if let (ParseMode::Strict, ClientQueryType::Unknown { token, .. }) =
+                (mode, &client_query.query_type)
+            {
+                return Err(FsdMessageParseError::UnknownMessageType(token.clone()));
+            }
+            return Ok(Self::ClientQueryMessage(client_query));
         }
         if fields[0].starts_with("$CR") {
             return Ok(Self::ClientQueryResponseMessage(
@@ -480,8 +1751,119 @@ impl FsdMessageType {
     }
 }
 
+/// Writes the message in its wire form.
+///
+/// Parsing the written form gives back an equal message, except where the wire format cannot hold everything the
+/// struct does:
+///
+/// - Coordinates in `%`, `'` and `@` messages are written to 5 decimal places, and in `#ST`, `#SL` and `^` messages
+///   to 7. Altitudes are written to whole feet in `@` messages and to 2 decimal places in the velocity messages, and
+///   velocities to 4 decimal places.
+/// - Pitch, bank and heading are packed into 10 bits each, so are only kept to within about 0.35°.
+/// - [`FsdError::Other`][crate::errors::FsdError::Other] is always written with error number 18.
+/// - The FSInn plane information variants, which carry no data, are written as only their identifying token
+///   (`FSIPIR` and `FSIPI`), which cannot be parsed.
+///
+/// # Example
+/// ```
+/// use fsd_interface::parse_message;
+/// let lines = [
+///     "#AAEGLL_N_TWR:SERVER:Joe Bloggs:1234567:password:5:101",
+///     "#APBAW123:SERVER:1234567:password:1:101:9:Joe: Bloggs",
+///     "#DAEGLL_N_TWR:1234567",
+///     "#DPBAW123:1234567",
+///     "%EGLL_N_TWR:18500:4:50:5:51.47000:-0.45000:83",
+///     "'EGLL_N_TWR:1:51.47000:-0.45000",
+///     "@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8",
+///     "$ZCSERVER:BAW123:abcdef0123",
+///     "$ZRBAW123:SERVER:abcdef0123",
+///     "#TMEGLL_N_TWR:BAW123:Contact Heathrow Director: 119.725",
+///     "#TMEGLL_N_TWR:@18500:Frequency message",
+///     "$XXBAW123:SERVER:newserver.example.com",
+///     "$DISERVER:CLIENT:VATSIM FSD V3.53:0123456789abcdef",
+///     "$IDBAW123:SERVER:69d7:vPilot:3:8:1234567:123456789",
+///     "$SFBAW123:SERVER:1",
+///     "#STBAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.00",
+///     "#SLBAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100",
+///     "^BAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100",
+///     "$!!SERVER:BAW123:Kicked: abusive behaviour",
+///     "$AXEGLL_TWR:SERVER:METAR:EGLL",
+///     "$ARSERVER:EGLL_TWR:METAR:EGLL 141250Z 24012KT",
+///     "$PIEGLL_TWR:SERVER:12345",
+///     "$POSERVER:EGLL_TWR:12345",
+///     "#SBEZY38UB:BAW123:PIR",
+///     "#SBBAW123:EZY38UB:PI:GEN:EQUIPMENT=B738:AIRLINE=BAW:LIVERY=BAW",
+///     "$ERSERVER:BAW123:007:BAW456:",
+///     "$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR UL9 KONAN",
+///     "$AMEGLL_N_TWR:SERVER:BAW123:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR",
+///     "#DLSERVER:*:0:0",
+///     "#DLSERVER:*:12:3",
+///     "$CQEHAM_GND:@94835:WH:KLM167",
+///     "$CQEGLL_N_TWR:@94835:HT:BAW123:EGLL_S_TWR",
+///     "$CQEGLL_N_TWR:@94835:TA:BAW123:7000",
+///     "$CQEGLL_N_TWR:@94835:BC:BAW123:4621",
+///     "$CQEGLL_N_TWR:@94835:SC:BAW123:HDG:270",
+///     "$CQEGLL_N_TWR:@94835:GD:KEY:VALUE",
+///     "$CREGLL_N_TWR:BAW123:ATIS:T:Heathrow Tower, information Alpha",
+///     "$CRBAW123:EGLL_N_TWR:CAPS:VERSION=1:ATCINFO=1:MODELDESC=1",
+///     "$HOEGLL_N_TWR:EGLL_S_TWR:BAW123",
+///     "$HAEGLL_S_TWR:EGLL_N_TWR:BAW123",
+///     "#PCEGLL_N_TWR:EGLL_S_TWR:CCP:SC:BAW123:HDG:270",
+///     "#PCEGLL_N_TWR:EGLL_S_TWR:CCP:ZZ:BAW123:1:2",
+/// ];
+/// for line in lines {
+///     let message = parse_message(line).unwrap();
+///     assert_eq!(line, message.to_string());
+///     assert_eq!(message, parse_message(message.to_string()).unwrap());
+/// }
+/// ```
+impl Display for FsdMessageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FsdMessageType::AtcRegisterMessage(m) => m.fmt(f),
+            FsdMessageType::PilotRegisterMessage(m) => m.fmt(f),
+            FsdMessageType::AtcDeregisterMessage(m) => m.fmt(f),
+            FsdMessageType::PilotDeregisterMessage(m) => m.fmt(f),
+            FsdMessageType::AtcPositionUpdateMessage(m) => m.fmt(f),
+            FsdMessageType::AtcSecondaryVisCentreMessage(m) => m.fmt(f),
+            FsdMessageType::PilotPositionUpdateMessage(m) => m.fmt(f),
+            FsdMessageType::AuthenticationChallengeMessage(m) => m.fmt(f),
+            FsdMessageType::AuthenticationResponseMessage(m) => m.fmt(f),
+            FsdMessageType::TextMessage(m) => m.fmt(f),
+            FsdMessageType::FrequencyMessage(m) => m.fmt(f),
+            FsdMessageType::ChangeServerMessage(m) => m.fmt(f),
+            FsdMessageType::InitialServerHandshakeMessage(m) => m.fmt(f),
+            FsdMessageType::InitialClientHandshakeMessage(m) => m.fmt(f),
+            FsdMessageType::SendFastPositionUpdatesMessage(m) => m.fmt(f),
+            FsdMessageType::VelocityPositionStoppedMessage(m) => m.fmt(f),
+            FsdMessageType::VelocityPositionSlowMessage(m) => m.fmt(f),
+            FsdMessageType::VelocityPositionFastMessage(m) => m.fmt(f),
+            FsdMessageType::KillMessage(m) => m.fmt(f),
+            FsdMessageType::MetarRequestMessage(m) => m.fmt(f),
+            FsdMessageType::MetarResponseMessage(m) => m.fmt(f),
+            FsdMessageType::PingMessage(m) => m.fmt(f),
+            FsdMessageType::PongMessage(m) => m.fmt(f),
+            FsdMessageType::PlaneInfoRequestMessage(m) => m.fmt(f),
+            FsdMessageType::PlaneInfoResponseMessage(m) => m.fmt(f),
+            FsdMessageType::FsdErrorMessage(m) => m.fmt(f),
+            FsdMessageType::FlightPlanMessage(m) => m.fmt(f),
+            FsdMessageType::FlightPlanAmendmentMessage(m) => m.fmt(f),
+            FsdMessageType::FSInnPlaneInformationRequestMessage => write!(f, "FSIPIR"),
+            FsdMessageType::FSInnPlaneInformationResponseMessage => write!(f, "FSIPI"),
+            FsdMessageType::ServerHeartbeat(m) => m.fmt(f),
+            FsdMessageType::ClientQueryMessage(m) => m.fmt(f),
+            FsdMessageType::ClientQueryResponseMessage(m) => m.fmt(f),
+            FsdMessageType::HandoffOfferMessage(m) => m.fmt(f),
+            FsdMessageType::HandoffAcceptMessage(m) => m.fmt(f),
+            FsdMessageType::SharedStateMessage(m) => m.fmt(f),
+        }
+    }
+}
+
 #[allow(unused)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data"))]
 pub enum ClientQueryType {
     IsValidATC(String), //ATC
     Capabilities,       //CAPS
@@ -497,21 +1879,65 @@ pub enum ClientQueryType {
     CancelRequestRelief, //HI
     //RequestHelp, //HLP
     //CancelRequestHelp, //NOHLP
-    WhoHas(String),                                //WH
-    InitiateTrack(String),                         //IT
-    AcceptHandoff(String, String),                 //HT
-    DropTrack(String),                             //DR
-    SetFinalAltitude(String, u32),                 //FA
-    SetTempAltitude(String, u32),                  //TA
-    SetBeaconCode(String, TransponderCode),        //BC
-    SetScratchpad(String, String),                 //SC
-    SetVoiceType(String, VoiceCapability),         //VT
-    AircraftConfigurationRequest,                  //ACC
+    WhoHas(String),                         //WH
+    InitiateTrack(String),                  //IT
+    AcceptHandoff(String, String),          //HT
+    DropTrack(String),                      //DR
+    SetFinalAltitude(String, Level),        //FA
+    SetTempAltitude(String, Level),         //TA
+    SetBeaconCode(String, TransponderCode), //BC
+    ForceBeaconCode(TransponderCode),       //IPC
+    /// A request for help from a supervisor, optionally with a message describing the problem. The message is
+    /// `None` when the token is sent on its own, and empty when it is followed by an empty field (`HLP:`).
+    HelpRequest(Option<String>), //HLP
+    /// Withdraws a help request, optionally with a message, which is `None` or empty as for
+    /// [`HelpRequest`][ClientQueryType::HelpRequest]
+    CancelHelpRequest(Option<String>), //NOHLP
+    SetScratchpad(String, String),          //SC
+    SetVoiceType(String, VoiceCapability),  //VT
+    AircraftConfigurationRequest,           //ACC
     AircraftConfigurationResponse(AircraftConfig), //ACC
     //NewInfo, //NEWINFO
     NewATIS(char, String, String), //NEWATIS
-                                   //Estimate, //EST
-                                   //SetGlobalData, //GD
+    /// The UTC time a pilot client's simulator should be set to
+    SimTime(SystemTime), //SIMTIME
+    //Estimate, //EST
+    /// Free-form data about an aircraft shared between controllers, laid out as `GD:<callsign>:<contents>`. The
+    /// contents may themselves contain colons. This is the client query form of [`SharedStateType::GlobalData`].
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::ClientQueryMessage, parse_message, ClientQueryType, FsdMessageType};
+    /// let message = ClientQueryMessage::set_global_data("EGLL_N_TWR", "@94835", "baw123", "EST:1432:LAM");
+    /// assert_eq!("$CQEGLL_N_TWR:@94835:GD:BAW123:EST:1432:LAM", message.to_string());
+    ///
+    /// let FsdMessageType::ClientQueryMessage(reparsed) = parse_message(&message.to_string()).unwrap() else {
+    ///     panic!("expected a client query");
+    /// };
+    /// let ClientQueryType::SetGlobalData(ref subject, ref contents) = reparsed.query_type else {
+    ///     panic!("expected global data");
+    /// };
+    /// assert_eq!("BAW123", subject);
+    /// assert_eq!("EST:1432:LAM", contents);
+    /// assert_eq!(message, reparsed);
+    /// ```
+    SetGlobalData(String, String), //GD
+    /// A query type not recognised by this crate, kept so that it can be re-emitted verbatim
+    Unknown {
+        token: String,
+        fields: Vec<String>,
+    },
+}
+
+fn write_help_message(
+    f: &mut std::fmt::Formatter<'_>,
+    token: &str,
+    message: &Option<String>,
+) -> std::fmt::Result {
+    match message {
+        Some(message) => write!(f, "{}:{}", token, message),
+        None => write!(f, "{}", token),
+    }
 }
 
 impl Display for ClientQueryType {
@@ -538,6 +1964,11 @@ impl Display for ClientQueryType {
             ClientQueryType::SetBeaconCode(subject, code) => {
                 write!(f, "BC:{}:{}", subject, code)
             }
+            ClientQueryType::HelpRequest(message) => write_help_message(f, "HLP", message),
+            ClientQueryType::CancelHelpRequest(message) => write_help_message(f, "NOHLP", message),
+            ClientQueryType::ForceBeaconCode(code) => {
+                write!(f, "IPC:{}:{}:{}", IPC_MAGIC_W, IPC_MAGIC_852, code)
+            }
             ClientQueryType::SetScratchpad(subject, contents) => {
                 write!(f, "SC:{}:{}", subject, contents)
             }
@@ -553,12 +1984,25 @@ impl Display for ClientQueryType {
             ClientQueryType::NewATIS(letter, wind, pressure) => {
                 write!(f, "NEWATIS:ATIS {}:  {} - {}", letter, wind, pressure)
             }
+            ClientQueryType::SimTime(time) => write!(f, "SIMTIME:{}", util::format_sim_time(*time)),
+            ClientQueryType::SetGlobalData(subject, contents) => {
+                write!(f, "GD:{}:{}", subject, contents)
+            }
+            ClientQueryType::Unknown { token, fields } => {
+                write!(f, "{}", token)?;
+                for field in fields {
+                    write!(f, ":{}", field)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
 #[allow(unused)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data"))]
 pub enum AtisLine {
     VoiceServer(String),
     TextLine(String),
@@ -578,12 +2022,15 @@ impl Display for AtisLine {
 }
 
 #[allow(unused)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data"))]
 pub enum ClientResponseType {
     Com1Freq(RadioFrequency),
     ATIS(AtisLine),
     RealName(String, String, u8),
-    Capabilities(Vec<ClientCapability>),
+    /// The capabilities advertised, whether as supported (`=1`) or unsupported (`=0`), in the order they were sent
+    Capabilities(Vec<AdvertisedCapability>),
     PublicIP(String),
     IsValidATC(String, bool),
 }
@@ -601,7 +2048,7 @@ impl Display for ClientResponseType {
                 write!(f, "CAPS:")?;
                 let mut capabilities = capabilities.iter().peekable();
                 while let Some(capability) = capabilities.next() {
-                    write!(f, "{}=1", capability)?;
+                    write!(f, "{}", capability)?;
                     if capabilities.peek().is_some() {
                         write!(f, ":")?;
                     }
@@ -618,18 +2065,68 @@ impl Display for ClientResponseType {
 }
 
 #[allow(unused)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data"))]
 pub enum SharedStateType {
     Version,
     ID,
     DI,
     IHave(String),
     ScratchPad(String, String),
-    TempAltitude(String, u32),
-    FinalAltitude(String, u32),
+    TempAltitude(String, Level),
+    FinalAltitude(String, Level),
     VoiceType(String, VoiceCapability),
     BeaconCode(String, TransponderCode),
     HandoffCancel(String),
+    /// Free-form data about an aircraft shared between controllers, laid out as `GD:<callsign>:<contents>`.
+    /// The contents may themselves contain colons.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::SharedStateMessage, parse_message, FsdMessageType, SharedStateType};
+    /// let message = SharedStateMessage::global_data("EGLL_N_TWR", "@94835", "BAW123", "EST:1432:LAM");
+    /// assert_eq!("#PCEGLL_N_TWR:@94835:CCP:GD:BAW123:EST:1432:LAM", message.to_string());
+    ///
+    /// let FsdMessageType::SharedStateMessage(reparsed) = parse_message(&message.to_string()).unwrap() else {
+    ///     panic!("expected a shared state message");
+    /// };
+    /// let SharedStateType::GlobalData(ref subject, ref contents) = reparsed.shared_state_type else {
+    ///     panic!("expected global data");
+    /// };
+    /// assert_eq!("BAW123", subject);
+    /// assert_eq!("EST:1432:LAM", contents);
+    /// assert_eq!(message.to_string(), reparsed.to_string());
+    /// ```
+    GlobalData(String, String),
+    /// A shared state type not recognised by this crate, kept so that it can be re-emitted verbatim
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, FsdMessageType, SharedStateType};
+    /// let message = "#PCEGLL_N_TWR:EGLL_S_TWR:CCP:ZZ:BAW123:1:2";
+    /// let FsdMessageType::SharedStateMessage(shared_state) = parse_message(message).unwrap() else {
+    ///     panic!("expected a shared state message");
+    /// };
+    /// let SharedStateType::Unknown { ref token, ref fields } = shared_state.shared_state_type else {
+    ///     panic!("expected an unknown shared state type");
+    /// };
+    /// assert_eq!("ZZ", token);
+    /// assert_eq!(vec!["BAW123", "1", "2"], *fields);
+    /// assert_eq!(message, shared_state.to_string());
+    ///
+    /// // Commands which carry nothing after their token, such as the land line commands, need no further fields
+    /// let message = "#PCEGLL_N_TWR:EGLL_S_TWR:CCP:EC";
+    /// let FsdMessageType::SharedStateMessage(shared_state) = parse_message(message).unwrap() else {
+    ///     panic!("expected a shared state message");
+    /// };
+    /// assert!(matches!(shared_state.shared_state_type, SharedStateType::Unknown { ref fields, .. } if fields.is_empty()));
+    /// assert_eq!(message, shared_state.to_string());
+    /// ```
+    Unknown {
+        token: String,
+        fields: Vec<String>,
+    },
 }
 
 impl Display for SharedStateType {
@@ -653,11 +2150,22 @@ impl Display for SharedStateType {
             }
             SharedStateType::BeaconCode(subject, code) => write!(f, "BC:{}:{}", subject, code),
             SharedStateType::HandoffCancel(subject) => write!(f, "HC:{}", subject),
+            SharedStateType::GlobalData(subject, contents) => {
+                write!(f, "GD:{}:{}", subject, contents)
+            }
+            SharedStateType::Unknown { token, fields } => {
+                write!(f, "{}", token)?;
+                for field in fields {
+                    write!(f, ":{}", field)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VoiceCapability {
     Unknown,
     Voice,
@@ -689,3 +2197,355 @@ impl Display for VoiceCapability {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fmt::Debug};
+
+    use super::*;
+    use crate::{
+        aircraft_config::AircraftConfig,
+        errors::FsdError,
+        parse_message,
+        structs::{FlightPlan, PlaneInfo},
+    };
+
+    /// Formats a message, splits it on colons and parses it back with its own `TryFrom` impl
+    fn round_trip<T>(message: &T)
+    where
+        T: Display
+            + Debug
+            + PartialEq
+            + for<'a> TryFrom<&'a [&'a str], Error = FsdMessageParseError>,
+    {
+        let line = message.to_string();
+        let fields: Vec<&str> = line.split(':').collect();
+        let parsed = T::try_from(fields.as_slice())
+            .unwrap_or_else(|e| panic!("{line} could not be parsed: {e}"));
+        assert_eq!(*message, parsed, "{line}");
+    }
+
+    /// Checks one message both through its own `TryFrom` impl and through [`parse_message`]. The match has no
+    /// wildcard arm, so a new variant cannot be added without deciding how it round-trips.
+    fn check(message: &FsdMessageType) {
+        match message {
+            FsdMessageType::AtcRegisterMessage(m) => round_trip(m),
+            FsdMessageType::PilotRegisterMessage(m) => round_trip(m),
+            FsdMessageType::AtcDeregisterMessage(m) => round_trip(m),
+            FsdMessageType::PilotDeregisterMessage(m) => round_trip(m),
+            FsdMessageType::AtcPositionUpdateMessage(m) => round_trip(m),
+            FsdMessageType::AtcSecondaryVisCentreMessage(m) => round_trip(m),
+            FsdMessageType::PilotPositionUpdateMessage(m) => round_trip(m),
+            FsdMessageType::AuthenticationChallengeMessage(m) => round_trip(m),
+            FsdMessageType::AuthenticationResponseMessage(m) => round_trip(m),
+            FsdMessageType::TextMessage(m) => round_trip(m),
+            FsdMessageType::FrequencyMessage(m) => round_trip(m),
+            FsdMessageType::ChangeServerMessage(m) => round_trip(m),
+            FsdMessageType::InitialServerHandshakeMessage(m) => round_trip(m),
+            FsdMessageType::InitialClientHandshakeMessage(m) => round_trip(m),
+            FsdMessageType::SendFastPositionUpdatesMessage(m) => round_trip(m),
+            FsdMessageType::VelocityPositionStoppedMessage(m) => round_trip(m),
+            FsdMessageType::VelocityPositionSlowMessage(m) => round_trip(m),
+            FsdMessageType::VelocityPositionFastMessage(m) => round_trip(m),
+            FsdMessageType::KillMessage(m) => round_trip(m),
+            FsdMessageType::MetarRequestMessage(m) => round_trip(m),
+            FsdMessageType::MetarResponseMessage(m) => round_trip(m),
+            FsdMessageType::PingMessage(m) => round_trip(m),
+            FsdMessageType::PongMessage(m) => round_trip(m),
+            FsdMessageType::PlaneInfoRequestMessage(m) => round_trip(m),
+            FsdMessageType::PlaneInfoResponseMessage(m) => round_trip(m),
+            FsdMessageType::FsdErrorMessage(m) => round_trip(m),
+            FsdMessageType::FlightPlanMessage(m) => round_trip(m),
+            FsdMessageType::FlightPlanAmendmentMessage(m) => round_trip(m),
+            FsdMessageType::ServerHeartbeat(m) => round_trip(m),
+            FsdMessageType::ClientQueryMessage(m) => round_trip(m),
+            FsdMessageType::ClientQueryResponseMessage(m) => round_trip(m),
+            FsdMessageType::HandoffOfferMessage(m) => round_trip(m),
+            FsdMessageType::HandoffAcceptMessage(m) => round_trip(m),
+            FsdMessageType::SharedStateMessage(m) => round_trip(m),
+            // These carry no data, so only their identifying token is written, as documented on the Display impl
+            FsdMessageType::FSInnPlaneInformationRequestMessage => {
+                assert_eq!("FSIPIR", message.to_string());
+                return;
+            }
+            FsdMessageType::FSInnPlaneInformationResponseMessage => {
+                assert_eq!("FSIPI", message.to_string());
+                return;
+            }
+        }
+        let line = message.to_string();
+        assert_eq!(*message, parse_message(&line).unwrap(), "{line}");
+    }
+
+    fn flight_plan() -> FlightPlan {
+        FlightPlan::new(
+            FlightRules::IFR,
+            "B738",
+            450,
+            "EGLL",
+            1200,
+            0,
+            35000,
+            "LIRF",
+            2,
+            20,
+            4,
+            0,
+            "LIRA",
+            "/V/ PBN/A1B1",
+            "MIMFO Y312 DVR UL9 KONAN",
+        )
+    }
+
+    fn every_variant() -> Vec<FsdMessageType> {
+        let frequency = RadioFrequency::new(118, 500).unwrap();
+        let squawk = TransponderCode::try_from(4621).unwrap();
+        let plane_info = PlaneInfo {
+            equipment: Some("B738".to_string()),
+            airline: Some("BAW".to_string()),
+            livery: None,
+        };
+        vec![
+            FsdMessageType::AtcRegisterMessage(AtcRegisterMessage::new(
+                "EGLL_N_TWR",
+                "SERVER",
+                "Joe Bloggs",
+                "1234567",
+                "password",
+                AtcRating::S2,
+                ProtocolRevision::Vatsim2022,
+            )),
+            FsdMessageType::PilotRegisterMessage(PilotRegisterMessage::new(
+                "BAW123",
+                "SERVER",
+                "Joe: Bloggs",
+                "1234567",
+                "password",
+                PilotRating::Student,
+                ProtocolRevision::Vatsim2022,
+                SimulatorType::XPlane11,
+            )),
+            FsdMessageType::AtcDeregisterMessage(AtcDeregisterMessage::with_reason(
+                "EGLL_N_TWR",
+                "1234567",
+                "End of shift",
+            )),
+            FsdMessageType::PilotDeregisterMessage(PilotDeregisterMessage::new(
+                "BAW123", "1234567",
+            )),
+            FsdMessageType::AtcPositionUpdateMessage(AtcPositionUpdateMessage::new(
+                "EGLL_N_TWR",
+                vec![frequency],
+                AtcType::Tower,
+                50,
+                AtcRating::S2,
+                51.47,
+                -0.45,
+                83,
+            )),
+            FsdMessageType::AtcSecondaryVisCentreMessage(AtcSecondaryVisCentreMessage::new(
+                "EGLL_N_TWR",
+                1,
+                51.47,
+                -0.45,
+            )),
+            FsdMessageType::PilotPositionUpdateMessage(PilotPositionUpdateMessage::new(
+                "BAW123",
+                TransponderMode::ModeC,
+                squawk,
+                PilotRating::Student,
+                51.47,
+                -0.45,
+                1200.0,
+                1150.0,
+                250,
+                0.0,
+                0.0,
+                90.0,
+                false,
+            )),
+            FsdMessageType::AuthenticationChallengeMessage(AuthenticationChallengeMessage::new(
+                "SERVER",
+                "BAW123",
+                "abcdef0123",
+            )),
+            FsdMessageType::AuthenticationResponseMessage(AuthenticationResponseMessage::new(
+                "BAW123",
+                "SERVER",
+                "abcdef0123",
+            )),
+            FsdMessageType::TextMessage(TextMessage::new(
+                "EGLL_N_TWR",
+                "BAW123",
+                "Contact Heathrow Director: 119.725",
+            )),
+            FsdMessageType::FrequencyMessage(FrequencyMessage::new(
+                "EGLL_N_TWR",
+                vec![frequency],
+                "Frequency message",
+            )),
+            FsdMessageType::ChangeServerMessage(ChangeServerMessage::new(
+                "SERVER",
+                "BAW123",
+                "newserver.example.com",
+            )),
+            FsdMessageType::InitialServerHandshakeMessage(InitialServerHandshakeMessage::new(
+                "SERVER",
+                "CLIENT",
+                "VATSIM FSD V3.53",
+                "0123456789abcdef",
+            )),
+            FsdMessageType::InitialClientHandshakeMessage(InitialClientHandshakeMessage::with_key(
+                "BAW123",
+                "SERVER",
+                0x69d7,
+                "vPilot",
+                3,
+                8,
+                "1234567",
+                "123456789",
+                "abcdef",
+            )),
+            FsdMessageType::SendFastPositionUpdatesMessage(SendFastPositionUpdatesMessage::new(
+                "SERVER", "BAW123", true,
+            )),
+            FsdMessageType::VelocityPositionStoppedMessage(VelocityPositionStoppedMessage::new(
+                "BAW123", 51.47, -0.45, 83.0, 0.0, 0.0, 0.0, 90.0, true, None,
+            )),
+            FsdMessageType::VelocityPositionSlowMessage(VelocityPositionSlowMessage::new(
+                "BAW123",
+                51.47,
+                -0.45,
+                1200.0,
+                1150.0,
+                0.0,
+                0.0,
+                90.0,
+                false,
+                12.5,
+                -3.2,
+                0.0,
+                0.0,
+                0.01,
+                0.0,
+                Some(1.5),
+            )),
+            FsdMessageType::VelocityPositionFastMessage(VelocityPositionFastMessage::new(
+                "BAW123", 51.47, -0.45, 1200.0, 1150.0, 0.0, 0.0, 90.0, false, 12.5, -3.2, 0.0,
+                0.0, 0.01, 0.0, None,
+            )),
+            FsdMessageType::KillMessage(KillMessage::new(
+                "SERVER",
+                "BAW123",
+                Some("Kicked: abusive behaviour"),
+            )),
+            FsdMessageType::MetarRequestMessage(MetarRequestMessage::new(
+                "EGLL_TWR", "SERVER", "EGLL",
+            )),
+            FsdMessageType::MetarResponseMessage(MetarResponseMessage::new(
+                "SERVER",
+                "EGLL_TWR",
+                "EGLL 141250Z 24012KT 9999 FEW035 17/09 Q1021",
+            )),
+            FsdMessageType::PingMessage(PingMessage::new("EGLL_TWR", "SERVER", 12345)),
+            FsdMessageType::PongMessage(PongMessage::new("SERVER", "EGLL_TWR", 12345)),
+            FsdMessageType::PlaneInfoRequestMessage(PlaneInfoRequestMessage::new(
+                "EZY38UB", "BAW123",
+            )),
+            FsdMessageType::PlaneInfoResponseMessage(PlaneInfoResponseMessage::new(
+                "BAW123", "EZY38UB", plane_info,
+            )),
+            FsdMessageType::FsdErrorMessage(FsdErrorMessage::new(
+                "SERVER",
+                "BAW123",
+                FsdError::NoSuchCallsign("BAW456".to_string()),
+            )),
+            FsdMessageType::FlightPlanMessage(FlightPlanMessage::new(
+                "*A",
+                "BAW123",
+                flight_plan(),
+            )),
+            FsdMessageType::FlightPlanAmendmentMessage(FlightPlanAmendmentMessage::amend(
+                "EGLL_DEL",
+                "BAW123",
+                flight_plan(),
+            )),
+            FsdMessageType::FSInnPlaneInformationRequestMessage,
+            FsdMessageType::FSInnPlaneInformationResponseMessage,
+            FsdMessageType::ServerHeartbeat(ServerHeartbeatMessage::new(
+                "SERVER",
+                "*",
+                ["12", "3"],
+            )),
+            FsdMessageType::ClientQueryMessage(ClientQueryMessage::set_scratchpad(
+                "EGLL_N_TWR",
+                "@94835",
+                "BAW123",
+                "HDG: 270",
+            )),
+            FsdMessageType::ClientQueryMessage(ClientQueryMessage::aircraft_config_response(
+                "BAW123",
+                "EZY38UB",
+                AircraftConfig {
+                    gear_down: Some(true),
+                    flaps_pct: Some(25),
+                    ..AircraftConfig::default()
+                },
+            )),
+            FsdMessageType::ClientQueryMessage(ClientQueryMessage::help_request(
+                "BAW123",
+                "@94835",
+                Some("Frequency 118.700: no reply"),
+            )),
+            FsdMessageType::ClientQueryMessage(ClientQueryMessage::cancel_help_request(
+                "BAW123",
+                "@94835",
+                Some(""),
+            )),
+            FsdMessageType::ClientQueryResponseMessage(ClientQueryResponseMessage::capabilities(
+                "BAW123",
+                "EGLL_N_TWR",
+                vec![ClientCapability::Version, ClientCapability::ATCInfo],
+            )),
+            FsdMessageType::ClientQueryResponseMessage(ClientQueryResponseMessage::is_valid_atc(
+                "SERVER",
+                "EGLL_N_TWR",
+                "EGLL_S_TWR",
+                true,
+            )),
+            FsdMessageType::HandoffOfferMessage(HandoffOfferMessage::new(
+                "EGLL_N_TWR",
+                "EGLL_S_TWR",
+                "BAW123",
+            )),
+            FsdMessageType::HandoffAcceptMessage(HandoffAcceptMessage::new(
+                "EGLL_S_TWR",
+                "EGLL_N_TWR",
+                "BAW123",
+            )),
+            FsdMessageType::SharedStateMessage(SharedStateMessage::beacon_code(
+                "EGLL_N_TWR",
+                "EGLL_S_TWR",
+                "BAW123",
+                squawk,
+            )),
+        ]
+    }
+
+    #[test]
+    fn every_message_round_trips() {
+        for message in every_variant() {
+            check(&message);
+        }
+    }
+
+    #[test]
+    fn every_message_type_is_covered() {
+        let covered: HashSet<&str> = every_variant()
+            .iter()
+            .map(|message| message.kind().name)
+            .collect();
+        let listed: HashSet<&str> = PREFIX_TABLE.iter().map(|(_, kind)| kind.name).collect();
+        assert_eq!(listed, covered);
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::session::SessionState;
+
 #[derive(Error, Debug)]
 pub enum FsdMessageParseError {
     #[error("invalid field count. Expected {0}, found {1}.")]
@@ -68,10 +70,18 @@ pub enum FsdMessageParseError {
     InvalidSharedStateType(String),
     #[error("{0} is not a valid client capability")]
     InvalidClientCapability(String),
+    #[error("{0} is not a valid callsign: {1}")]
+    InvalidCallsign(String, String),
+    #[error("IPC constant {0} did not match. Expected {1}, found {2}.")]
+    IpcConstantMismatch(&'static str, &'static str, String),
+    #[error("field {0} is not valid UTF-8")]
+    InvalidEncoding(usize),
 }
 
 /// An error message received from the FSD server
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "data"))]
 pub enum FsdError {
     /// Attempted to log in with a callsign that is already in use
     #[error("Callsign in use")]
@@ -128,7 +138,54 @@ pub enum FsdError {
     #[error("Other: {0}")]
     Other(String),
 }
+
+/// Looks up an error by its number on the wire, the inverse of [`FsdError::error_number`]
+///
+/// The errors which carry text have it left empty: the callsign or station of [`FsdError::NoSuchCallsign`],
+/// [`FsdError::NoFlightPlan`] and [`FsdError::NoWeatherProfile`], and the message of [`FsdError::Other`]. Numbers
+/// outside 1 to 18 are rejected.
+///
+/// # Example
+/// ```
+/// use fsd_interface::errors::FsdError;
+/// for number in 1..=18 {
+///     assert_eq!(number, FsdError::try_from(number).unwrap().error_number());
+/// }
+/// assert_eq!(FsdError::InvalidCallsign, FsdError::try_from(2).unwrap());
+/// assert_eq!(FsdError::InvalidSourceCallsign, FsdError::try_from(5).unwrap());
+/// assert_eq!(FsdError::NoSuchCallsign(String::new()), FsdError::try_from(7).unwrap());
+/// assert!(FsdError::try_from(0).is_err());
+/// assert!(FsdError::try_from(19).is_err());
+/// ```
+impl TryFrom<u8> for FsdError {
+    type Error = FsdMessageParseError;
+    fn try_from(number: u8) -> Result<Self, Self::Error> {
+        Ok(match number {
+            1 => FsdError::CallsignInUse,
+            2 => FsdError::InvalidCallsign,
+            3 => FsdError::AlreadyRegistered,
+            4 => FsdError::SyntaxError,
+            5 => FsdError::InvalidSourceCallsign,
+            6 => FsdError::InvalidCidPassword,
+            7 => FsdError::NoSuchCallsign(String::new()),
+            8 => FsdError::NoFlightPlan(String::new()),
+            9 => FsdError::NoWeatherProfile(String::new()),
+            10 => FsdError::InvalidProtocolRevision,
+            11 => FsdError::RequestedLevelTooHigh,
+            12 => FsdError::ServerFull,
+            13 => FsdError::CertificateSuspended,
+            14 => FsdError::InvalidControl,
+            15 => FsdError::InvalidPositionForRating,
+            16 => FsdError::UnauthorisedClient,
+            17 => FsdError::AuthTimeOut,
+            18 => FsdError::Other(String::new()),
+            _ => return Err(FsdMessageParseError::InvalidServerError(number.to_string())),
+        })
+    }
+}
+
 impl FsdError {
+    /// The number identifying the error on the wire. The `TryFrom<u8>` implementation is the reverse.
     pub fn error_number(&self) -> u8 {
         match *self {
             FsdError::CallsignInUse => 1,
@@ -151,4 +208,107 @@ impl FsdError {
             FsdError::Other(_) => 18,
         }
     }
+
+    /// The broad kind of problem the error reports, for aggregating errors on a dashboard
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::errors::{ErrorCategory, FsdError};
+    /// let expected = [
+    ///     (FsdError::CallsignInUse, ErrorCategory::Client, true),
+    ///     (FsdError::InvalidCallsign, ErrorCategory::Client, false),
+    ///     (FsdError::AlreadyRegistered, ErrorCategory::Protocol, false),
+    ///     (FsdError::SyntaxError, ErrorCategory::Protocol, false),
+    ///     (FsdError::InvalidSourceCallsign, ErrorCategory::Protocol, false),
+    ///     (FsdError::InvalidCidPassword, ErrorCategory::Auth, false),
+    ///     (FsdError::NoSuchCallsign(String::from("N123")), ErrorCategory::Client, false),
+    ///     (FsdError::NoFlightPlan(String::from("N123")), ErrorCategory::Client, false),
+    ///     (FsdError::NoWeatherProfile(String::from("XXXX")), ErrorCategory::Client, false),
+    ///     (FsdError::InvalidProtocolRevision, ErrorCategory::Protocol, false),
+    ///     (FsdError::RequestedLevelTooHigh, ErrorCategory::Auth, false),
+    ///     (FsdError::ServerFull, ErrorCategory::Capacity, true),
+    ///     (FsdError::CertificateSuspended, ErrorCategory::Auth, false),
+    ///     (FsdError::InvalidControl, ErrorCategory::Auth, false),
+    ///     (FsdError::InvalidPositionForRating, ErrorCategory::Auth, false),
+    ///     (FsdError::UnauthorisedClient, ErrorCategory::Auth, false),
+    ///     (FsdError::AuthTimeOut, ErrorCategory::Auth, true),
+    ///     (FsdError::Other(String::from("Unknown")), ErrorCategory::Other, false),
+    /// ];
+    /// for (error, category, retryable) in expected {
+    ///     assert_eq!(category, error.category(), "{error:?}");
+    ///     assert_eq!(retryable, error.is_retryable(), "{error:?}");
+    /// }
+    /// ```
+    pub fn category(&self) -> ErrorCategory {
+        match *self {
+            FsdError::InvalidCidPassword
+            | FsdError::RequestedLevelTooHigh
+            | FsdError::CertificateSuspended
+            | FsdError::InvalidControl
+            | FsdError::InvalidPositionForRating
+            | FsdError::UnauthorisedClient
+            | FsdError::AuthTimeOut => ErrorCategory::Auth,
+            FsdError::ServerFull => ErrorCategory::Capacity,
+            FsdError::AlreadyRegistered
+            | FsdError::SyntaxError
+            | FsdError::InvalidSourceCallsign
+            | FsdError::InvalidProtocolRevision => ErrorCategory::Protocol,
+            FsdError::CallsignInUse
+            | FsdError::InvalidCallsign
+            | FsdError::NoSuchCallsign(_)
+            | FsdError::NoFlightPlan(_)
+            | FsdError::NoWeatherProfile(_) => ErrorCategory::Client,
+            FsdError::Other(_) => ErrorCategory::Other,
+        }
+    }
+
+    /// Whether repeating the same connection attempt may succeed later, without the user changing anything.
+    ///
+    /// This is the case when the server is full, when a callsign is still held by a connection which has not yet
+    /// timed out, and when the authentication challenge was not answered in time.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            *self,
+            FsdError::ServerFull | FsdError::CallsignInUse | FsdError::AuthTimeOut
+        )
+    }
+}
+
+/// The broad kind of problem an [`FsdError`] reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The user's credentials, rating or client do not permit the action
+    Auth,
+    /// The server has no room for another client
+    Capacity,
+    /// The client broke the protocol
+    Protocol,
+    /// The request was well-formed but refers to something that is invalid or does not exist
+    Client,
+    /// An error the server did not classify
+    Other,
+}
+
+/// A message was received out of order in a client's connection lifecycle
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SessionError {
+    #[error("unexpected {0} from {1} while in state {2:?}")]
+    UnexpectedMessage(&'static str, String, SessionState),
+}
+
+/// A line received from the network could not be framed
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FramingError {
+    /// A line was longer than the framer's maximum, given in bytes, and was discarded. The length is how much of the
+    /// line had been received when it was rejected.
+    #[error("line of at least {0} bytes is longer than the maximum of {1}")]
+    LineTooLong(usize, usize),
+}
+
+/// A message builder was asked to build a message without every required field set
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BuilderError {
+    /// The named field is required but was never set
+    #[error("required field {0} was not set")]
+    MissingField(&'static str),
 }
@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+
+use crate::{errors::FramingError, util};
+
+/// Splits the byte stream received from an FSD server into lines, however the stream was fragmented across reads.
+///
+/// Lines are terminated by `\r\n`, and a bare `\n` is accepted too. The terminator is stripped and empty lines are
+/// skipped. Lines which are not valid UTF-8 are decoded as Windows-1252, as sent by some older clients.
+///
+/// A line longer than the maximum length is discarded and reported as a [`FramingError::LineTooLong`] in its place,
+/// so that a peer which never sends a terminator cannot make the buffer grow without bound.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{errors::FramingError, parse_message, FsdFramer};
+/// let mut framer = FsdFramer::new(64);
+/// framer.push_bytes(b"#TMEGLL_N_TWR:BAW123:Contact ");
+/// assert!(framer.next_line().is_none());
+///
+/// framer.push_bytes(b"Heathrow Director\r\n$PISERVER:BAW123:1234\n\r\n#DPBAW");
+/// assert_eq!(Some(Ok(String::from("#TMEGLL_N_TWR:BAW123:Contact Heathrow Director"))), framer.next_line());
+/// assert_eq!(Some(Ok(String::from("$PISERVER:BAW123:1234"))), framer.next_line());
+/// assert!(framer.next_line().is_none());
+///
+/// framer.push_bytes(b"123:1234567\r\n");
+/// for line in framer.drain() {
+///     assert!(parse_message(line.unwrap()).is_ok());
+/// }
+///
+/// framer.push_bytes(&[b'X'; 100]);
+/// framer.push_bytes(b"XXXX\r\n$PISERVER:BAW123:1235\r\n");
+/// assert_eq!(Some(Err(FramingError::LineTooLong(100, 64))), framer.next_line());
+/// assert_eq!(Some(Ok(String::from("$PISERVER:BAW123:1235"))), framer.next_line());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FsdFramer {
+    max_line_len: usize,
+    partial: Vec<u8>,
+    /// Set while the rest of an overlong line is being skipped
+    discarding: bool,
+    lines: VecDeque<Result<String, FramingError>>,
+}
+
+impl Default for FsdFramer {
+    fn default() -> Self {
+        FsdFramer::new(FsdFramer::DEFAULT_MAX_LINE_LEN)
+    }
+}
+
+impl FsdFramer {
+    /// The maximum line length, in bytes, used by [`FsdFramer::default`]
+    pub const DEFAULT_MAX_LINE_LEN: usize = 8192;
+
+    /// Creates a framer which rejects lines longer than `max_line_len` bytes, not counting the terminator
+    pub fn new(max_line_len: usize) -> Self {
+        FsdFramer {
+            max_line_len,
+            partial: Vec::new(),
+            discarding: false,
+            lines: VecDeque::new(),
+        }
+    }
+
+    /// Adds bytes received from the network
+    pub fn push_bytes(&mut self, buf: &[u8]) {
+        let mut rest = buf;
+        while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+            let (line, remainder) = (&rest[..end], &rest[end + 1..]);
+            rest = remainder;
+            if self.discarding {
+                self.discarding = false;
+                continue;
+            }
+            self.partial.extend_from_slice(line);
+            let line = std::mem::take(&mut self.partial);
+            self.complete_line(line);
+        }
+
+        if self.discarding {
+            return;
+        }
+        self.partial.extend_from_slice(rest);
+        // Allow for the '\r' of a line of the maximum length whose '\n' has not yet arrived
+        if self.partial.len() > self.max_line_len + 1 {
+            self.lines.push_back(Err(FramingError::LineTooLong(
+                self.partial.len(),
+                self.max_line_len,
+            )));
+            self.partial.clear();
+            self.discarding = true;
+        }
+    }
+
+    fn complete_line(&mut self, mut line: Vec<u8>) {
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if line.is_empty() {
+            return;
+        }
+        if line.len() > self.max_line_len {
+            self.lines.push_back(Err(FramingError::LineTooLong(
+                line.len(),
+                self.max_line_len,
+            )));
+            return;
+        }
+        let line = match String::from_utf8(line) {
+            Ok(line) => line,
+            Err(error) => util::decode_windows_1252(error.as_bytes()),
+        };
+        self.lines.push_back(Ok(line));
+    }
+
+    /// The next complete line, or [`None`] if no complete line has been received yet
+    pub fn next_line(&mut self) -> Option<Result<String, FramingError>> {
+        self.lines.pop_front()
+    }
+
+    /// An iterator over every line completed so far
+    pub fn drain(&mut self) -> Drain<'_> {
+        Drain { framer: self }
+    }
+}
+
+/// Iterates over the complete lines held by an [`FsdFramer`], as returned by [`FsdFramer::drain`]
+#[derive(Debug)]
+pub struct Drain<'a> {
+    framer: &'a mut FsdFramer,
+}
+
+impl Iterator for Drain<'_> {
+    type Item = Result<String, FramingError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.framer.next_line()
+    }
+}
@@ -0,0 +1,43 @@
+//! Serialises an [`FsdMessageType`] as the single FSD message string that is sent over the network, and deserialises
+//! it by parsing that string again. Use it with `#[serde(with = "fsd_interface::fsd_wire")]` to record messages
+//! compactly, in exactly the form they took on the wire.
+//!
+//! # Example
+//! ```
+//! use fsd_interface::{parse_message, FsdMessageType};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Recorded {
+//!     received: u64,
+//!     #[serde(with = "fsd_interface::fsd_wire")]
+//!     message: FsdMessageType,
+//! }
+//!
+//! let recorded = Recorded {
+//!     received: 1700000000,
+//!     message: parse_message("$CQEHAM_GND:@94835:WH:KLM167").unwrap(),
+//! };
+//! let json = serde_json::to_string(&recorded).unwrap();
+//! assert_eq!(r#"{"received":1700000000,"message":"$CQEHAM_GND:@94835:WH:KLM167"}"#, json);
+//! assert_eq!(recorded, serde_json::from_str(&json).unwrap());
+//!
+//! // Strings that are not valid FSD messages are rejected
+//! assert!(serde_json::from_str::<Recorded>(r#"{"received":0,"message":"hello"}"#).is_err());
+//! ```
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+use crate::{enums::FsdMessageType, parse_message};
+
+pub fn serialize<S: Serializer>(
+    message: &FsdMessageType,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(message)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<FsdMessageType, D::Error> {
+    let wire = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+    parse_message(wire.as_ref()).map_err(de::Error::custom)
+}
@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::enums::FsdMessageType;
+
+/// Records when each aircraft last squawked ident, so that a scope can keep its target flashing for a while after
+/// the momentary ident ends.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, Instant};
+/// use fsd_interface::{parse_message, FsdMessageType, IdentTracker};
+/// let FsdMessageType::PilotPositionUpdateMessage(ident) = parse_message("@Y:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8").unwrap() else {
+///     panic!("expected a pilot position update");
+/// };
+/// assert!(ident.is_ident());
+/// let FsdMessageType::PilotPositionUpdateMessage(normal) = parse_message("@N:EZY38UB:7000:1:51.47000:-0.45000:83:0:4290769920:-8").unwrap() else {
+///     panic!("expected a pilot position update");
+/// };
+/// assert!(!normal.is_ident());
+///
+/// let mut tracker = IdentTracker::new(Duration::from_secs(5));
+/// let received = Instant::now();
+/// assert!(tracker.process(&FsdMessageType::PilotPositionUpdateMessage(ident), received));
+/// assert!(!tracker.process(&FsdMessageType::PilotPositionUpdateMessage(normal), received));
+///
+/// assert!(tracker.should_flash("baw123", received + Duration::from_secs(4)));
+/// assert!(!tracker.should_flash("BAW123", received + Duration::from_secs(5)));
+/// assert!(!tracker.should_flash("EZY38UB", received));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IdentTracker {
+    window: Duration,
+    last_ident: HashMap<String, Instant>,
+}
+
+impl IdentTracker {
+    /// Creates a tracker which flashes a target for `window` after its last ident
+    pub fn new(window: Duration) -> Self {
+        IdentTracker {
+            window,
+            last_ident: HashMap::new(),
+        }
+    }
+
+    /// Records an ident from a pilot position update received at `received`, and forgets aircraft which disconnect.
+    ///
+    /// Returns whether an ident was recorded.
+    pub fn process(&mut self, message: &FsdMessageType, received: Instant) -> bool {
+        match message {
+            FsdMessageType::PilotPositionUpdateMessage(m) if m.is_ident() => {
+                self.record(&m.callsign, received);
+                true
+            }
+            FsdMessageType::PilotDeregisterMessage(m) => {
+                self.last_ident.remove(&m.from.to_uppercase());
+                false
+            }
+            _ => false,
+        }
+    }
+
+    pub fn record(&mut self, callsign: impl AsRef<str>, at: Instant) {
+        self.last_ident.insert(callsign.as_ref().to_uppercase(), at);
+    }
+
+    /// Whether the aircraft's target should be flashing at `now`, because it squawked ident less than the window ago
+    pub fn should_flash(&self, callsign: impl AsRef<str>, now: Instant) -> bool {
+        match self.last_ident.get(&callsign.as_ref().to_uppercase()) {
+            Some(at) => now.saturating_duration_since(*at) < self.window,
+            None => false,
+        }
+    }
+}
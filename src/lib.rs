@@ -48,22 +48,24 @@
 //! 
 //! ## Examples
 //! ```
+//! use fsd_interface::{messages::ClientQueryMessage, ClientQueryType, FsdMessageType};
+//!
 //! // Imagine this is a message we have received from an FSD server
 //! let message_text = String::from("$CQEHAM_GND:@94835:WH:KLM167");
 //! 
 //! // We can identify what type of message it is, deserialise it
-//! let message_deserialised = fsd_messages::parse(&message_text).unwrap();
-//! if let FsdMessage::ClientQueryMessage(client_query_message) = message_deserialised {
+//! let message_deserialised = fsd_interface::parse_message(&message_text).unwrap();
+//! if let FsdMessageType::ClientQueryMessage(client_query_message) = message_deserialised {
 //! 
 //!     // And access its data
 //!     assert_eq!("EHAM_GND", client_query_message.from.as_str());
 //!     assert_eq!("@94835", client_query_message.to.as_str());
 //!     if let ClientQueryType::WhoHas(aircraft) = client_query_message.query_type {
-//!         assert_eq!("KLM367", aircraft.as_str());
+//!         assert_eq!("KLM167", aircraft.as_str());
 //!     }
 //! 
 //!     // Plus, on the flip side, we can create our own messages and serialise them
-//!     let new_message = messages::ClientQuery::message::who_has("LIRF_TWR", "@94835", "ITA1561");
+//!     let new_message = ClientQueryMessage::who_has("LIRF_TWR", "@94835", "ITA1561");
 //!     assert_eq!(String::from("$CQLIRF_TWR:@94835:WH:ITA1561"), new_message.to_string());
 //! }
 //! ```
@@ -81,18 +83,45 @@
 //! responsible for checking that it is indeed compliant with the VATSIM FSD protocol.
 
 mod aircraft_config;
+mod anonymize;
+mod callsign;
 mod enums;
 
 /// Contains error types used in the crate
 pub mod errors;
 
+mod framing;
+
+/// Serde support for recording messages as their FSD message strings
+#[cfg(feature = "serde")]
+pub mod fsd_wire;
+
+mod ident_tracker;
+mod message_builder;
 pub mod messages;
+mod pending_requests;
+mod plane_info_cache;
+mod session;
 mod structs;
-mod util;
+mod timeline;
+
+/// Helpers for the lower-level encodings used inside FSD message fields
+pub mod util;
+mod validation;
 
 pub use enums::*;
 pub use structs::*;
 pub use aircraft_config::*;
+pub use anonymize::*;
+pub use callsign::*;
+pub use framing::*;
+pub use ident_tracker::*;
+pub use message_builder::*;
+pub use pending_requests::*;
+pub use plane_info_cache::*;
+pub use session::*;
+pub use timeline::*;
+pub use validation::*;
 
 
 
@@ -100,8 +129,36 @@ pub use aircraft_config::*;
 /// 
 /// If the string is a valid FSD message, deserialises it into the appropriate struct and returns it inside an [`FsdMessageType`] enum variant that indicates which type it is.
 /// If there are any validation errors, an [`FsdMessageParseError`][errors::FsdMessageParseError] is returned instead.
+/// 
+/// Messages are parsed with [`ParseMode::Lenient`], which accepts some messages that earlier versions of the crate rejected: flight plans with too few or too many fields,
+/// client query and shared state types the crate does not recognise, pilot position updates with a missing or invalid rating, which are read as [`PilotRating::Unknown`],
+/// and ATC position updates with a malformed elevation, which is read as 0.
+/// Use [`parse_message_with_mode`] with [`ParseMode::Strict`] to keep rejecting them.
 pub fn parse_message(message: impl AsRef<str>) -> Result<FsdMessageType, errors::FsdMessageParseError> {
-    FsdMessageType::identify(message.as_ref())
+    FsdMessageType::identify(message.as_ref(), ParseMode::default())
+}
+
+/// Deserialises a valid FSD message string into a struct, using the given [`ParseMode`].
+/// 
+/// [`parse_message`] uses [`ParseMode::Lenient`]. Pass [`ParseMode::Strict`] to reject messages containing anything the crate does not recognise.
+pub fn parse_message_with_mode(message: impl AsRef<str>, mode: ParseMode) -> Result<FsdMessageType, errors::FsdMessageParseError> {
+    FsdMessageType::identify(message.as_ref(), mode)
+}
+
+/// Deserialises a valid FSD message string into a struct, using the given [`ParseMode`] and reading pilot position updates as sent by a client using the given [`ProtocolRevision`].
+/// 
+/// The other parsing functions read position updates as [`ProtocolRevision::Vatsim2022`]; see [`PilotPositionUpdateMessage::parse_with_protocol`][messages::PilotPositionUpdateMessage::parse_with_protocol] for how older revisions differ.
+/// To parse the messages of one client using the revision it registered with, see [`ClientSession::parse_message`].
+pub fn parse_message_with_protocol(message: impl AsRef<str>, mode: ParseMode, protocol: ProtocolRevision) -> Result<FsdMessageType, errors::FsdMessageParseError> {
+    FsdMessageType::identify_with_protocol(message.as_ref(), mode, protocol)
+}
+
+/// Deserialises an FSD message received as raw bytes into a struct.
+/// 
+/// The message must be valid UTF-8, except for the free text fields which older clients may send in another encoding: the real name of a pilot or ATC registration and the remarks of a flight plan.
+/// These are decoded as Windows-1252 if they are not valid UTF-8. Any other invalid byte results in an [`InvalidEncoding`][errors::FsdMessageParseError::InvalidEncoding] error.
+pub fn parse_message_bytes(message: impl AsRef<[u8]>) -> Result<FsdMessageType, errors::FsdMessageParseError> {
+    FsdMessageType::identify_bytes(message.as_ref(), ParseMode::default())
 }
 
 
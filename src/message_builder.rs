@@ -0,0 +1,254 @@
+use crate::{
+    enums::{PilotRating, TransponderMode},
+    errors::BuilderError,
+    messages::{
+        ClientQueryMessage, MetarRequestMessage, PilotPositionUpdateMessage, TextMessage,
+        AIRCRAFT_HANDLER_RECIPIENT, SERVER_CALLSIGN,
+    },
+    structs::TransponderCode,
+};
+
+/// Builds the most commonly sent messages on behalf of a single connected client, filling in its callsign as the
+/// sender of each.
+///
+/// Every method returns the message ready to be sent, as produced by the matching constructor on the message type.
+/// For anything not covered here, use those constructors directly.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{MessageBuilder, PilotRating, TransponderMode};
+/// let pilot = MessageBuilder::new("baw123");
+/// assert_eq!("BAW123", pilot.callsign());
+/// assert_eq!(
+///     "#TMBAW123:EGLL_N_TWR:Request startup",
+///     pilot.text_to("EGLL_N_TWR", "Request startup").to_string()
+/// );
+/// assert_eq!("$AXBAW123:SERVER:METAR:EGLL", pilot.request_metar("egll").to_string());
+/// assert_eq!(
+///     "@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769922:-8",
+///     pilot
+///         .position(
+///             TransponderMode::ModeC, "7000".parse().unwrap(), PilotRating::Student,
+///             51.47, -0.45, 83.0, 75.0, 0, 0.7, 0.35, 90.0, true,
+///         )
+///         .to_string()
+/// );
+///
+/// let controller = MessageBuilder::new("EGLL_N_TWR");
+/// assert_eq!(
+///     "$CQEGLL_N_TWR:@94835:BC:BAW123:4721",
+///     controller.set_squawk("BAW123", "4721".parse().unwrap()).to_string()
+/// );
+/// ```
+///
+/// Each message parses back to what was built:
+///
+/// ```
+/// use fsd_interface::{parse_message, MessageBuilder};
+/// let pilot = MessageBuilder::new("BAW123");
+/// for message in [
+///     pilot.text_to("EGLL_N_TWR", "Request startup").to_string(),
+///     pilot.request_metar("EGLL").to_string(),
+///     pilot.set_squawk("EZY38UB", "1234".parse().unwrap()).to_string(),
+/// ] {
+///     assert_eq!(message, parse_message(&message).unwrap().to_string());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageBuilder {
+    callsign: String,
+}
+
+impl MessageBuilder {
+    pub fn new(callsign: impl AsRef<str>) -> Self {
+        MessageBuilder {
+            callsign: callsign.as_ref().to_uppercase(),
+        }
+    }
+
+    /// The callsign messages are sent from
+    pub fn callsign(&self) -> &str {
+        &self.callsign
+    }
+
+    /// A private text message to another client
+    pub fn text_to(&self, to: impl AsRef<str>, message: impl Into<String>) -> TextMessage {
+        TextMessage::new(&self.callsign, to, message)
+    }
+
+    /// A pilot position update for this client's aircraft
+    #[allow(clippy::too_many_arguments)]
+    pub fn position(
+        &self,
+        transponder_mode: TransponderMode,
+        transponder_code: TransponderCode,
+        rating: PilotRating,
+        latitude: f64,
+        longitude: f64,
+        true_altitude: f64,
+        pressure_altitude: f64,
+        ground_speed: u32,
+        pitch: f64,
+        bank: f64,
+        heading: f64,
+        on_ground: bool,
+    ) -> PilotPositionUpdateMessage {
+        PilotPositionUpdateMessage::new(
+            &self.callsign,
+            transponder_mode,
+            transponder_code,
+            rating,
+            latitude,
+            longitude,
+            true_altitude,
+            pressure_altitude,
+            ground_speed,
+            pitch,
+            bank,
+            heading,
+            on_ground,
+        )
+    }
+
+    /// Requests the METAR for a station from the server
+    pub fn request_metar(&self, station: impl AsRef<str>) -> MetarRequestMessage {
+        MetarRequestMessage::new(&self.callsign, SERVER_CALLSIGN, station)
+    }
+
+    /// Assigns a squawk code to an aircraft, informing every other controller
+    pub fn set_squawk(
+        &self,
+        aircraft: impl AsRef<str>,
+        code: TransponderCode,
+    ) -> ClientQueryMessage {
+        ClientQueryMessage::set_beacon_code(
+            &self.callsign,
+            AIRCRAFT_HANDLER_RECIPIENT,
+            aircraft,
+            code,
+        )
+    }
+}
+
+/// Builds a [`PilotPositionUpdateMessage`] field by field, as an alternative to the long list of positional arguments
+/// taken by [`PilotPositionUpdateMessage::new`].
+///
+/// The callsign, transponder, rating, position and altitudes must be set, and [`build`][Self::build] fails if any of
+/// them is missing. The ground speed and attitude default to zero, and the aircraft to being airborne.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{errors::BuilderError, PilotPositionUpdateBuilder, PilotRating, TransponderMode};
+/// let position = PilotPositionUpdateBuilder::new()
+///     .callsign("baw123")
+///     .transponder(TransponderMode::ModeC, "7000".parse().unwrap())
+///     .rating(PilotRating::Student)
+///     .position(51.47, -0.45)
+///     .altitudes(83.0, 75.0)
+///     .attitude(0.7, 0.35, 90.0)
+///     .on_ground(true)
+///     .build()
+///     .unwrap();
+/// assert_eq!("@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769922:-8", position.to_string());
+///
+/// let missing = PilotPositionUpdateBuilder::new()
+///     .callsign("BAW123")
+///     .transponder(TransponderMode::ModeC, "7000".parse().unwrap())
+///     .rating(PilotRating::Student)
+///     .altitudes(83.0, 75.0)
+///     .build();
+/// assert_eq!(Err(BuilderError::MissingField("position")), missing);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PilotPositionUpdateBuilder {
+    callsign: Option<String>,
+    transponder: Option<(TransponderMode, TransponderCode)>,
+    rating: Option<PilotRating>,
+    position: Option<(f64, f64)>,
+    altitudes: Option<(f64, f64)>,
+    ground_speed: u32,
+    attitude: (f64, f64, f64),
+    on_ground: bool,
+}
+
+impl PilotPositionUpdateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn callsign(mut self, callsign: impl AsRef<str>) -> Self {
+        self.callsign = Some(callsign.as_ref().to_uppercase());
+        self
+    }
+
+    pub fn transponder(mut self, mode: TransponderMode, code: TransponderCode) -> Self {
+        self.transponder = Some((mode, code));
+        self
+    }
+
+    pub fn rating(mut self, rating: PilotRating) -> Self {
+        self.rating = Some(rating);
+        self
+    }
+
+    /// The latitude and longitude, in degrees
+    pub fn position(mut self, latitude: f64, longitude: f64) -> Self {
+        self.position = Some((latitude, longitude));
+        self
+    }
+
+    /// The true and pressure altitudes, in feet
+    pub fn altitudes(mut self, true_altitude: f64, pressure_altitude: f64) -> Self {
+        self.altitudes = Some((true_altitude, pressure_altitude));
+        self
+    }
+
+    /// The ground speed, in knots
+    pub fn ground_speed(mut self, ground_speed: u32) -> Self {
+        self.ground_speed = ground_speed;
+        self
+    }
+
+    /// The pitch, bank and heading, in degrees
+    pub fn attitude(mut self, pitch: f64, bank: f64, heading: f64) -> Self {
+        self.attitude = (pitch, bank, heading);
+        self
+    }
+
+    pub fn on_ground(mut self, on_ground: bool) -> Self {
+        self.on_ground = on_ground;
+        self
+    }
+
+    pub fn build(self) -> Result<PilotPositionUpdateMessage, BuilderError> {
+        let callsign = self
+            .callsign
+            .ok_or(BuilderError::MissingField("callsign"))?;
+        let (transponder_mode, transponder_code) = self
+            .transponder
+            .ok_or(BuilderError::MissingField("transponder"))?;
+        let rating = self.rating.ok_or(BuilderError::MissingField("rating"))?;
+        let (latitude, longitude) = self
+            .position
+            .ok_or(BuilderError::MissingField("position"))?;
+        let (true_altitude, pressure_altitude) = self
+            .altitudes
+            .ok_or(BuilderError::MissingField("altitudes"))?;
+        let (pitch, bank, heading) = self.attitude;
+        Ok(PilotPositionUpdateMessage::new(
+            callsign,
+            transponder_mode,
+            transponder_code,
+            rating,
+            latitude,
+            longitude,
+            true_altitude,
+            pressure_altitude,
+            self.ground_speed,
+            pitch,
+            bank,
+            heading,
+            self.on_ground,
+        ))
+    }
+}
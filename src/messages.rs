@@ -2,25 +2,75 @@
 //!
 //!
 
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    sync::OnceLock,
+    time::{Duration, Instant, SystemTime},
+};
 
 use bevy_reflect::Reflect;
 
 use crate::{
     aircraft_config::AircraftConfig,
     enums::{
-        AtcRating, AtcType, AtisLine, ClientCapability, ClientQueryType, ClientResponseType,
-        PilotRating, ProtocolRevision, SharedStateType, SimulatorType, TransponderMode,
-        VoiceCapability,
+        AdvertisedCapability, AtcRating, AtcType, AtisLine, ClientCapability, ClientQueryType,
+        ClientResponseType, ParseMode, PilotRating, ProtocolRevision, SharedStateType,
+        SimulatorType, TransponderMode, VoiceCapability,
     },
     errors::{FsdError, FsdMessageParseError},
-    structs::{FlightPlan, PlaneInfo, RadioFrequency, TransponderCode},
+    structs::{
+        CapabilitySet, Elevation, FlightPlan, GroundConsistency, Level, PlaneInfo, PositionSanity,
+        RadioFrequency, TransponderCode,
+    },
     util,
 };
 
 pub const SERVER_CALLSIGN: &str = "SERVER";
 pub const ATC_TEXT_CHANNEL_FREQUENCY: RadioFrequency = RadioFrequency(149, 999);
 pub const AIRCRAFT_HANDLER_RECIPIENT: &str = "@94835";
+pub const BROADCAST_RECIPIENT: &str = "*";
+
+/// The largest difference between two floating point fields, such as coordinates, altitudes and velocities, for which
+/// the messages holding them still compare equal.
+///
+/// Every message with floating point fields compares them to within this tolerance rather than exactly. This absorbs
+/// the error introduced by formatting a parsed message and parsing it again, so that a message survives the round
+/// trip unchanged. It is far smaller than the precision of any field on the wire, so values which would be written
+/// differently compare unequal. Pitch, bank and heading are compared modulo 360°, so a heading just short of 360° is
+/// equal to one of 0°.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, FsdMessageType};
+/// let line = "@N:BAW123:7000:1:51.47812:-0.45123:1200:250:4290769920:-8";
+/// let parsed = parse_message(line).unwrap();
+/// let reparsed = parse_message(parsed.to_string()).unwrap();
+/// assert_eq!(parsed, reparsed);
+///
+/// let FsdMessageType::PilotPositionUpdateMessage(mut position) = reparsed else {
+///     panic!("expected a pilot position update");
+/// };
+/// position.latitude += 1e-9;
+/// assert_eq!(parsed, FsdMessageType::PilotPositionUpdateMessage(position.clone()));
+/// position.latitude += 1e-5;
+/// assert_ne!(parsed, FsdMessageType::PilotPositionUpdateMessage(position.clone()));
+///
+/// let mut north = position.clone();
+/// north.heading = 0.0;
+/// position.heading = 359.9999999;
+/// assert_eq!(north, position);
+/// ```
+pub const FLOAT_TOLERANCE: f64 = 1e-6;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= FLOAT_TOLERANCE
+}
+
+/// Compares two angles in degrees to within [`FLOAT_TOLERANCE`], going the shorter way round the circle
+fn approx_eq_degrees(a: f64, b: f64) -> bool {
+    let difference = (a - b).rem_euclid(360.0);
+    difference.min(360.0 - difference) <= FLOAT_TOLERANCE
+}
 
 macro_rules! check_min_num_fields {
     ($fields: ident, $i: literal) => {
@@ -38,8 +88,17 @@ macro_rules! check_exact_num_fields {
     };
 }
 
+macro_rules! check_max_num_fields {
+    ($fields: ident, $i: literal) => {
+        if $fields.len() > $i {
+            return Err(FsdMessageParseError::InvalidFieldCount($i, $fields.len()));
+        }
+    };
+}
+
 /// Sent by an ATC client to register itself on the network after the initial handshake
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AtcRegisterMessage {
     pub from: String,
     pub to: String,
@@ -61,7 +120,7 @@ impl Display for AtcRegisterMessage {
             self.real_name,
             self.cid,
             self.password,
-            self.rating as u8,
+            self.rating.as_u8(),
             self.protocol as u8
         )
     }
@@ -107,7 +166,53 @@ impl AtcRegisterMessage {
 }
 
 /// Sent by a pilot client to register itself on the network after the initial handshake
-#[derive(Debug, Clone)]
+///
+/// `#AP(CALLSIGN):(RECIPIENT):(CID):(PASSWORD):(RATING):(PROTOCOL):(SIMULATOR TYPE)[:(REAL NAME)]`
+///
+/// The real name field is sent by clients using the VATSIM protocol revisions ([`ProtocolRevision::VatsimNoAuth`] onwards).
+/// Clients on [`ProtocolRevision::Classic`] servers may omit it, in which case `real_name` is `None`.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{messages::PilotRegisterMessage, parse_message, FsdMessageType};
+/// use fsd_interface::{PilotRating, ProtocolRevision, SimulatorType};
+/// let message = PilotRegisterMessage::new(
+///     "EZY38UB", "SERVER", "Joe Bloggs EGGW", "1234567", "password",
+///     PilotRating::Student, ProtocolRevision::Vatsim2022, SimulatorType::MSFSX,
+/// );
+/// let wire = message.to_string();
+/// assert_eq!("#APEZY38UB:SERVER:1234567:password:1:101:9:Joe Bloggs EGGW", wire);
+///
+/// let FsdMessageType::PilotRegisterMessage(reparsed) = parse_message(&wire).unwrap() else {
+///     panic!("expected a pilot register message");
+/// };
+/// assert_eq!(Some("Joe Bloggs EGGW"), reparsed.real_name.as_deref());
+/// assert_eq!("1234567", reparsed.cid);
+///
+/// // Legacy form without the real name
+/// let legacy = "#APEZY38UB:SERVER:newcert::1:9:1";
+/// let FsdMessageType::PilotRegisterMessage(reparsed) = parse_message(legacy).unwrap() else {
+///     panic!("expected a pilot register message");
+/// };
+/// assert_eq!(None, reparsed.real_name);
+/// assert_eq!(ProtocolRevision::Classic, reparsed.protocol);
+/// assert_eq!(legacy, reparsed.to_string());
+///
+/// let built = PilotRegisterMessage::without_real_name(
+///     "EZY38UB", "SERVER", "newcert", "", PilotRating::Student, ProtocolRevision::Classic, SimulatorType::MSFS95,
+/// );
+/// assert_eq!(legacy, built.to_string());
+///
+/// // A simulator the crate doesn't know about doesn't prevent registration
+/// let new_sim = "#APEZY38UB:SERVER:1234567:password:1:101:77:Joe Bloggs EGGW";
+/// let FsdMessageType::PilotRegisterMessage(reparsed) = parse_message(new_sim).unwrap() else {
+///     panic!("expected a pilot register message");
+/// };
+/// assert_eq!(SimulatorType::Unknown(77), reparsed.simulator_type);
+/// assert_eq!(new_sim, reparsed.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PilotRegisterMessage {
     pub from: String,
     pub to: String,
@@ -116,23 +221,26 @@ pub struct PilotRegisterMessage {
     pub rating: PilotRating,
     pub protocol: ProtocolRevision,
     pub simulator_type: SimulatorType,
-    pub real_name: String,
+    pub real_name: Option<String>,
 }
 //#APEZY38UB:SERVER:newcert::1:1:1
 impl Display for PilotRegisterMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "#AP{}:{}:{}:{}:{}:{}:{}:{}",
+            "#AP{}:{}:{}:{}:{}:{}:{}",
             self.from,
             self.to,
             self.cid,
             self.password,
-            self.rating as u8,
+            self.rating.as_u8(),
             self.protocol as u8,
-            self.simulator_type as u8,
-            self.real_name
-        )
+            self.simulator_type.as_u8(),
+        )?;
+        if let Some(ref real_name) = self.real_name {
+            write!(f, ":{}", real_name)?;
+        }
+        Ok(())
     }
 }
 impl TryFrom<&[&str]> for PilotRegisterMessage {
@@ -140,19 +248,21 @@ impl TryFrom<&[&str]> for PilotRegisterMessage {
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
         check_min_num_fields!(fields, 7);
         let first = &fields[0][3..];
-        Ok(PilotRegisterMessage::new(
+        let mut message = PilotRegisterMessage::without_real_name(
             first,
             fields[1],
-            *fields.get(7).unwrap_or(&""),
             fields[2],
             fields[3],
             fields[4].parse()?,
             fields[5].parse()?,
             fields[6].parse()?,
-        ))
+        );
+        message.real_name = (fields.len() > 7).then(|| util::assemble_with_colons(&fields[7..]));
+        Ok(message)
     }
 }
 impl PilotRegisterMessage {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
@@ -162,6 +272,31 @@ impl PilotRegisterMessage {
         rating: PilotRating,
         protocol: ProtocolRevision,
         simulator_type: SimulatorType,
+    ) -> Self {
+        PilotRegisterMessage {
+            real_name: Some(real_name.into()),
+            ..PilotRegisterMessage::without_real_name(
+                from,
+                to,
+                cid,
+                password,
+                rating,
+                protocol,
+                simulator_type,
+            )
+        }
+    }
+
+    /// Creates a registration without the real name field, as sent by clients on [`ProtocolRevision::Classic`]
+    /// servers
+    pub fn without_real_name(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        cid: impl Into<String>,
+        password: impl Into<String>,
+        rating: PilotRating,
+        protocol: ProtocolRevision,
+        simulator_type: SimulatorType,
     ) -> Self {
         PilotRegisterMessage {
             from: from.as_ref().to_uppercase(),
@@ -171,21 +306,28 @@ impl PilotRegisterMessage {
             password: password.into(),
             rating,
             protocol,
-            real_name: real_name.into(),
+            real_name: None,
         }
     }
 }
 
 /// Sent by an ATC client before disconnecting
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AtcDeregisterMessage {
     pub from: String,
     pub cid: String,
+    /// Appended by some servers to give the reason for the disconnection
+    pub reason: Option<String>,
 }
 
 impl Display for AtcDeregisterMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "#DA{}:{}", self.from, self.cid)
+        write!(f, "#DA{}:{}", self.from, self.cid)?;
+        if let Some(ref reason) = self.reason {
+            write!(f, ":{}", reason)?;
+        }
+        Ok(())
     }
 }
 
@@ -194,7 +336,10 @@ impl TryFrom<&[&str]> for AtcDeregisterMessage {
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
         check_min_num_fields!(fields, 2);
         let first = &fields[0][3..];
-        Ok(AtcDeregisterMessage::new(first, fields[1]))
+        Ok(match fields.get(2) {
+            Some(reason) => AtcDeregisterMessage::with_reason(first, fields[1], *reason),
+            None => AtcDeregisterMessage::new(first, fields[1]),
+        })
     }
 }
 
@@ -203,20 +348,40 @@ impl AtcDeregisterMessage {
         AtcDeregisterMessage {
             from: from.as_ref().to_uppercase(),
             cid: cid.into(),
+            reason: None,
+        }
+    }
+
+    /// Creates a deregistration carrying the reason for the disconnection, as appended by some servers
+    pub fn with_reason(
+        from: impl AsRef<str>,
+        cid: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        AtcDeregisterMessage {
+            reason: Some(reason.into()),
+            ..AtcDeregisterMessage::new(from, cid)
         }
     }
 }
 
 /// Sent by a pilot client before disconnecting
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PilotDeregisterMessage {
     pub from: String,
     pub cid: String,
+    /// Appended by some servers to give the reason for the disconnection
+    pub reason: Option<String>,
 }
 
 impl Display for PilotDeregisterMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "#DP{}:{}", self.from, self.cid)
+        write!(f, "#DP{}:{}", self.from, self.cid)?;
+        if let Some(ref reason) = self.reason {
+            write!(f, ":{}", reason)?;
+        }
+        Ok(())
     }
 }
 
@@ -225,7 +390,10 @@ impl TryFrom<&[&str]> for PilotDeregisterMessage {
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
         check_min_num_fields!(fields, 2);
         let first = &fields[0][3..];
-        Ok(PilotDeregisterMessage::new(first, fields[1]))
+        Ok(match fields.get(2) {
+            Some(reason) => PilotDeregisterMessage::with_reason(first, fields[1], *reason),
+            None => PilotDeregisterMessage::new(first, fields[1]),
+        })
     }
 }
 
@@ -234,12 +402,155 @@ impl PilotDeregisterMessage {
         PilotDeregisterMessage {
             from: from.as_ref().to_uppercase(),
             cid: cid.into(),
+            reason: None,
+        }
+    }
+
+    /// Creates a deregistration carrying the reason for the disconnection, as appended by some servers
+    pub fn with_reason(
+        from: impl AsRef<str>,
+        cid: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        PilotDeregisterMessage {
+            reason: Some(reason.into()),
+            ..PilotDeregisterMessage::new(from, cid)
         }
     }
 }
 
+/// Common interface of [`AtcDeregisterMessage`] and [`PilotDeregisterMessage`], so that disconnects can be handled
+/// without duplicating logic for ATC and pilot clients.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{messages::{DeregisterMessage, PilotDeregisterMessage}, parse_message, FsdMessageType};
+/// let disconnects = [
+///     ("#DAEGLL_N_TWR:1234567", "EGLL_N_TWR", "1234567", true),
+///     ("#DPBAW123:7654321", "BAW123", "7654321", false),
+/// ];
+/// for (line, callsign, cid, atc) in disconnects {
+///     let message = parse_message(line).unwrap();
+///     let deregister = message.as_deregister().unwrap();
+///     assert_eq!(callsign, deregister.from());
+///     assert_eq!(cid, deregister.cid());
+///     assert_eq!(atc, deregister.is_atc());
+///     assert_eq!(None, deregister.reason());
+/// }
+///
+/// let line = "#DPBAW123:7654321:Kicked by supervisor";
+/// let FsdMessageType::PilotDeregisterMessage(kicked) = parse_message(line).unwrap() else {
+///     panic!("expected a pilot deregister message");
+/// };
+/// assert_eq!(Some("Kicked by supervisor"), kicked.reason());
+/// assert_eq!(line, kicked.to_string());
+///
+/// let built = PilotDeregisterMessage::with_reason("BAW123", "7654321", "Kicked by supervisor");
+/// assert_eq!(line, built.to_string());
+/// ```
+pub trait DeregisterMessage {
+    /// The callsign of the client that is disconnecting
+    fn from(&self) -> &str;
+    /// The CID of the client that is disconnecting
+    fn cid(&self) -> &str;
+    /// The reason for the disconnection, if the server gave one
+    fn reason(&self) -> Option<&str>;
+    /// Returns true for an ATC client, false for a pilot client
+    fn is_atc(&self) -> bool;
+}
+
+impl DeregisterMessage for AtcDeregisterMessage {
+    fn from(&self) -> &str {
+        &self.from
+    }
+    fn cid(&self) -> &str {
+        &self.cid
+    }
+    fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+    fn is_atc(&self) -> bool {
+        true
+    }
+}
+
+impl DeregisterMessage for PilotDeregisterMessage {
+    fn from(&self) -> &str {
+        &self.from
+    }
+    fn cid(&self) -> &str {
+        &self.cid
+    }
+    fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+    fn is_atc(&self) -> bool {
+        false
+    }
+}
+
 /// Sent at regular intervals by an ATC client to update the server with its position
+///
+/// `%(CALLSIGN):(FREQUENCIES):(FACILITY TYPE):(VISIBILITY RANGE):(RATING):(LATITUDE):(LONGITUDE)[:(ELEVATION)]`
+///
+/// Every field up to and including the longitude is mandatory, and a malformed value in any of them is an error.
+/// The elevation is optional, as some clients do not send it or send it as an empty field, and defaults to 0 when
+/// absent. A malformed elevation is an error in [`ParseMode::Strict`], and is read as 0 in [`ParseMode::Lenient`].
+/// The frequency list may be empty, but it must still be present as an empty field. [`AtcPositionUpdateMessage::parse`]
+/// takes the mode, and [`TryFrom`] parses strictly.
+///
+/// Coordinates are written to 5 decimal places (about 1 m), the precision ATC clients use.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{errors::FsdMessageParseError, messages::AtcPositionUpdateMessage, parse_message};
+/// use fsd_interface::{parse_message_with_mode, AtcRating, AtcType, FsdMessageType, ParseMode};
+/// let full = "%EGLL_N_TWR:18650:4:30:5:51.47800:-0.45000:83";
+/// let FsdMessageType::AtcPositionUpdateMessage(update) = parse_message(full).unwrap() else {
+///     panic!("expected an ATC position update");
+/// };
+/// assert_eq!(AtcType::Tower, update.atc_type);
+/// assert_eq!(AtcRating::C1, update.rating);
+/// assert_eq!(83, update.elevation.feet());
+/// assert_eq!(full, update.to_string());
+///
+/// // Without the trailing elevation
+/// let minimal = "%EGLL_OBS::0:100:1:51.47800:-0.45000";
+/// let FsdMessageType::AtcPositionUpdateMessage(update) = parse_message(minimal).unwrap() else {
+///     panic!("expected an ATC position update");
+/// };
+/// assert!(update.frequencies.is_empty());
+/// assert_eq!(0, update.elevation.feet());
+///
+/// // With the elevation left empty
+/// let FsdMessageType::AtcPositionUpdateMessage(update) = parse_message("%EGLL_OBS::0:100:1:51.47800:-0.45000:").unwrap() else {
+///     panic!("expected an ATC position update");
+/// };
+/// assert_eq!(0, update.elevation.feet());
+///
+/// // The rating is mandatory
+/// assert!(matches!(
+///     parse_message("%EGLL_N_TWR:18650:4:30::51.47800:-0.45000:83"),
+///     Err(FsdMessageParseError::InvalidRating(_))
+/// ));
+///
+/// // A malformed elevation is only rejected in strict mode
+/// let malformed = "%EGLL_N_TWR:18650:4:30:5:51.47800:-0.45000:high";
+/// assert!(matches!(
+///     parse_message_with_mode(malformed, ParseMode::Strict),
+///     Err(FsdMessageParseError::InvalidAltitude(_))
+/// ));
+/// let FsdMessageType::AtcPositionUpdateMessage(update) = parse_message(malformed).unwrap() else {
+///     panic!("expected an ATC position update");
+/// };
+/// assert_eq!(0, update.elevation.feet());
+///
+/// let fields: Vec<&str> = malformed.split(':').collect();
+/// assert_eq!(update, AtcPositionUpdateMessage::parse(&fields, ParseMode::Lenient).unwrap());
+/// assert!(AtcPositionUpdateMessage::try_from(fields.as_slice()).is_err());
+/// ```
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AtcPositionUpdateMessage {
     pub callsign: String,
     pub frequencies: Vec<RadioFrequency>,
@@ -248,7 +559,20 @@ pub struct AtcPositionUpdateMessage {
     pub rating: AtcRating,
     pub latitude: f64,
     pub longitude: f64,
-    pub elevation: i32,
+    pub elevation: Elevation,
+}
+
+impl PartialEq for AtcPositionUpdateMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.callsign == other.callsign
+            && self.frequencies == other.frequencies
+            && self.atc_type == other.atc_type
+            && self.vis_range == other.vis_range
+            && self.rating == other.rating
+            && approx_eq(self.latitude, other.latitude)
+            && approx_eq(self.longitude, other.longitude)
+            && self.elevation == other.elevation
+    }
 }
 
 impl Display for AtcPositionUpdateMessage {
@@ -261,7 +585,7 @@ impl Display for AtcPositionUpdateMessage {
             freqs_string,
             self.atc_type as u8,
             self.vis_range,
-            self.rating as u8,
+            self.rating.as_u8(),
             self.latitude,
             self.longitude,
             self.elevation
@@ -272,8 +596,24 @@ impl Display for AtcPositionUpdateMessage {
 impl TryFrom<&[&str]> for AtcPositionUpdateMessage {
     type Error = FsdMessageParseError;
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
+        AtcPositionUpdateMessage::parse(fields, ParseMode::Strict)
+    }
+}
+
+impl AtcPositionUpdateMessage {
+    /// Parses a `%` message, applying the rules for mandatory and defaulted fields described on
+    /// [`AtcPositionUpdateMessage`] for the given mode
+    pub fn parse(fields: &[&str], mode: ParseMode) -> Result<Self, FsdMessageParseError> {
         check_min_num_fields!(fields, 7);
         let first = &fields[0][1..];
+        let elevation = match fields.get(7) {
+            Some(elevation) if !elevation.is_empty() => match elevation.parse() {
+                Ok(elevation) => elevation,
+                Err(_) if mode == ParseMode::Lenient => Elevation::default(),
+                Err(e) => return Err(e),
+            },
+            _ => Elevation::default(),
+        };
         Ok(AtcPositionUpdateMessage::new(
             first,
             util::split_frequencies(fields[1]),
@@ -288,12 +628,11 @@ impl TryFrom<&[&str]> for AtcPositionUpdateMessage {
             fields[6]
                 .parse()
                 .map_err(|_| FsdMessageParseError::InvalidCoordinate(fields[6].to_string()))?,
-            fields.get(7).unwrap_or(&"0").parse().unwrap_or_default(),
+            elevation,
         ))
     }
-}
 
-impl AtcPositionUpdateMessage {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         callsign: impl AsRef<str>,
         frequencies: impl Into<Vec<RadioFrequency>>,
@@ -302,7 +641,7 @@ impl AtcPositionUpdateMessage {
         rating: AtcRating,
         latitude: f64,
         longitude: f64,
-        elevation: i32,
+        elevation: impl Into<Elevation>,
     ) -> Self {
         AtcPositionUpdateMessage {
             callsign: callsign.as_ref().to_uppercase(),
@@ -312,12 +651,194 @@ impl AtcPositionUpdateMessage {
             rating,
             latitude,
             longitude,
-            elevation,
+            elevation: elevation.into(),
         }
     }
+
+    /// Returns true if the given position lies within the controller's visibility range (in nautical miles)
+    /// of either its primary centre or any of its secondary vis centres.
+    ///
+    /// Secondary vis centres belonging to other callsigns are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::*, AtcRating, AtcType};
+    /// let primary = AtcPositionUpdateMessage::new(
+    ///     "EGTT_CTR", vec![], AtcType::Centre, 50, AtcRating::C1, 51.47, -0.45, 0,
+    /// );
+    /// let secondaries = [AtcSecondaryVisCentreMessage::new("EGTT_CTR", 0, 53.35, -2.27)];
+    ///
+    /// // Near London: inside the primary circle, outside the secondary
+    /// assert!(primary.is_visible(&secondaries, 51.5, -0.1));
+    /// // Near Manchester: outside the primary circle, inside the secondary
+    /// assert!(!primary.is_visible(&[], 53.4, -2.3));
+    /// assert!(primary.is_visible(&secondaries, 53.4, -2.3));
+    /// // Paris is outside both
+    /// assert!(!primary.is_visible(&secondaries, 48.85, 2.35));
+    /// ```
+    pub fn is_visible(
+        &self,
+        secondaries: &[AtcSecondaryVisCentreMessage],
+        ac_lat: f64,
+        ac_lon: f64,
+    ) -> bool {
+        let range = self.vis_range as f64;
+        std::iter::once((self.latitude, self.longitude))
+            .chain(
+                secondaries
+                    .iter()
+                    .filter(|secondary| secondary.callsign == self.callsign)
+                    .map(|secondary| (secondary.latitude, secondary.longitude)),
+            )
+            .any(|(lat, lon)| util::great_circle_distance_nm(lat, lon, ac_lat, ac_lon) <= range)
+    }
+
+    /// Returns the primary frequency of this controller if the other controller primaries the same channel and
+    /// their coverage overlaps, i.e. the great circle distance between their vis centres is no more than the sum of
+    /// their visibility ranges (in nautical miles).
+    ///
+    /// Only the first frequency of each controller, its primary, is compared, and only the primary vis centres.
+    /// Frequencies are compared by [`RadioFrequency::canonical_channel`], so 132.308 and 132.310 conflict. The special
+    /// frequencies outside the airband, such as 199.998 used by observers, never conflict.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::*, AtcRating, AtcType, RadioFrequency};
+    /// let freq = |s: &str| RadioFrequency::try_from_human_readable_string(s).unwrap();
+    /// let tower = |callsign, frequencies: &[&str], lat, lon| AtcPositionUpdateMessage::new(
+    ///     callsign, frequencies.iter().map(|&f| freq(f)).collect::<Vec<_>>(), AtcType::Tower, 30, AtcRating::S2, lat,
+    ///     lon, 0,
+    /// );
+    /// let heathrow = tower("EGLL_N_TWR", &["132.310"], 51.47, -0.45);
+    ///
+    /// // Heathrow and Gatwick are about 22 nm apart
+    /// let gatwick = tower("EGKK_TWR", &["132.308"], 51.15, -0.18);
+    /// assert_eq!(Some(freq("132.310")), heathrow.frequency_conflict(&gatwick));
+    /// assert_eq!(Some(freq("132.308")), gatwick.frequency_conflict(&heathrow));
+    /// // A frequency other than the primary is not a conflict
+    /// let gatwick = tower("EGKK_TWR", &["124.225", "132.310"], 51.15, -0.18);
+    /// assert_eq!(None, heathrow.frequency_conflict(&gatwick));
+    /// // Manchester is well over 60 nm away
+    /// let manchester = tower("EGCC_TWR", &["132.310"], 53.35, -2.27);
+    /// assert_eq!(None, heathrow.frequency_conflict(&manchester));
+    ///
+    /// let observer = |callsign| AtcPositionUpdateMessage::new(
+    ///     callsign, vec![freq("199.998")], AtcType::Observer, 300, AtcRating::Observer, 51.47, -0.45, 0,
+    /// );
+    /// assert_eq!(None, observer("EGLL_OBS").frequency_conflict(&observer("EGKK_OBS")));
+    /// ```
+    pub fn frequency_conflict(&self, other: &AtcPositionUpdateMessage) -> Option<RadioFrequency> {
+        let primary = *self.frequencies.first()?;
+        let other_primary = *other.frequencies.first()?;
+        if !(118..=137).contains(&primary.0)
+            || primary.canonical_channel() != other_primary.canonical_channel()
+        {
+            return None;
+        }
+        let distance = util::great_circle_distance_nm(
+            self.latitude,
+            self.longitude,
+            other.latitude,
+            other.longitude,
+        );
+        (distance <= f64::from(self.vis_range) + f64::from(other.vis_range)).then_some(primary)
+    }
+
+    /// Exports the controller's coverage as a GeoJSON `FeatureCollection`, for display on a web map.
+    ///
+    /// Each vis centre becomes a `Polygon` feature approximating a circle with a radius of the controller's
+    /// visibility range. The primary centre comes first, followed by the secondary vis centres belonging to the
+    /// same callsign. Each feature's properties give the `callsign`, whether the centre is `primary` or
+    /// `secondary`, the secondary's `index` and the `vis_range` in nautical miles.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::*, AtcRating, AtcType};
+    /// let primary = AtcPositionUpdateMessage::new(
+    ///     "EGTT_CTR", vec![], AtcType::Centre, 50, AtcRating::C1, 51.47, -0.45, 0,
+    /// );
+    /// let secondaries = [
+    ///     AtcSecondaryVisCentreMessage::new("EGTT_CTR", 0, 53.35, -2.27),
+    ///     AtcSecondaryVisCentreMessage::new("EGPX_CTR", 0, 55.95, -3.37),
+    /// ];
+    ///
+    /// let geojson: serde_json::Value = serde_json::from_str(&primary.coverage_geojson(&secondaries)).unwrap();
+    /// assert_eq!("FeatureCollection", geojson["type"]);
+    /// let features = geojson["features"].as_array().unwrap();
+    /// assert_eq!(2, features.len());
+    /// assert_eq!("primary", features[0]["properties"]["centre"]);
+    /// assert_eq!("secondary", features[1]["properties"]["centre"]);
+    /// assert_eq!(0, features[1]["properties"]["index"]);
+    ///
+    /// let geometry = &features[0]["geometry"];
+    /// assert_eq!("Polygon", geometry["type"]);
+    /// let ring = geometry["coordinates"][0].as_array().unwrap();
+    /// assert_eq!(ring.first(), ring.last());
+    /// for point in ring {
+    ///     // GeoJSON positions are longitude first
+    ///     let (lon, lat) = (point[0].as_f64().unwrap(), point[1].as_f64().unwrap());
+    ///     assert!((50.6..52.4).contains(&lat), "{}", lat);
+    ///     assert!((-1.9..1.0).contains(&lon), "{}", lon);
+    /// }
+    /// ```
+    #[cfg(feature = "geojson")]
+    pub fn coverage_geojson(&self, secondaries: &[AtcSecondaryVisCentreMessage]) -> String {
+        const CIRCLE_SEGMENTS: usize = 64;
+
+        let range = self.vis_range as f64;
+        let circle = |lat: f64, lon: f64| {
+            (0..=CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let bearing = (i % CIRCLE_SEGMENTS) as f64 * 360.0 / CIRCLE_SEGMENTS as f64;
+                    let (lat, lon) = util::destination_point(lat, lon, bearing, range);
+                    serde_json::json!([lon, lat])
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut features = vec![serde_json::json!({
+            "type": "Feature",
+            "properties": {
+                "callsign": self.callsign,
+                "centre": "primary",
+                "vis_range": self.vis_range,
+            },
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [circle(self.latitude, self.longitude)],
+            },
+        })];
+        features.extend(
+            secondaries
+                .iter()
+                .filter(|secondary| secondary.callsign == self.callsign)
+                .map(|secondary| {
+                    serde_json::json!({
+                        "type": "Feature",
+                        "properties": {
+                            "callsign": secondary.callsign,
+                            "centre": "secondary",
+                            "index": secondary.index,
+                            "vis_range": self.vis_range,
+                        },
+                        "geometry": {
+                            "type": "Polygon",
+                            "coordinates": [circle(secondary.latitude, secondary.longitude)],
+                        },
+                    })
+                }),
+        );
+
+        serde_json::json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+        .to_string()
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AtcSecondaryVisCentreMessage {
     pub callsign: String,
     pub index: usize,
@@ -325,6 +846,15 @@ pub struct AtcSecondaryVisCentreMessage {
     pub longitude: f64,
 }
 
+impl PartialEq for AtcSecondaryVisCentreMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.callsign == other.callsign
+            && self.index == other.index
+            && approx_eq(self.latitude, other.latitude)
+            && approx_eq(self.longitude, other.longitude)
+    }
+}
+
 impl Display for AtcSecondaryVisCentreMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -367,7 +897,42 @@ impl AtcSecondaryVisCentreMessage {
 }
 
 /// Sent at regular intervals by a pilot client to update the server with its position
+///
+/// `@(TRANSPONDER MODE):(CALLSIGN):(TRANSPONDER CODE):(RATING):(LATITUDE):(LONGITUDE):(TRUE ALTITUDE):(GROUND SPEED):(PITCH BANK HEADING):(PRESSURE ALTITUDE DIFFERENCE)`
+///
+/// The transponder mode comes before the callsign, directly after the `@`, unlike most messages which start with the
+/// sender's callsign.
+///
+/// Coordinates are written to 5 decimal places (about 1 m), and the true altitude and the pressure altitude
+/// difference are rounded to the nearest foot, matching what pilot clients send. Anything finer is deliberately dropped.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, FsdMessageType, TransponderMode};
+/// let message = "@N:BAW123:7000:1:-89.99999:179.99999:41000:480:4290769920:-1450";
+/// let FsdMessageType::PilotPositionUpdateMessage(mut position) = parse_message(message).unwrap() else {
+///     panic!("expected a pilot position update");
+/// };
+/// assert_eq!("BAW123", position.callsign);
+/// assert_eq!(TransponderMode::ModeC, position.transponder_mode);
+/// assert_eq!(message, position.to_string());
+///
+/// let FsdMessageType::PilotPositionUpdateMessage(reparsed) = parse_message(position.to_string()).unwrap() else {
+///     panic!("expected a pilot position update");
+/// };
+/// assert_eq!(position, reparsed);
+///
+/// position.true_altitude = 1199.6;
+/// position.pressure_altitude = 1190.0;
+/// assert_eq!("@N:BAW123:7000:1:-89.99999:179.99999:1200:480:4290769920:-10", position.to_string());
+///
+/// // The difference is taken between the rounded altitudes, so the pressure altitude survives to the nearest foot
+/// position.true_altitude = 1000.4;
+/// position.pressure_altitude = 999.6;
+/// assert_eq!("@N:BAW123:7000:1:-89.99999:179.99999:1000:480:4290769920:0", position.to_string());
+/// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PilotPositionUpdateMessage {
     pub callsign: String,
     pub transponder_mode: TransponderMode,
@@ -384,24 +949,42 @@ pub struct PilotPositionUpdateMessage {
     pub on_ground: bool,
 }
 
+impl PartialEq for PilotPositionUpdateMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.callsign == other.callsign
+            && self.transponder_mode == other.transponder_mode
+            && self.transponder_code == other.transponder_code
+            && self.rating == other.rating
+            && approx_eq(self.latitude, other.latitude)
+            && approx_eq(self.longitude, other.longitude)
+            && approx_eq(self.true_altitude, other.true_altitude)
+            && approx_eq(self.pressure_altitude, other.pressure_altitude)
+            && self.ground_speed == other.ground_speed
+            && approx_eq_degrees(self.pitch, other.pitch)
+            && approx_eq_degrees(self.bank, other.bank)
+            && approx_eq_degrees(self.heading, other.heading)
+            && self.on_ground == other.on_ground
+    }
+}
+
 impl Display for PilotPositionUpdateMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pbh =
             util::encode_pitch_bank_heading(self.pitch, self.bank, self.heading, self.on_ground);
-        let alt_diff = self.pressure_altitude - self.true_altitude;
+        let alt_diff = util::encode_altitude_difference(self.true_altitude, self.pressure_altitude);
         write!(
             f,
             "@{}:{}:{}:{}:{:.5}:{:.5}:{}:{}:{}:{}",
             self.transponder_mode,
             self.callsign,
             self.transponder_code,
-            self.rating as u8,
+            self.rating.as_u8(),
             self.latitude,
             self.longitude,
-            self.true_altitude as i32,
+            self.true_altitude.round() as i32,
             self.ground_speed,
             pbh,
-            alt_diff as i32,
+            alt_diff,
         )
     }
 }
@@ -409,27 +992,99 @@ impl Display for PilotPositionUpdateMessage {
 impl TryFrom<&[&str]> for PilotPositionUpdateMessage {
     type Error = FsdMessageParseError;
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
-        check_min_num_fields!(fields, 10);
+        PilotPositionUpdateMessage::parse_with_protocol(
+            fields,
+            ProtocolRevision::Vatsim2022,
+            ParseMode::Strict,
+        )
+    }
+}
+
+impl PilotPositionUpdateMessage {
+    /// Parses a pilot position update sent by a client using the given protocol revision.
+    ///
+    /// The rating is not needed to plot the target, so in [`ParseMode::Lenient`] a missing or invalid rating is read
+    /// as [`PilotRating::Unknown`]. In [`ParseMode::Strict`] it is an error. [`TryFrom`] parses strictly.
+    ///
+    /// The pressure altitude difference was only added to the update in [`ProtocolRevision::VatsimAuth`]. For
+    /// earlier revisions it is optional, and when it is absent the pressure altitude is taken to be the true altitude.
+    /// [`TryFrom`] and [`parse_message`][crate::parse_message] parse updates as [`ProtocolRevision::Vatsim2022`], for
+    /// which it is required. To parse whole messages with another revision, use
+    /// [`parse_message_with_protocol`][crate::parse_message_with_protocol] or
+    /// [`ClientSession::parse_message`][crate::ClientSession::parse_message].
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::PilotPositionUpdateMessage, ParseMode, PilotRating, ProtocolRevision};
+    /// let classic: Vec<&str> = "@N:BAW123:7000:1:51.47000:-0.45000:1200:140:4290769920".split(':').collect();
+    /// let position =
+    ///     PilotPositionUpdateMessage::parse_with_protocol(&classic, ProtocolRevision::Classic, ParseMode::Strict).unwrap();
+    /// assert_eq!(1200.0, position.pressure_altitude);
+    /// assert!(PilotPositionUpdateMessage::try_from(classic.as_slice()).is_err());
+    ///
+    /// // The difference is still read if an older client sends it
+    /// let modern: Vec<&str> = "@N:BAW123:7000:1:51.47000:-0.45000:1200:140:4290769920:-50".split(':').collect();
+    /// let position =
+    ///     PilotPositionUpdateMessage::parse_with_protocol(&modern, ProtocolRevision::Classic, ParseMode::Strict).unwrap();
+    /// assert_eq!(1150.0, position.pressure_altitude);
+    ///
+    /// let unrated: Vec<&str> = "@N:BAW123:7000:X:51.47000:-0.45000:1200:140:4290769920:-50".split(':').collect();
+    /// let position =
+    ///     PilotPositionUpdateMessage::parse_with_protocol(&unrated, ProtocolRevision::Vatsim2022, ParseMode::Lenient)
+    ///         .unwrap();
+    /// assert_eq!(PilotRating::Unknown, position.rating);
+    /// assert!(PilotPositionUpdateMessage::try_from(unrated.as_slice()).is_err());
+    ///
+    /// // The unknown rating is written as 0, which reads back unchanged
+    /// let rewritten = position.to_string();
+    /// let rewritten: Vec<&str> = rewritten.split(':').collect();
+    /// assert_eq!(position, PilotPositionUpdateMessage::try_from(rewritten.as_slice()).unwrap());
+    /// ```
+    pub fn parse_with_protocol(
+        fields: &[&str],
+        protocol: ProtocolRevision,
+        mode: ParseMode,
+    ) -> Result<Self, FsdMessageParseError> {
+        let sends_altitude_difference = matches!(
+            protocol,
+            ProtocolRevision::VatsimAuth | ProtocolRevision::Vatsim2022
+        );
+        if sends_altitude_difference {
+            check_min_num_fields!(fields, 10);
+        } else {
+            check_min_num_fields!(fields, 9);
+        }
         let first = &fields[0][1..];
 
         let true_altitude = fields[6]
             .parse()
             .map_err(|_| FsdMessageParseError::InvalidAltitude(fields[6].to_string()))?;
-        let alt_diff: f64 = fields[9]
-            .parse()
-            .map_err(|_| FsdMessageParseError::InvalidAltitudeDifference(fields[9].to_string()))?;
+        let pressure_altitude = match fields.get(9) {
+            Some(alt_diff) => {
+                let alt_diff: f64 = alt_diff.parse().map_err(|_| {
+                    FsdMessageParseError::InvalidAltitudeDifference(alt_diff.to_string())
+                })?;
+                util::decode_altitude_difference(true_altitude, alt_diff)
+            }
+            None => true_altitude,
+        };
         let (pitch, bank, heading, on_ground) = {
             let pbh = fields[8].parse().map_err(|_| {
                 FsdMessageParseError::InvalidPitchBankHeading(fields[8].to_string())
             })?;
             util::decode_pitch_bank_heading(pbh)
         };
+        let rating = match fields[3].parse() {
+            Ok(rating) => rating,
+            Err(_) if mode == ParseMode::Lenient => PilotRating::Unknown,
+            Err(e) => return Err(e),
+        };
 
         Ok(PilotPositionUpdateMessage::new(
             fields[1],
             first.parse()?,
             fields[2].parse()?,
-            fields[3].parse()?,
+            rating,
             fields[4]
                 .parse()
                 .map_err(|_| FsdMessageParseError::InvalidCoordinate(fields[4].to_string()))?,
@@ -437,7 +1092,7 @@ impl TryFrom<&[&str]> for PilotPositionUpdateMessage {
                 .parse()
                 .map_err(|_| FsdMessageParseError::InvalidCoordinate(fields[5].to_string()))?,
             true_altitude,
-            true_altitude + alt_diff,
+            pressure_altitude,
             fields[7]
                 .parse()
                 .map_err(|_| FsdMessageParseError::InvalidSpeed(fields[7].to_string()))?,
@@ -447,9 +1102,14 @@ impl TryFrom<&[&str]> for PilotPositionUpdateMessage {
             on_ground,
         ))
     }
-}
 
-impl PilotPositionUpdateMessage {
+    /// Whether the pilot is squawking ident. Ident is momentary, so see [`IdentTracker`][crate::IdentTracker] to keep
+    /// a target highlighted for a while afterwards.
+    pub fn is_ident(&self) -> bool {
+        self.transponder_mode == TransponderMode::Ident
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         callsign: impl AsRef<str>,
         transponder_mode: TransponderMode,
@@ -481,9 +1141,183 @@ impl PilotPositionUpdateMessage {
             on_ground,
         }
     }
+
+    /// Best-effort checks for positions which cannot be genuine, for filtering garbage out of recorded data.
+    /// See [`PositionSanity`] for what is and isn't checked.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, FsdMessageType};
+    /// let message = "@N:BAW123:7000:1:51.47000:-0.45000:1200:250:4290769920:-8";
+    /// let FsdMessageType::PilotPositionUpdateMessage(position) = parse_message(message).unwrap() else {
+    ///     panic!("expected a pilot position update");
+    /// };
+    /// assert!(position.sanity_flags().is_plausible());
+    ///
+    /// let mut swapped = position.clone();
+    /// (swapped.latitude, swapped.longitude) = (-120.5, 51.47);
+    /// assert!(!swapped.sanity_flags().coordinates_in_range);
+    ///
+    /// let mut uninitialised = position.clone();
+    /// (uninitialised.latitude, uninitialised.longitude) = (0.0, 0.0);
+    /// assert!(!uninitialised.sanity_flags().away_from_null_island);
+    ///
+    /// let mut orbiting = position.clone();
+    /// orbiting.true_altitude = 250000.0;
+    /// assert!(!orbiting.sanity_flags().altitude_plausible);
+    ///
+    /// let mut taxiing_at_cruise = position.clone();
+    /// taxiing_at_cruise.on_ground = true;
+    /// taxiing_at_cruise.true_altitude = 35000.0;
+    /// assert!(!taxiing_at_cruise.sanity_flags().ground_state_plausible);
+    /// assert!(!taxiing_at_cruise.sanity_flags().is_plausible());
+    /// ```
+    pub fn sanity_flags(&self) -> PositionSanity {
+        // Below the Dead Sea shore, and above the service ceiling of any civil aircraft
+        const MIN_ALTITUDE_FT: f64 = -1500.0;
+        const MAX_ALTITUDE_FT: f64 = 70000.0;
+        // Just above the highest aerodrome in the world
+        const MAX_GROUND_ALTITUDE_FT: f64 = 15000.0;
+        const MAX_GROUND_SPEED_KT: u32 = 250;
+        const MAX_PRESSURE_ALTITUDE_DIFFERENCE_FT: f64 = 3000.0;
+        const NULL_ISLAND_RADIUS_DEG: f64 = 0.01;
+
+        let on_ground_plausible = !self.on_ground
+            || (self.true_altitude <= MAX_GROUND_ALTITUDE_FT
+                && self.ground_speed <= MAX_GROUND_SPEED_KT);
+
+        PositionSanity {
+            coordinates_in_range: (-90.0..=90.0).contains(&self.latitude)
+                && (-180.0..=180.0).contains(&self.longitude),
+            away_from_null_island: self.latitude.abs() > NULL_ISLAND_RADIUS_DEG
+                || self.longitude.abs() > NULL_ISLAND_RADIUS_DEG,
+            altitude_plausible: (MIN_ALTITUDE_FT..=MAX_ALTITUDE_FT).contains(&self.true_altitude),
+            ground_state_plausible: on_ground_plausible,
+            pressure_altitude_plausible: (self.pressure_altitude - self.true_altitude).abs()
+                <= MAX_PRESSURE_ALTITUDE_DIFFERENCE_FT,
+        }
+    }
+
+    /// The fastest climb or descent, in feet per minute, that [`vertical_speed_fpm`][Self::vertical_speed_fpm]
+    /// reports. Anything faster is almost certainly a jump in the reported altitude, such as after a pilot
+    /// repositions, and is clamped to this.
+    pub const MAX_VERTICAL_SPEED_FPM: f64 = 20000.0;
+
+    /// The rate of climb (positive) or descent (negative), in feet per minute, from the change in true altitude since
+    /// `previous`, which was received `dt_secs` seconds before this update.
+    ///
+    /// The result is clamped to [`MAX_VERTICAL_SPEED_FPM`][Self::MAX_VERTICAL_SPEED_FPM] either way. If `dt_secs` is
+    /// not a positive number, no rate can be derived and 0 is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::PilotPositionUpdateMessage, parse_message, FsdMessageType};
+    /// let position = |line| {
+    ///     let FsdMessageType::PilotPositionUpdateMessage(position) = parse_message(line).unwrap() else {
+    ///         panic!("expected a pilot position update");
+    ///     };
+    ///     position
+    /// };
+    /// let low = position("@N:BAW123:7000:1:51.47000:-0.45000:5000:250:0:0");
+    /// let high = position("@N:BAW123:7000:1:51.47000:-0.45000:5100:250:0:0");
+    ///
+    /// assert_eq!(1200.0, high.vertical_speed_fpm(&low, 5.0));
+    /// assert_eq!(-1200.0, low.vertical_speed_fpm(&high, 5.0));
+    /// assert_eq!(0.0, high.vertical_speed_fpm(&low, 0.0));
+    ///
+    /// let repositioned = position("@N:BAW123:7000:1:51.47000:-0.45000:35000:250:0:0");
+    /// assert_eq!(PilotPositionUpdateMessage::MAX_VERTICAL_SPEED_FPM, repositioned.vertical_speed_fpm(&low, 5.0));
+    /// ```
+    pub fn vertical_speed_fpm(&self, previous: &Self, dt_secs: f64) -> f64 {
+        if dt_secs.is_nan() || dt_secs <= 0.0 {
+            return 0.0;
+        }
+        let feet_per_minute = (self.true_altitude - previous.true_altitude) / dt_secs * 60.0;
+        feet_per_minute.clamp(-Self::MAX_VERTICAL_SPEED_FPM, Self::MAX_VERTICAL_SPEED_FPM)
+    }
+
+    /// Estimates the position at fraction `t` (from 0 to 1) of the way between this update and the next one from the
+    /// same aircraft, for smoothing playback of recorded updates.
+    ///
+    /// The coordinates follow the great circle between the two positions. Altitudes and ground speed are
+    /// interpolated linearly, and pitch, bank and heading turn the shorter way round, so a heading of 350° followed
+    /// by 10° passes through 0° rather than 180°. Values which cannot be interpolated, such as the transponder
+    /// settings and whether the aircraft is on the ground, are taken from whichever update is nearer.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, FsdMessageType};
+    /// let position = |line| {
+    ///     let FsdMessageType::PilotPositionUpdateMessage(position) = parse_message(line).unwrap() else {
+    ///         panic!("expected a pilot position update");
+    ///     };
+    ///     position
+    /// };
+    /// let mut first = position("@N:BAW123:7000:1:0.00000:-10.00000:10000:300:0:0");
+    /// let mut second = position("@N:BAW123:7000:1:0.00000:10.00000:12000:320:0:0");
+    /// first.heading = 350.0;
+    /// second.heading = 10.0;
+    ///
+    /// let midpoint = first.interpolate(&second, 0.5);
+    /// assert!(midpoint.latitude.abs() < 1e-9);
+    /// assert!(midpoint.longitude.abs() < 1e-9);
+    /// assert_eq!(11000.0, midpoint.true_altitude);
+    /// assert_eq!(310, midpoint.ground_speed);
+    /// assert!(midpoint.heading.abs() < 1e-9 || (midpoint.heading - 360.0).abs() < 1e-9);
+    ///
+    /// let quarter = first.interpolate(&second, 0.25);
+    /// assert!((quarter.heading - 355.0).abs() < 1e-9);
+    ///
+    /// // Along a great circle rather than a line of latitude, so the midpoint of two points at 50°N is further north
+    /// let paris = position("@N:AFR123:7000:1:50.00000:0.00000:35000:450:0:0");
+    /// let vancouver = position("@N:AFR123:7000:1:50.00000:-120.00000:35000:450:0:0");
+    /// assert!(paris.interpolate(&vancouver, 0.5).latitude > 65.0);
+    /// ```
+    pub fn interpolate(
+        &self,
+        next: &PilotPositionUpdateMessage,
+        t: f64,
+    ) -> PilotPositionUpdateMessage {
+        let nearer = if t < 0.5 { self } else { next };
+        let (latitude, longitude) = util::interpolate_great_circle(
+            self.latitude,
+            self.longitude,
+            next.latitude,
+            next.longitude,
+            t,
+        );
+        let signed_angle = |angle: f64| {
+            let angle = angle.rem_euclid(360.0);
+            if angle > 180.0 {
+                angle - 360.0
+            } else {
+                angle
+            }
+        };
+
+        PilotPositionUpdateMessage {
+            callsign: nearer.callsign.clone(),
+            transponder_mode: nearer.transponder_mode,
+            transponder_code: nearer.transponder_code,
+            rating: nearer.rating,
+            latitude,
+            longitude,
+            true_altitude: self.true_altitude + (next.true_altitude - self.true_altitude) * t,
+            pressure_altitude: self.pressure_altitude
+                + (next.pressure_altitude - self.pressure_altitude) * t,
+            ground_speed: (self.ground_speed as f64
+                + (next.ground_speed as f64 - self.ground_speed as f64) * t)
+                .round() as u32,
+            pitch: signed_angle(util::interpolate_angle(self.pitch, next.pitch, t)),
+            bank: signed_angle(util::interpolate_angle(self.bank, next.bank, t)),
+            heading: util::interpolate_angle(self.heading, next.heading, t).rem_euclid(360.0),
+            on_ground: nearer.on_ground,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AuthenticationChallengeMessage {
     pub from: String,
     pub to: String,
@@ -517,7 +1351,8 @@ impl AuthenticationChallengeMessage {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AuthenticationResponseMessage {
     pub from: String,
     pub to: String,
@@ -551,7 +1386,8 @@ impl AuthenticationResponseMessage {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextMessage {
     pub from: String,
     pub to: String,
@@ -588,9 +1424,127 @@ impl TextMessage {
             message: message.into(),
         }
     }
+
+    /// A private message to a single callsign
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::TextMessage, parse_message, FsdMessageType, RadioFrequency};
+    /// let private = TextMessage::private("EGLL_N_TWR", "BAW123", "Are you ready?");
+    /// assert_eq!("#TMEGLL_N_TWR:BAW123:Are you ready?", private.to_string());
+    ///
+    /// let broadcast = TextMessage::broadcast("EGLL_N_TWR", "Closing in 5 minutes");
+    /// assert_eq!("#TMEGLL_N_TWR:*:Closing in 5 minutes", broadcast.to_string());
+    ///
+    /// let frequencies = [RadioFrequency::new(118, 500).unwrap(), RadioFrequency::new(118, 700).unwrap()];
+    /// let radio = TextMessage::to_frequencies("EGLL_N_TWR", frequencies, "BAW123, cleared to land");
+    /// assert_eq!("#TMEGLL_N_TWR:@18500&@18700:BAW123, cleared to land", radio.to_string());
+    ///
+    /// for message in [private.to_string(), broadcast.to_string()] {
+    ///     assert!(matches!(parse_message(&message).unwrap(), FsdMessageType::TextMessage(_)));
+    /// }
+    /// assert!(matches!(parse_message(radio.to_string()).unwrap(), FsdMessageType::FrequencyMessage(_)));
+    /// ```
+    pub fn private(from: impl AsRef<str>, to: impl AsRef<str>, message: impl Into<String>) -> Self {
+        TextMessage::new(from, to, message)
+    }
+
+    /// A message to every client on the server
+    pub fn broadcast(from: impl AsRef<str>, message: impl Into<String>) -> Self {
+        TextMessage::new(from, BROADCAST_RECIPIENT, message)
+    }
+
+    /// A radio message to every client tuned to any of the given frequencies. These are sent as a
+    /// [`FrequencyMessage`], which is the type frequency-addressed `#TM` messages are parsed into.
+    pub fn to_frequencies(
+        from: impl AsRef<str>,
+        frequencies: impl Into<Vec<RadioFrequency>>,
+        message: impl Into<String>,
+    ) -> FrequencyMessage {
+        FrequencyMessage::new(from, frequencies, message)
+    }
+
+    /// Splits a long text into as many messages as needed to keep each message's text within `max_len` bytes.
+    ///
+    /// Text is split at whitespace, and runs of whitespace are collapsed to a single space. A word that is longer
+    /// than `max_len` by itself is broken between user-perceived characters, so that neither a multi-byte character
+    /// nor a sequence such as an accented letter, a flag or a joined emoji is broken up. A single such character
+    /// longer than `max_len` is kept whole in a part of its own. Empty text produces no messages.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::messages::TextMessage;
+    /// let parts = TextMessage::split("EGLL_N_TWR", "BAW123", "Contact Heathrow Director", 64);
+    /// assert_eq!(1, parts.len());
+    /// assert_eq!("#TMEGLL_N_TWR:BAW123:Contact Heathrow Director", parts[0].to_string());
+    ///
+    /// let text = "Expect vectors for an ILS approach to runway two seven left, number three in traffic";
+    /// let parts = TextMessage::split("EGLL_N_TWR", "BAW123", text, 30);
+    /// assert!(parts.len() > 1);
+    /// for part in &parts {
+    ///     assert_eq!("BAW123", part.to);
+    ///     assert!(part.message.len() <= 30);
+    /// }
+    /// let words: Vec<&str> = parts.iter().flat_map(|p| p.message.split(' ')).collect();
+    /// assert_eq!(text.split(' ').collect::<Vec<_>>(), words);
+    ///
+    /// // A family emoji joined with zero width joiners, an accented letter and a flag are never broken up
+    /// let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+    /// let text = format!("ab{family}e\u{301}\u{1F1EC}\u{1F1E7}");
+    /// let parts = TextMessage::split("EGLL_N_TWR", "BAW123", &text, 8);
+    /// let messages: Vec<&str> = parts.iter().map(|p| p.message.as_str()).collect();
+    /// assert_eq!(vec!["ab", family, "e\u{301}", "\u{1F1EC}\u{1F1E7}"], messages);
+    /// ```
+    pub fn split(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        long_text: impl AsRef<str>,
+        max_len: usize,
+    ) -> Vec<TextMessage> {
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        for mut word in long_text.as_ref().split_whitespace() {
+            while word.len() > max_len {
+                let mut split_at = max_len;
+                while split_at > 0 && !util::is_cluster_boundary(word, split_at) {
+                    split_at -= 1;
+                }
+                if split_at == 0 {
+                    split_at = (1..=word.len())
+                        .find(|&i| util::is_cluster_boundary(word, i))
+                        .unwrap_or(word.len());
+                }
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+                parts.push(word[..split_at].to_string());
+                word = &word[split_at..];
+            }
+            if word.is_empty() {
+                continue;
+            }
+            if !current.is_empty() {
+                if current.len() + 1 + word.len() > max_len {
+                    parts.push(std::mem::take(&mut current));
+                } else {
+                    current.push(' ');
+                }
+            }
+            current.push_str(word);
+        }
+        if !current.is_empty() {
+            parts.push(current);
+        }
+
+        parts
+            .into_iter()
+            .map(|part| TextMessage::new(from.as_ref(), to.as_ref(), part))
+            .collect()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrequencyMessage {
     pub from: String,
     pub to: Vec<RadioFrequency>,
@@ -636,9 +1590,47 @@ impl FrequencyMessage {
             message: message.into(),
         }
     }
-}
 
-#[derive(Debug)]
+    /// Whether the message was sent on the ATC text channel ([`ATC_TEXT_CHANNEL_FREQUENCY`]), which carries
+    /// controller-to-controller chat rather than radio transmissions
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, FsdMessageType, RadioFrequency};
+    /// let FsdMessageType::FrequencyMessage(radio) = parse_message("#TMEGLL_N_TWR:@18500:BAW123, cleared to land").unwrap() else {
+    ///     panic!("expected a frequency message");
+    /// };
+    /// assert!(!radio.is_atc_text_channel());
+    /// assert_eq!(vec![RadioFrequency::new(118, 500).unwrap()], radio.radio_frequencies());
+    ///
+    /// let FsdMessageType::FrequencyMessage(chat) = parse_message("#TMEGLL_N_TWR:@49999:Going for a break").unwrap() else {
+    ///     panic!("expected a frequency message");
+    /// };
+    /// assert!(chat.is_atc_text_channel());
+    /// assert!(chat.radio_frequencies().is_empty());
+    ///
+    /// let FsdMessageType::FrequencyMessage(both) = parse_message("#TMEGLL_N_TWR:@18500&@49999:Runway change").unwrap() else {
+    ///     panic!("expected a frequency message");
+    /// };
+    /// assert!(both.is_atc_text_channel());
+    /// assert_eq!(vec![RadioFrequency::new(118, 500).unwrap()], both.radio_frequencies());
+    /// ```
+    pub fn is_atc_text_channel(&self) -> bool {
+        self.to.contains(&ATC_TEXT_CHANNEL_FREQUENCY)
+    }
+
+    /// The genuine radio frequencies the message was sent on, excluding the ATC text channel
+    pub fn radio_frequencies(&self) -> Vec<RadioFrequency> {
+        self.to
+            .iter()
+            .copied()
+            .filter(|frequency| *frequency != ATC_TEXT_CHANNEL_FREQUENCY)
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChangeServerMessage {
     pub from: String,
     pub to: String,
@@ -671,7 +1663,8 @@ impl ChangeServerMessage {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InitialServerHandshakeMessage {
     pub from: String,
     pub to: String,
@@ -717,7 +1710,38 @@ impl InitialServerHandshakeMessage {
     }
 }
 
-#[derive(Debug)]
+/// Sent by a client in reply to the server's [`InitialServerHandshakeMessage`], identifying the client software
+///
+/// The client ID is written as 4 lowercase hex digits, and an ID of more than 4 digits is always rejected. An ID in
+/// uppercase or with fewer digits is accepted but written back out in that form, unless parsed with
+/// [`ParseMode::Strict`], which rejects it so that the message is always written back out as it was read.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{errors::FsdMessageParseError, parse_message, parse_message_with_mode, FsdMessageType, ParseMode};
+/// let line = "$IDBAW123:SERVER:69d7:vPilot:3:8:1234567:123456789";
+/// let FsdMessageType::InitialClientHandshakeMessage(message) = parse_message(line).unwrap() else {
+///     panic!("expected a client handshake");
+/// };
+/// assert_eq!(0x69d7, message.client_id);
+/// assert_eq!(line, message.to_string());
+///
+/// assert!(matches!(
+///     parse_message("$IDBAW123:SERVER:069d7:vPilot:3:8:1234567:123456789"),
+///     Err(FsdMessageParseError::InvalidClientID(_))
+/// ));
+///
+/// // Uppercase digits are only accepted by the default, lenient mode
+/// let upper = "$IDBAW123:SERVER:69D7:vPilot:3:8:1234567:123456789";
+/// assert_eq!(line, parse_message(upper).unwrap().to_string());
+/// assert!(matches!(
+///     parse_message_with_mode(upper, ParseMode::Strict),
+///     Err(FsdMessageParseError::InvalidClientID(_))
+/// ));
+/// assert!(parse_message_with_mode("$IDBAW123:SERVER:de1:vPilot:3:8:1234567:123456789", ParseMode::Strict).is_err());
+/// ```
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InitialClientHandshakeMessage {
     pub from: String,
     pub to: String,
@@ -757,6 +1781,9 @@ impl TryFrom<&[&str]> for InitialClientHandshakeMessage {
         check_min_num_fields!(fields, 8);
         let first = &fields[0][3..];
 
+        if fields[2].len() > 4 || !fields[2].chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(FsdMessageParseError::InvalidClientID(fields[2].to_string()));
+        }
         Ok(InitialClientHandshakeMessage::new(
             first,
             fields[1],
@@ -777,6 +1804,7 @@ impl TryFrom<&[&str]> for InitialClientHandshakeMessage {
 }
 
 impl InitialClientHandshakeMessage {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
@@ -800,9 +1828,77 @@ impl InitialClientHandshakeMessage {
             initial_key: initial_key.map(|x| x.into()),
         }
     }
+
+    /// Creates a handshake carrying the initial key which seeds the authentication challenges
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::messages::InitialClientHandshakeMessage;
+    /// let with_key = InitialClientHandshakeMessage::with_key(
+    ///     "BAW123", "SERVER", 0xde1e, "vPilot", 3, 8, "1234567", "1234567890", "a1b2c3",
+    /// );
+    /// assert_eq!("$IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890:a1b2c3", with_key.to_string());
+    ///
+    /// let without_key = InitialClientHandshakeMessage::without_key(
+    ///     "BAW123", "SERVER", 0xde1e, "vPilot", 3, 8, "1234567", "1234567890",
+    /// );
+    /// assert_eq!("$IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890", without_key.to_string());
+    /// assert!(without_key.initial_key.is_none());
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_key(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        client_id: u16,
+        client_name: impl Into<String>,
+        major_version: u32,
+        minor_version: u32,
+        cid: impl Into<String>,
+        guid: impl Into<String>,
+        initial_key: impl Into<String>,
+    ) -> Self {
+        InitialClientHandshakeMessage::new(
+            from,
+            to,
+            client_id,
+            client_name,
+            major_version,
+            minor_version,
+            cid,
+            guid,
+            Some(initial_key),
+        )
+    }
+
+    /// Creates a handshake without an initial key, sparing callers the type annotation that passing [`None`] to
+    /// [`new`][Self::new] requires
+    #[allow(clippy::too_many_arguments)]
+    pub fn without_key(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        client_id: u16,
+        client_name: impl Into<String>,
+        major_version: u32,
+        minor_version: u32,
+        cid: impl Into<String>,
+        guid: impl Into<String>,
+    ) -> Self {
+        InitialClientHandshakeMessage::new(
+            from,
+            to,
+            client_id,
+            client_name,
+            major_version,
+            minor_version,
+            cid,
+            guid,
+            None::<String>,
+        )
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SendFastPositionUpdatesMessage {
     pub from: String,
     pub to: String,
@@ -839,7 +1935,31 @@ impl SendFastPositionUpdatesMessage {
     }
 }
 
+/// Sent by a pilot client in place of fast position updates while the aircraft is stationary
+///
+/// `#ST(CALLSIGN):(LATITUDE):(LONGITUDE):(TRUE ALTITUDE):(ALTITUDE AGL):(PITCH BANK HEADING)[:(NOSE GEAR ANGLE)]`
+///
+/// The aircraft is not moving, so unlike [`VelocityPositionFastMessage`] the message carries no velocities, and a
+/// `#ST` line with velocity fields after the nose gear angle is rejected.
+///
+/// Uses the same precision as [`VelocityPositionFastMessage`].
+///
+/// # Example
+/// ```
+/// use fsd_interface::{errors::FsdMessageParseError, parse_message, FsdMessageType};
+/// let stopped = "#STBAW123:51.4700000:-0.4500000:83.00:0.00:4290769922:12.50";
+/// let FsdMessageType::VelocityPositionStoppedMessage(message) = parse_message(stopped).unwrap() else {
+///     panic!("expected a stopped position update");
+/// };
+/// assert!(message.on_ground);
+/// assert_eq!(Some(12.5), message.nose_gear_angle);
+/// assert_eq!(stopped, message.to_string());
+///
+/// let moving = "#STBAW123:51.4700000:-0.4500000:83.00:0.00:4290769922:12.50:0.0000:12.5000:-3.2000";
+/// assert!(matches!(parse_message(moving), Err(FsdMessageParseError::InvalidFieldCount(7, 10))));
+/// ```
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VelocityPositionStoppedMessage {
     pub from: String,
     pub latitude: f64,
@@ -853,6 +1973,24 @@ pub struct VelocityPositionStoppedMessage {
     pub nose_gear_angle: Option<f64>,
 }
 
+impl PartialEq for VelocityPositionStoppedMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.from == other.from
+            && approx_eq(self.latitude, other.latitude)
+            && approx_eq(self.longitude, other.longitude)
+            && approx_eq(self.true_altitude, other.true_altitude)
+            && approx_eq(self.altitude_agl, other.altitude_agl)
+            && approx_eq_degrees(self.pitch, other.pitch)
+            && approx_eq_degrees(self.bank, other.bank)
+            && approx_eq_degrees(self.heading, other.heading)
+            && self.on_ground == other.on_ground
+            && match (self.nose_gear_angle, other.nose_gear_angle) {
+                (Some(a), Some(b)) => approx_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
 impl Display for VelocityPositionStoppedMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pbh =
@@ -873,6 +2011,7 @@ impl TryFrom<&[&str]> for VelocityPositionStoppedMessage {
     type Error = FsdMessageParseError;
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
         check_min_num_fields!(fields, 6);
+        check_max_num_fields!(fields, 7);
         let first = &fields[0][3..];
         let pbh = fields[5]
             .parse::<u32>()
@@ -910,6 +2049,12 @@ impl TryFrom<&[&str]> for VelocityPositionStoppedMessage {
 }
 
 impl VelocityPositionStoppedMessage {
+    /// Checks the on ground flag against the height above ground, as described for [`GroundConsistency`]
+    pub fn ground_consistency(&self) -> GroundConsistency {
+        GroundConsistency::assess(self.on_ground, self.altitude_agl)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         from: impl AsRef<str>,
         latitude: f64,
@@ -937,7 +2082,11 @@ impl VelocityPositionStoppedMessage {
     }
 }
 
+/// Sent by a pilot client at a reduced rate in place of fast position updates
+///
+/// Uses the same precision as [`VelocityPositionFastMessage`].
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VelocityPositionSlowMessage {
     pub from: String,
     pub latitude: f64,
@@ -957,6 +2106,30 @@ pub struct VelocityPositionSlowMessage {
     pub nose_gear_angle: Option<f64>,
 }
 
+impl PartialEq for VelocityPositionSlowMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.from == other.from
+            && approx_eq(self.latitude, other.latitude)
+            && approx_eq(self.longitude, other.longitude)
+            && approx_eq(self.true_altitude, other.true_altitude)
+            && approx_eq(self.altitude_agl, other.altitude_agl)
+            && approx_eq_degrees(self.pitch, other.pitch)
+            && approx_eq_degrees(self.bank, other.bank)
+            && approx_eq_degrees(self.heading, other.heading)
+            && self.on_ground == other.on_ground
+            && approx_eq(self.x_velocity, other.x_velocity)
+            && approx_eq(self.y_velocity, other.y_velocity)
+            && approx_eq(self.z_velocity, other.z_velocity)
+            && approx_eq(self.pitch_rad_per_sec, other.pitch_rad_per_sec)
+            && approx_eq(self.heading_rad_per_sec, other.heading_rad_per_sec)
+            && approx_eq(self.bank_rad_per_sec, other.bank_rad_per_sec)
+            && match (self.nose_gear_angle, other.nose_gear_angle) {
+                (Some(a), Some(b)) => approx_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
 impl Display for VelocityPositionSlowMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pbh =
@@ -1044,6 +2217,28 @@ impl TryFrom<&[&str]> for VelocityPositionSlowMessage {
 }
 
 impl VelocityPositionSlowMessage {
+    /// Checks the on ground flag against the height above ground, as described for [`GroundConsistency`]
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, FsdMessageType};
+    /// let line = "#SLBAW123:51.4700000:-0.4500000:1200.00:1150.00:4290769920:0.0000:12.5000:-3.2000:0.0000:0.0000:0.0100";
+    /// let FsdMessageType::VelocityPositionSlowMessage(mut position) = parse_message(line).unwrap() else {
+    ///     panic!("expected a slow position update");
+    /// };
+    /// position.on_ground = false;
+    /// assert!(position.ground_consistency().agrees);
+    ///
+    /// position.on_ground = true;
+    /// let consistency = position.ground_consistency();
+    /// assert!(!consistency.agrees);
+    /// assert!(!consistency.on_ground);
+    /// ```
+    pub fn ground_consistency(&self) -> GroundConsistency {
+        GroundConsistency::assess(self.on_ground, self.altitude_agl)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         from: impl AsRef<str>,
         latitude: f64,
@@ -1083,7 +2278,28 @@ impl VelocityPositionSlowMessage {
     }
 }
 
+/// Sent by a pilot client several times a second, when requested by [`SendFastPositionUpdatesMessage`], to allow
+/// smooth interpolation of its position
+///
+/// Coordinates are written to 7 decimal places (about 1 cm), altitudes and the nose gear angle to 2, and the linear
+/// and angular velocities to 4, matching the precision pilot clients send. Values parsed from the wire therefore
+/// round-trip exactly. Finer detail in locally constructed values is deliberately dropped: a velocity of
+/// `0.00004` is written as `0.0000`.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, FsdMessageType};
+/// let message = "^BAW123:-89.9999999:179.9999999:60000.25:-1500.75:4290769920:-250.1234:0.0001:99.9999:-3.1415:3.1416:0.0000:-60.00";
+/// let FsdMessageType::VelocityPositionFastMessage(mut position) = parse_message(message).unwrap() else {
+///     panic!("expected a fast position update");
+/// };
+/// assert_eq!(message, position.to_string());
+///
+/// position.x_velocity = 0.00004;
+/// assert!(position.to_string().contains(":0.0000:0.0001:"));
+/// ```
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VelocityPositionFastMessage {
     pub from: String,
     pub latitude: f64,
@@ -1103,6 +2319,30 @@ pub struct VelocityPositionFastMessage {
     pub nose_gear_angle: Option<f64>,
 }
 
+impl PartialEq for VelocityPositionFastMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.from == other.from
+            && approx_eq(self.latitude, other.latitude)
+            && approx_eq(self.longitude, other.longitude)
+            && approx_eq(self.true_altitude, other.true_altitude)
+            && approx_eq(self.altitude_agl, other.altitude_agl)
+            && approx_eq_degrees(self.pitch, other.pitch)
+            && approx_eq_degrees(self.bank, other.bank)
+            && approx_eq_degrees(self.heading, other.heading)
+            && self.on_ground == other.on_ground
+            && approx_eq(self.x_velocity, other.x_velocity)
+            && approx_eq(self.y_velocity, other.y_velocity)
+            && approx_eq(self.z_velocity, other.z_velocity)
+            && approx_eq(self.pitch_rad_per_sec, other.pitch_rad_per_sec)
+            && approx_eq(self.heading_rad_per_sec, other.heading_rad_per_sec)
+            && approx_eq(self.bank_rad_per_sec, other.bank_rad_per_sec)
+            && match (self.nose_gear_angle, other.nose_gear_angle) {
+                (Some(a), Some(b)) => approx_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
 impl From<VelocityPositionSlowMessage> for VelocityPositionFastMessage {
     fn from(value: VelocityPositionSlowMessage) -> Self {
         VelocityPositionFastMessage {
@@ -1213,6 +2453,12 @@ impl TryFrom<&[&str]> for VelocityPositionFastMessage {
 }
 
 impl VelocityPositionFastMessage {
+    /// Checks the on ground flag against the height above ground, as described for [`GroundConsistency`]
+    pub fn ground_consistency(&self) -> GroundConsistency {
+        GroundConsistency::assess(self.on_ground, self.altitude_agl)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         from: impl AsRef<str>,
         latitude: f64,
@@ -1250,9 +2496,32 @@ impl VelocityPositionFastMessage {
             nose_gear_angle,
         }
     }
-}
 
-#[derive(Debug)]
+    /// The ground speed, in knots, derived from the horizontal components of the velocity vector.
+    ///
+    /// Velocities are taken to be in metres per second, with `x_velocity` pointing east, `y_velocity` up and
+    /// `z_velocity` north, as sent by VATSIM pilot clients. The vertical component is ignored and the result is
+    /// rounded to the nearest knot.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::messages::VelocityPositionFastMessage;
+    /// let position = VelocityPositionFastMessage::new(
+    ///     "BAW123", 51.4775, -0.4614, 1500.0, 1420.0, 5.0, 0.0, 270.0, false,
+    ///     -80.0, 5.0, 60.0, 0.0, 0.0, 0.0, None,
+    /// );
+    /// // 100 m/s horizontally
+    /// assert_eq!(194, position.ground_speed_knots());
+    /// ```
+    pub fn ground_speed_knots(&self) -> u32 {
+        const METRES_PER_NAUTICAL_MILE: f64 = 1852.0;
+        let metres_per_second = self.x_velocity.hypot(self.z_velocity);
+        (metres_per_second * 3600.0 / METRES_PER_NAUTICAL_MILE).round() as u32
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KillMessage {
     pub from: String,
     pub to: String,
@@ -1276,7 +2545,11 @@ impl TryFrom<&[&str]> for KillMessage {
         check_min_num_fields!(fields, 2);
         let first = &fields[0][3..];
 
-        Ok(KillMessage::new(first, fields[1], fields.get(2).copied()))
+        Ok(KillMessage::new(
+            first,
+            fields[1],
+            (fields.len() > 2).then(|| util::assemble_with_colons(&fields[2..])),
+        ))
     }
 }
 
@@ -1294,7 +2567,8 @@ impl KillMessage {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetarRequestMessage {
     pub from: String,
     pub to: String,
@@ -1327,7 +2601,22 @@ impl MetarRequestMessage {
     }
 }
 
-#[derive(Debug)]
+/// A METAR sent in response to a [`MetarRequestMessage`].
+///
+/// The METAR is read up to the end of the message, so a payload containing colons, as some TAF concatenations do,
+/// is not truncated.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, FsdMessageType};
+/// let FsdMessageType::MetarResponseMessage(m) = parse_message("$ARSERVER:EGLL_TWR:METAR:EGLL 141250Z 24012KT 9999 FEW030 14/08 Q1012 TAF: BECMG 1418/1420 30010KT").unwrap() else {
+///     panic!("expected a METAR response");
+/// };
+/// assert_eq!("EGLL 141250Z 24012KT 9999 FEW030 14/08 Q1012 TAF: BECMG 1418/1420 30010KT", m.metar);
+/// assert_eq!("$ARSERVER:EGLL_TWR:METAR:EGLL 141250Z 24012KT 9999 FEW030 14/08 Q1012 TAF: BECMG 1418/1420 30010KT", m.to_string());
+/// ```
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetarResponseMessage {
     pub from: String,
     pub to: String,
@@ -1346,7 +2635,11 @@ impl TryFrom<&[&str]> for MetarResponseMessage {
         check_min_num_fields!(fields, 4);
         let first = &fields[0][3..];
 
-        Ok(MetarResponseMessage::new(first, fields[1], fields[3]))
+        Ok(MetarResponseMessage::new(
+            first,
+            fields[1],
+            util::assemble_with_colons(&fields[3..]),
+        ))
     }
 }
 
@@ -1360,7 +2653,8 @@ impl MetarResponseMessage {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PingMessage {
     pub from: String,
     pub to: String,
@@ -1389,6 +2683,9 @@ impl TryFrom<&[&str]> for PingMessage {
     }
 }
 
+/// The instant that timestamps written by [`PingMessage::new_with_timestamp`] count from
+static PING_EPOCH: OnceLock<Instant> = OnceLock::new();
+
 impl PingMessage {
     pub fn new(from: impl AsRef<str>, to: impl AsRef<str>, timestamp: u64) -> Self {
         PingMessage {
@@ -1397,9 +2694,20 @@ impl PingMessage {
             timestamp,
         }
     }
+
+    /// Creates a ping whose timestamp is read from a monotonic clock, in microseconds, so that the round trip time
+    /// can be measured from the pong echoing it with [`PongMessage::round_trip_ms`].
+    ///
+    /// The clock counts from the first timestamped ping created by the process, so it is only meaningful to the
+    /// process that sent the ping.
+    pub fn new_with_timestamp(from: impl AsRef<str>, to: impl AsRef<str>) -> Self {
+        let epoch = *PING_EPOCH.get_or_init(Instant::now);
+        PingMessage::new(from, to, epoch.elapsed().as_micros() as u64)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PongMessage {
     pub from: String,
     pub to: String,
@@ -1436,9 +2744,54 @@ impl PongMessage {
             timestamp,
         }
     }
-}
 
-#[derive(Debug)]
+    /// The pong answering a ping: sent back to the ping's sender, echoing its timestamp
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::messages::{PingMessage, PongMessage};
+    /// let ping = PingMessage::new("SERVER", "BAW123", 1234567);
+    /// let pong = PongMessage::reply_to(&ping);
+    /// assert_eq!("$POBAW123:SERVER:1234567", pong.to_string());
+    /// ```
+    pub fn reply_to(ping: &PingMessage) -> Self {
+        PongMessage::new(&ping.to, &ping.from, ping.timestamp)
+    }
+
+    /// The time in milliseconds between sending a ping created with [`PingMessage::new_with_timestamp`] and `now`,
+    /// when this pong answering it was received.
+    ///
+    /// Returns `None` if no timestamped ping has been created, or if the timestamp is later than `now`. Timestamps are
+    /// plain numbers, so one from a ping sent by another client or the server cannot be recognised, and gives a
+    /// meaningless result unless it happens to be later than `now`. Only call this for pongs answering pings this
+    /// process sent, for instance by checking the pong comes from the callsign the ping was sent to.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use fsd_interface::messages::{PingMessage, PongMessage};
+    /// let ping = PingMessage::new_with_timestamp("BAW123", "SERVER");
+    /// let sent = Instant::now();
+    /// let pong = PongMessage::reply_to(&ping);
+    /// assert_eq!("SERVER", pong.from);
+    /// assert_eq!(ping.timestamp, pong.timestamp);
+    ///
+    /// let round_trip = pong.round_trip_ms(sent + Duration::from_millis(40)).unwrap();
+    /// assert!((40.0..41.0).contains(&round_trip), "{}", round_trip);
+    ///
+    /// // A timestamp later than now
+    /// assert_eq!(None, PongMessage::new("SERVER", "BAW123", u64::MAX).round_trip_ms(Instant::now()));
+    /// ```
+    pub fn round_trip_ms(&self, now: Instant) -> Option<f64> {
+        let epoch = PING_EPOCH.get()?;
+        let sent = epoch.checked_add(Duration::from_micros(self.timestamp))?;
+        let round_trip = now.checked_duration_since(sent)?;
+        Some(round_trip.as_secs_f64() * 1000.0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlaneInfoRequestMessage {
     pub from: String,
     pub to: String,
@@ -1463,13 +2816,14 @@ impl TryFrom<&[&str]> for PlaneInfoRequestMessage {
 impl PlaneInfoRequestMessage {
     pub fn new(from: impl AsRef<str>, to: impl AsRef<str>) -> Self {
         PlaneInfoRequestMessage {
-            from: from.as_ref().into(),
-            to: to.as_ref().into(),
+            from: from.as_ref().to_uppercase(),
+            to: to.as_ref().to_uppercase(),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlaneInfoResponseMessage {
     pub from: String,
     pub to: String,
@@ -1506,7 +2860,53 @@ impl PlaneInfoResponseMessage {
     }
 }
 
-#[derive(Debug, Clone)]
+/// An error reported by the FSD server.
+///
+/// The wire form is `$ER(FROM):(TO):(ERROR NUMBER):(PARAMETER):(MESSAGE)`. The parameter holds the callsign or
+/// location the error refers to, where there is one, and is otherwise left empty.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{errors::FsdError, messages::FsdErrorMessage, parse_message, FsdMessageType};
+/// let errors = [
+///     FsdError::CallsignInUse,
+///     FsdError::InvalidCallsign,
+///     FsdError::AlreadyRegistered,
+///     FsdError::SyntaxError,
+///     FsdError::InvalidSourceCallsign,
+///     FsdError::InvalidCidPassword,
+///     FsdError::NoSuchCallsign(String::from("BAW123")),
+///     FsdError::NoFlightPlan(String::from("BAW123")),
+///     FsdError::NoWeatherProfile(String::from("EGLL")),
+///     FsdError::InvalidProtocolRevision,
+///     FsdError::RequestedLevelTooHigh,
+///     FsdError::ServerFull,
+///     FsdError::CertificateSuspended,
+///     FsdError::InvalidControl,
+///     FsdError::InvalidPositionForRating,
+///     FsdError::UnauthorisedClient,
+///     FsdError::AuthTimeOut,
+///     FsdError::Other(String::from("Server shutting down: back at 1200z")),
+/// ];
+/// for error in errors {
+///     let message = FsdErrorMessage::new("SERVER", "BAW123", error.clone());
+///     let FsdMessageType::FsdErrorMessage(reparsed) = parse_message(&message.to_string()).unwrap() else {
+///         panic!("expected an error message");
+///     };
+///     assert_eq!(error, reparsed.error_type);
+///     assert_eq!(message.to_string(), reparsed.to_string());
+/// }
+///
+/// // The subject of the error is carried in the parameter field
+/// let message = FsdErrorMessage::new("SERVER", "BAW123", FsdError::NoSuchCallsign(String::from("N123")));
+/// assert_eq!("$ERSERVER:BAW123:007:N123:", message.to_string());
+/// let FsdMessageType::FsdErrorMessage(reparsed) = parse_message(&message.to_string()).unwrap() else {
+///     panic!("expected an error message");
+/// };
+/// assert_eq!(FsdError::NoSuchCallsign(String::from("N123")), reparsed.error_type);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FsdErrorMessage {
     pub from: String,
     pub to: String,
@@ -1515,23 +2915,21 @@ pub struct FsdErrorMessage {
 
 impl Display for FsdErrorMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let FsdError::Other(ref message) = self.error_type {
-            write!(
+        let error_number = self.error_type.error_number();
+        match self.error_type {
+            FsdError::Other(ref message) => write!(
                 f,
                 "$ER{}:{}:{:03}::{}",
-                self.from,
-                self.to,
-                self.error_type.error_number(),
-                message
-            )
-        } else {
-            write!(
+                self.from, self.to, error_number, message
+            ),
+            FsdError::NoSuchCallsign(ref subject)
+            | FsdError::NoFlightPlan(ref subject)
+            | FsdError::NoWeatherProfile(ref subject) => write!(
                 f,
-                "$ER{}:{}:{:03}::",
-                self.from,
-                self.to,
-                self.error_type.error_number()
-            )
+                "$ER{}:{}:{:03}:{}:",
+                self.from, self.to, error_number, subject
+            ),
+            _ => write!(f, "$ER{}:{}:{:03}::", self.from, self.to, error_number),
         }
     }
 }
@@ -1541,29 +2939,18 @@ impl TryFrom<&[&str]> for FsdErrorMessage {
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
         check_min_num_fields!(fields, 5);
         let first = &fields[0][3..];
-        let error_type = match fields[2]
+        let error_number = fields[2]
             .parse::<u8>()
-            .map_err(|_| FsdMessageParseError::InvalidServerError(fields[2].to_string()))?
-        {
-            1 => FsdError::CallsignInUse,
-            2 => FsdError::InvalidCallsign,
-            3 => FsdError::AlreadyRegistered,
-            4 => FsdError::SyntaxError,
-            5 => FsdError::InvalidCallsign,
-            6 => FsdError::InvalidCidPassword,
-            7 => FsdError::NoSuchCallsign(fields[3].to_uppercase()),
-            8 => FsdError::NoFlightPlan(fields[3].to_uppercase()),
-            9 => FsdError::NoWeatherProfile(fields[3].to_uppercase()),
-            10 => FsdError::InvalidProtocolRevision,
-            11 => FsdError::RequestedLevelTooHigh,
-            12 => FsdError::ServerFull,
-            13 => FsdError::CertificateSuspended,
-            14 => FsdError::InvalidControl,
-            15 => FsdError::InvalidPositionForRating,
-            16 => FsdError::UnauthorisedClient,
-            17 => FsdError::AuthTimeOut,
-            _ => FsdError::Other(fields[4].to_string()),
-        };
+            .map_err(|_| FsdMessageParseError::InvalidServerError(fields[2].to_string()))?;
+        let mut error_type =
+            FsdError::try_from(error_number).unwrap_or(FsdError::Other(String::new()));
+        match &mut error_type {
+            FsdError::NoSuchCallsign(subject)
+            | FsdError::NoFlightPlan(subject)
+            | FsdError::NoWeatherProfile(subject) => *subject = fields[3].to_uppercase(),
+            FsdError::Other(message) => *message = util::assemble_with_colons(&fields[4..]),
+            _ => {}
+        }
         Ok(FsdErrorMessage::new(first, fields[1], error_type))
     }
 }
@@ -1578,7 +2965,8 @@ impl FsdErrorMessage {
     }
 }
 
-#[derive(Debug, Reflect)]
+#[derive(Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FlightPlanMessage {
     pub to: String,
     pub callsign: String,
@@ -1597,18 +2985,28 @@ impl Display for FlightPlanMessage {
 impl TryFrom<&[&str]> for FlightPlanMessage {
     type Error = FsdMessageParseError;
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
-        check_exact_num_fields!(fields, 17);
+        FlightPlanMessage::parse(fields, ParseMode::Strict)
+    }
+}
+
+impl FlightPlanMessage {
+    /// Parses a `$FP` message. In [`ParseMode::Strict`] it must have exactly 17 fields; in [`ParseMode::Lenient`] the
+    /// flight plan is read as described for [`FlightPlan::parse`], so only the fields up to its destination are
+    /// needed.
+    pub fn parse(fields: &[&str], mode: ParseMode) -> Result<Self, FsdMessageParseError> {
+        match mode {
+            ParseMode::Strict => check_exact_num_fields!(fields, 17),
+            ParseMode::Lenient => check_min_num_fields!(fields, 10),
+        }
         let first = &fields[0][3..];
 
         Ok(FlightPlanMessage::new(
             fields[1],
             first,
-            fields[2..].try_into()?,
+            FlightPlan::parse(&fields[2..], mode)?,
         ))
     }
-}
 
-impl FlightPlanMessage {
     pub fn new(to: impl AsRef<str>, callsign: impl AsRef<str>, flight_plan: FlightPlan) -> Self {
         FlightPlanMessage {
             to: to.as_ref().to_uppercase(),
@@ -1616,9 +3014,60 @@ impl FlightPlanMessage {
             flight_plan,
         }
     }
-}
 
-#[derive(Debug, Reflect)]
+    /// A flight plan filed by the pilot of `callsign`, addressed to the server
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::FlightPlanMessage, parse_message, FsdMessageType};
+    /// let FsdMessageType::FlightPlanMessage(received) =
+    ///     parse_message("$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR").unwrap() else {
+    ///     panic!("expected a flight plan");
+    /// };
+    /// let filed = FlightPlanMessage::file("baw123", received.flight_plan);
+    /// assert_eq!("SERVER", filed.to);
+    /// assert_eq!("BAW123", filed.callsign);
+    /// assert_eq!(
+    ///     "$FPBAW123:SERVER:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR",
+    ///     filed.to_string()
+    /// );
+    /// ```
+    pub fn file(callsign: impl AsRef<str>, flight_plan: FlightPlan) -> Self {
+        FlightPlanMessage::new(SERVER_CALLSIGN, callsign, flight_plan)
+    }
+
+    /// Turns a filed flight plan into an amendment of it by the controller `from`, addressed to the server.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, FsdMessageType};
+    /// let filed = "$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR";
+    /// let FsdMessageType::FlightPlanMessage(plan) = parse_message(filed).unwrap() else {
+    ///     panic!("expected a flight plan");
+    /// };
+    ///
+    /// let mut amendment = plan.into_amendment("egll_del");
+    /// amendment.flight_plan.cruise_level = 37000;
+    /// let wire = amendment.to_string();
+    /// assert_eq!(
+    ///     "$AMEGLL_DEL:SERVER:BAW123:I:B738:450:EGLL:1200:0:37000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR",
+    ///     wire
+    /// );
+    ///
+    /// let FsdMessageType::FlightPlanAmendmentMessage(reparsed) = parse_message(&wire).unwrap() else {
+    ///     panic!("expected a flight plan amendment");
+    /// };
+    /// assert_eq!("EGLL_DEL", reparsed.from);
+    /// assert_eq!("BAW123", reparsed.callsign);
+    /// assert_eq!(wire, reparsed.to_string());
+    /// ```
+    pub fn into_amendment(self, from: impl AsRef<str>) -> FlightPlanAmendmentMessage {
+        FlightPlanAmendmentMessage::amend(from, self.callsign, self.flight_plan)
+    }
+}
+
+#[derive(Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FlightPlanAmendmentMessage {
     pub from: String,
     pub to: String,
@@ -1642,18 +3091,28 @@ impl Display for FlightPlanAmendmentMessage {
 impl TryFrom<&[&str]> for FlightPlanAmendmentMessage {
     type Error = FsdMessageParseError;
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
-        check_exact_num_fields!(fields, 18);
+        FlightPlanAmendmentMessage::parse(fields, ParseMode::Strict)
+    }
+}
+
+impl FlightPlanAmendmentMessage {
+    /// Parses an `$AM` message. In [`ParseMode::Strict`] it must have exactly 18 fields; in [`ParseMode::Lenient`]
+    /// the flight plan is read as described for [`FlightPlan::parse`], so only the fields up to its destination are
+    /// needed.
+    pub fn parse(fields: &[&str], mode: ParseMode) -> Result<Self, FsdMessageParseError> {
+        match mode {
+            ParseMode::Strict => check_exact_num_fields!(fields, 18),
+            ParseMode::Lenient => check_min_num_fields!(fields, 11),
+        }
         let first = &fields[0][3..];
         Ok(FlightPlanAmendmentMessage::new(
             first,
             fields[1],
             fields[2],
-            fields[3..].try_into()?,
+            FlightPlan::parse(&fields[3..], mode)?,
         ))
     }
-}
 
-impl FlightPlanAmendmentMessage {
     pub fn new(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
@@ -1667,10 +3126,121 @@ impl FlightPlanAmendmentMessage {
             flight_plan,
         }
     }
+
+    /// An amendment by the controller `from` of the flight plan of `callsign`, addressed to the server
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::FlightPlanAmendmentMessage, parse_message, FsdMessageType};
+    /// let FsdMessageType::FlightPlanMessage(received) =
+    ///     parse_message("$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR").unwrap() else {
+    ///     panic!("expected a flight plan");
+    /// };
+    /// let amendment = FlightPlanAmendmentMessage::amend("egll_del", "baw123", received.flight_plan);
+    /// assert_eq!("EGLL_DEL", amendment.from);
+    /// assert_eq!("SERVER", amendment.to);
+    /// assert_eq!("BAW123", amendment.callsign);
+    /// ```
+    pub fn amend(
+        from: impl AsRef<str>,
+        callsign: impl AsRef<str>,
+        flight_plan: FlightPlan,
+    ) -> Self {
+        FlightPlanAmendmentMessage::new(from, SERVER_CALLSIGN, callsign, flight_plan)
+    }
+}
+
+/// The heartbeat the server periodically broadcasts, usually `#DLSERVER:*:0:0`. The meaning of the fields after the
+/// recipient is not documented, so they are kept as received.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{messages::ServerHeartbeatMessage, parse_message, FsdMessageType};
+/// let FsdMessageType::ServerHeartbeat(heartbeat) = parse_message("#DLSERVER:*:12:3").unwrap() else {
+///     panic!("expected a heartbeat");
+/// };
+/// assert_eq!(vec!["12", "3"], heartbeat.data);
+/// assert_eq!("#DLSERVER:*:12:3", heartbeat.to_string());
+/// assert_eq!("#DLSERVER:*:0:0", ServerHeartbeatMessage::new("SERVER", "*", ["0", "0"]).to_string());
+/// ```
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerHeartbeatMessage {
+    pub from: String,
+    pub to: String,
+    pub data: Vec<String>,
 }
 
+impl Display for ServerHeartbeatMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#DL{}:{}", self.from, self.to)?;
+        for field in &self.data {
+            write!(f, ":{}", field)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&[&str]> for ServerHeartbeatMessage {
+    type Error = FsdMessageParseError;
+    fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
+        check_min_num_fields!(fields, 2);
+        let first = &fields[0][3..];
+
+        Ok(ServerHeartbeatMessage::new(
+            first,
+            fields[1],
+            fields[2..].iter().copied(),
+        ))
+    }
+}
+
+impl ServerHeartbeatMessage {
+    pub fn new(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        data: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        ServerHeartbeatMessage {
+            from: from.as_ref().to_uppercase(),
+            to: to.as_ref().to_uppercase(),
+            data: data.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Callsign suffixes used by clients connected to watch rather than control: observers, supervisors and administrators
+pub const OBSERVER_CALLSIGN_SUFFIXES: [&str; 3] = ["_OBS", "_SUP", "_ADM"];
+
+/// Whether a callsign is that of an observer, supervisor or administrator rather than a controlled position.
+/// Such clients are never a valid answer to an `ATC` ("is valid ATC") query.
+///
+/// # Example
+/// ```
+/// use fsd_interface::messages::is_observer_callsign;
+/// assert!(is_observer_callsign("EGLL_OBS"));
+/// assert!(is_observer_callsign("JB_SUP"));
+/// assert!(is_observer_callsign("vatsim_adm"));
+/// assert!(!is_observer_callsign("EGLL_N_TWR"));
+/// assert!(!is_observer_callsign("OBS"));
+/// ```
+pub fn is_observer_callsign(callsign: impl AsRef<str>) -> bool {
+    let callsign = callsign.as_ref().to_uppercase();
+    OBSERVER_CALLSIGN_SUFFIXES
+        .iter()
+        .any(|suffix| callsign.ends_with(suffix))
+}
+
+/// The first constant of an `IPC` force beacon code query: the server is asking the client to *write* to the
+/// simulator's FSUIPC interface.
+pub const IPC_MAGIC_W: &str = "W";
+/// The second constant of an `IPC` force beacon code query: the FSUIPC offset to write to, `0x0354` (852), which
+/// holds the transponder code.
+pub const IPC_MAGIC_852: &str = "852";
+
 #[non_exhaustive]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientQueryMessage {
     pub from: String,
     pub to: String,
@@ -1747,12 +3317,15 @@ impl TryFrom<&[&str]> for ClientQueryMessage {
                 Ok(ClientQueryMessage::new(
                     first,
                     fields[1],
-                    ClientQueryType::SetScratchpad(fields[3].to_uppercase(), fields[4].to_string()),
+                    ClientQueryType::SetScratchpad(
+                        fields[3].to_uppercase(),
+                        util::assemble_with_colons(&fields[4..]),
+                    ),
                 ))
             }
             "FA" => {
                 check_min_num_fields!(fields, 5);
-                let altitude = util::parse_altitude(fields[4])?;
+                let altitude = Level::from_feet(util::parse_altitude(fields[4])?);
                 Ok(ClientQueryMessage::new(
                     first,
                     fields[1],
@@ -1819,7 +3392,7 @@ impl TryFrom<&[&str]> for ClientQueryMessage {
             "TA" => {
                 check_min_num_fields!(fields, 5);
                 let subject = fields[3].to_uppercase();
-                let altitude = util::parse_altitude(fields[4])?;
+                let altitude = Level::from_feet(util::parse_altitude(fields[4])?);
                 Ok(ClientQueryMessage::new(
                     first,
                     fields[1],
@@ -1869,8 +3442,63 @@ impl TryFrom<&[&str]> for ClientQueryMessage {
                 fields[1],
                 ClientQueryType::INF,
             )),
-            _ => Err(FsdMessageParseError::UnknownMessageType(
-                fields[2].to_string(),
+            "HLP" | "NOHLP" => {
+                let message = (fields.len() > 3).then(|| util::assemble_with_colons(&fields[3..]));
+                let query_type = if fields[2] == "HLP" {
+                    ClientQueryType::HelpRequest(message)
+                } else {
+                    ClientQueryType::CancelHelpRequest(message)
+                };
+                Ok(ClientQueryMessage::new(first, fields[1], query_type))
+            }
+            "IPC" => {
+                check_min_num_fields!(fields, 6);
+                if fields[3] != IPC_MAGIC_W {
+                    return Err(FsdMessageParseError::IpcConstantMismatch(
+                        "IPC_MAGIC_W",
+                        IPC_MAGIC_W,
+                        fields[3].to_string(),
+                    ));
+                }
+                if fields[4] != IPC_MAGIC_852 {
+                    return Err(FsdMessageParseError::IpcConstantMismatch(
+                        "IPC_MAGIC_852",
+                        IPC_MAGIC_852,
+                        fields[4].to_string(),
+                    ));
+                }
+                Ok(ClientQueryMessage::new(
+                    first,
+                    fields[1],
+                    ClientQueryType::ForceBeaconCode(fields[5].parse()?),
+                ))
+            }
+            "GD" => {
+                check_min_num_fields!(fields, 5);
+                Ok(ClientQueryMessage::new(
+                    first,
+                    fields[1],
+                    ClientQueryType::SetGlobalData(
+                        fields[3].to_uppercase(),
+                        util::assemble_with_colons(&fields[4..]),
+                    ),
+                ))
+            }
+            "SIMTIME" => {
+                check_min_num_fields!(fields, 4);
+                Ok(ClientQueryMessage::new(
+                    first,
+                    fields[1],
+                    ClientQueryType::SimTime(util::parse_sim_time(fields[3])?),
+                ))
+            }
+            _ => Ok(ClientQueryMessage::new(
+                first,
+                fields[1],
+                ClientQueryType::Unknown {
+                    token: fields[2].to_string(),
+                    fields: fields[3..].iter().map(|x| x.to_string()).collect(),
+                },
             )),
         }
     }
@@ -1982,24 +3610,24 @@ impl ClientQueryMessage {
         from: impl AsRef<str>,
         to: impl AsRef<str>,
         subject: impl AsRef<str>,
-        altitude: u32,
+        altitude: impl Into<Level>,
     ) -> ClientQueryMessage {
         ClientQueryMessage::new(
             from,
             to,
-            ClientQueryType::SetFinalAltitude(subject.as_ref().to_uppercase(), altitude),
+            ClientQueryType::SetFinalAltitude(subject.as_ref().to_uppercase(), altitude.into()),
         )
     }
     pub fn set_temp_altitude(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
         subject: impl AsRef<str>,
-        altitude: u32,
+        altitude: impl Into<Level>,
     ) -> ClientQueryMessage {
         ClientQueryMessage::new(
             from,
             to,
-            ClientQueryType::SetTempAltitude(subject.as_ref().to_uppercase(), altitude),
+            ClientQueryType::SetTempAltitude(subject.as_ref().to_uppercase(), altitude.into()),
         )
     }
     pub fn set_beacon_code(
@@ -2014,6 +3642,137 @@ impl ClientQueryMessage {
             ClientQueryType::SetBeaconCode(subject.as_ref().to_uppercase(), code),
         )
     }
+    /// Converts a `#PC ... CCP:BC` shared state beacon code assignment into the equivalent `$CQ ... BC` query, keeping
+    /// the sender and recipient. Returns [`None`] if the message is not a beacon code assignment.
+    ///
+    /// See [`SharedStateMessage::from_beacon_query`] for the reverse conversion.
+    pub fn from_beacon_state(state: &SharedStateMessage) -> Option<ClientQueryMessage> {
+        match &state.shared_state_type {
+            SharedStateType::BeaconCode(subject, code) => Some(
+                ClientQueryMessage::set_beacon_code(&state.from, &state.to, subject, *code),
+            ),
+            _ => None,
+        }
+    }
+    /// Requests help from a supervisor, optionally with a message describing the problem. With `None` the `HLP`
+    /// token is sent on its own.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::ClientQueryMessage, parse_message, ClientQueryType, FsdMessageType};
+    /// let message = ClientQueryMessage::help_request("BAW123", "@94835", Some("Frequency 118.700: no reply"));
+    /// assert_eq!("$CQBAW123:@94835:HLP:Frequency 118.700: no reply", message.to_string());
+    ///
+    /// let FsdMessageType::ClientQueryMessage(reparsed) = parse_message(&message.to_string()).unwrap() else {
+    ///     panic!("expected a client query");
+    /// };
+    /// let ClientQueryType::HelpRequest(Some(ref text)) = reparsed.query_type else {
+    ///     panic!("expected a help request with a message");
+    /// };
+    /// assert_eq!("Frequency 118.700: no reply", text);
+    /// assert_eq!(message, reparsed);
+    ///
+    /// assert_eq!("$CQBAW123:@94835:HLP", ClientQueryMessage::help_request("BAW123", "@94835", None::<&str>).to_string());
+    ///
+    /// // An empty message field is kept as an empty message, so that the line is re-emitted as received
+    /// let line = "$CQBAW123:@94835:NOHLP:";
+    /// let FsdMessageType::ClientQueryMessage(cancel) = parse_message(line).unwrap() else {
+    ///     panic!("expected a client query");
+    /// };
+    /// assert_eq!(ClientQueryType::CancelHelpRequest(Some(String::new())), cancel.query_type);
+    /// assert_eq!(line, cancel.to_string());
+    /// assert_eq!(cancel, ClientQueryMessage::cancel_help_request("BAW123", "@94835", Some("")));
+    /// ```
+    pub fn help_request(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        message: Option<impl Into<String>>,
+    ) -> ClientQueryMessage {
+        ClientQueryMessage::new(
+            from,
+            to,
+            ClientQueryType::HelpRequest(message.map(Into::into)),
+        )
+    }
+    /// Withdraws a previous help request, optionally with a message as for [`help_request`][Self::help_request]
+    pub fn cancel_help_request(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        message: Option<impl Into<String>>,
+    ) -> ClientQueryMessage {
+        ClientQueryMessage::new(
+            from,
+            to,
+            ClientQueryType::CancelHelpRequest(message.map(Into::into)),
+        )
+    }
+    /// Instructs a pilot client to set its transponder code.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::ClientQueryMessage, parse_message, FsdMessageType};
+    /// let message = ClientQueryMessage::force_beacon_code("SERVER", "BAW123", "4721".parse().unwrap());
+    /// assert_eq!("$CQSERVER:BAW123:IPC:W:852:4721", message.to_string());
+    ///
+    /// let FsdMessageType::ClientQueryMessage(parsed) = parse_message(&message.to_string()).unwrap() else {
+    ///     panic!("expected a client query");
+    /// };
+    /// assert_eq!(message.to_string(), parsed.to_string());
+    ///
+    /// // The FSUIPC offset must be the transponder's
+    /// let error = parse_message("$CQSERVER:BAW123:IPC:W:853:4721").unwrap_err();
+    /// assert!(error.to_string().contains("IPC_MAGIC_852"));
+    /// ```
+    pub fn force_beacon_code(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        code: TransponderCode,
+    ) -> ClientQueryMessage {
+        ClientQueryMessage::new(from, to, ClientQueryType::ForceBeaconCode(code))
+    }
+    /// Tells a pilot client to set its simulator to the given UTC time.
+    ///
+    /// The time is sent to the whole second, with any fraction of a second truncated. [`SystemTime`] has no leap
+    /// seconds, and a leap second (`60` seconds) is rejected when parsing.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use fsd_interface::{messages::ClientQueryMessage, parse_message, ClientQueryType, FsdMessageType};
+    /// let time = UNIX_EPOCH + Duration::from_millis(1_721_304_245_750);
+    /// let message = ClientQueryMessage::sim_time("SERVER", "BAW123", time);
+    /// assert_eq!("$CQSERVER:BAW123:SIMTIME:20240718120405", message.to_string());
+    ///
+    /// let FsdMessageType::ClientQueryMessage(reparsed) = parse_message(&message.to_string()).unwrap() else {
+    ///     panic!("expected a client query");
+    /// };
+    /// let ClientQueryType::SimTime(reparsed_time) = reparsed.query_type else {
+    ///     panic!("expected a sim time query");
+    /// };
+    /// assert_eq!(UNIX_EPOCH + Duration::from_secs(1_721_304_245), reparsed_time);
+    /// assert_eq!(message.to_string(), reparsed.to_string());
+    ///
+    /// assert!(parse_message("$CQSERVER:BAW123:SIMTIME:20161231235960").is_err());
+    /// ```
+    pub fn sim_time(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        time: SystemTime,
+    ) -> ClientQueryMessage {
+        ClientQueryMessage::new(from, to, ClientQueryType::SimTime(time))
+    }
+    pub fn set_global_data(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        subject: impl AsRef<str>,
+        contents: impl Into<String>,
+    ) -> ClientQueryMessage {
+        ClientQueryMessage::new(
+            from,
+            to,
+            ClientQueryType::SetGlobalData(subject.as_ref().to_uppercase(), contents.into()),
+        )
+    }
     pub fn set_scratchpad(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
@@ -2078,7 +3837,8 @@ impl ClientQueryMessage {
 }
 
 #[non_exhaustive]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClientQueryResponseMessage {
     pub from: String,
     pub to: String,
@@ -2142,7 +3902,7 @@ impl TryFrom<&[&str]> for ClientQueryResponseMessage {
                     .to_string(),
             ),
             "ATC" => {
-                check_min_num_fields!(fields, 5);
+                check_min_num_fields!(fields, 4);
                 let is_valid = match fields[3].to_uppercase().as_str() {
                     "Y" => true,
                     "N" => false,
@@ -2152,7 +3912,8 @@ impl TryFrom<&[&str]> for ClientQueryResponseMessage {
                         ))
                     }
                 };
-                let subject = fields[4].to_string();
+                // Some servers leave out the subject when answering about the recipient itself
+                let subject = fields.get(4).unwrap_or(&fields[1]).to_uppercase();
                 ClientResponseType::IsValidATC(subject, is_valid)
             }
             "CAPS" => {
@@ -2192,6 +3953,70 @@ impl ClientQueryResponseMessage {
     ) -> ClientQueryResponseMessage {
         ClientQueryResponseMessage::new(from, to, ClientResponseType::ATIS(atis_line))
     }
+    /// Builds the complete, ordered sequence of responses to an ATIS query: the voice server (if any), each line
+    /// of text, the logoff time (if any), and finally the end marker carrying the number of lines sent before it.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::ClientQueryResponseMessage, parse_message, AtisLine, ClientResponseType, FsdMessageType};
+    /// let sequence = ClientQueryResponseMessage::atis_sequence(
+    ///     "EGLL_N_TWR",
+    ///     "BAW123",
+    ///     None,
+    ///     &["Heathrow Tower", "Information Alpha"],
+    ///     Some(2130),
+    /// );
+    /// let wire: Vec<String> = sequence.iter().map(|m| m.to_string()).collect();
+    /// assert_eq!(
+    ///     vec![
+    ///         "$CREGLL_N_TWR:BAW123:ATIS:T:Heathrow Tower",
+    ///         "$CREGLL_N_TWR:BAW123:ATIS:T:Information Alpha",
+    ///         "$CREGLL_N_TWR:BAW123:ATIS:Z:2130z",
+    ///         "$CREGLL_N_TWR:BAW123:ATIS:E:3",
+    ///     ],
+    ///     wire
+    /// );
+    ///
+    /// for line in &wire {
+    ///     let FsdMessageType::ClientQueryResponseMessage(reparsed) = parse_message(line).unwrap() else {
+    ///         panic!("expected a client query response");
+    ///     };
+    ///     assert_eq!(*line, reparsed.to_string());
+    /// }
+    /// let FsdMessageType::ClientQueryResponseMessage(end) = parse_message(&wire[3]).unwrap() else {
+    ///     panic!("expected a client query response");
+    /// };
+    /// let ClientResponseType::ATIS(AtisLine::EndMarker(count)) = end.response_type else {
+    ///     panic!("expected an end marker");
+    /// };
+    /// assert_eq!(3, count);
+    /// ```
+    pub fn atis_sequence(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        voice_server: Option<&str>,
+        text_lines: &[&str],
+        logoff_time: Option<u16>,
+    ) -> Vec<ClientQueryResponseMessage> {
+        let mut lines = Vec::with_capacity(text_lines.len() + 3);
+        if let Some(voice_server) = voice_server {
+            lines.push(AtisLine::VoiceServer(voice_server.to_string()));
+        }
+        lines.extend(
+            text_lines
+                .iter()
+                .map(|text| AtisLine::TextLine(text.to_string())),
+        );
+        if let Some(logoff_time) = logoff_time {
+            lines.push(AtisLine::LogoffTime(Some(logoff_time)));
+        }
+        lines.push(AtisLine::EndMarker(lines.len()));
+
+        lines
+            .into_iter()
+            .map(|line| ClientQueryResponseMessage::atis(from.as_ref(), to.as_ref(), line))
+            .collect()
+    }
     pub fn real_name(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
@@ -2213,9 +4038,55 @@ impl ClientQueryResponseMessage {
         ClientQueryResponseMessage::new(
             from,
             to,
-            ClientResponseType::Capabilities(capabilities.into()),
+            ClientResponseType::Capabilities(
+                capabilities
+                    .into()
+                    .into_iter()
+                    .map(AdvertisedCapability::from)
+                    .collect(),
+            ),
         )
     }
+    /// Advertises the capabilities in a [`CapabilitySet`], in the order given by [`CapabilitySet::to_vec`].
+    /// Capabilities unknown to this crate, and capabilities advertised as unsupported, which were kept in the set are
+    /// advertised too.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::ClientQueryResponseMessage, parse_message, CapabilitySet, ClientResponseType, FsdMessageType};
+    /// let caps = "$CREGKK_APP:EGLL_N_TWR:CAPS:VERSION=1:ATCINFO=1:MODELDESC=1:NEWCAP=1:STEALTH=0";
+    /// let FsdMessageType::ClientQueryResponseMessage(response) = parse_message(caps).unwrap() else {
+    ///     panic!("expected a client query response");
+    /// };
+    /// let ClientResponseType::Capabilities(capabilities) = response.response_type else {
+    ///     panic!("expected a capabilities response");
+    /// };
+    /// let mut set = CapabilitySet::from(capabilities.as_slice());
+    /// let reply = ClientQueryResponseMessage::capabilities_from_set("EGKK_APP", "EGLL_N_TWR", &set);
+    /// assert_eq!(caps, reply.to_string());
+    ///
+    /// set.fast_positions = true;
+    /// let reply = ClientQueryResponseMessage::capabilities_from_set("EGKK_APP", "EGLL_N_TWR", &set);
+    /// assert_eq!(
+    ///     "$CREGKK_APP:EGLL_N_TWR:CAPS:VERSION=1:ATCINFO=1:MODELDESC=1:NEWCAP=1:STEALTH=0:FASTPOS=1",
+    ///     reply.to_string()
+    /// );
+    ///
+    /// set.stealth = true;
+    /// set.model_matching = false;
+    /// let reply = ClientQueryResponseMessage::capabilities_from_set("EGKK_APP", "EGLL_N_TWR", &set);
+    /// assert_eq!(
+    ///     "$CREGKK_APP:EGLL_N_TWR:CAPS:VERSION=1:ATCINFO=1:MODELDESC=0:NEWCAP=1:STEALTH=1:FASTPOS=1",
+    ///     reply.to_string()
+    /// );
+    /// ```
+    pub fn capabilities_from_set(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        set: &CapabilitySet,
+    ) -> ClientQueryResponseMessage {
+        ClientQueryResponseMessage::new(from, to, ClientResponseType::Capabilities(set.to_vec()))
+    }
     pub fn public_ip(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
@@ -2235,9 +4106,96 @@ impl ClientQueryResponseMessage {
             ClientResponseType::IsValidATC(subject.as_ref().to_uppercase(), valid),
         )
     }
-}
-
-#[derive(Debug)]
+    /// Answers an `ATC` query, addressing the response to the client that sent the query and from the client it
+    /// was sent to. Returns `None` if the query is not an `ATC` query. As with
+    /// [`is_valid_atc_answer`][Self::is_valid_atc_answer], the subject is only a valid controller if it is connected
+    /// as ATC and is not an observer.
+    ///
+    /// If a response omits the subject, it is read as being about the response's recipient.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::*, parse_message, ClientResponseType, FsdMessageType};
+    /// let FsdMessageType::ClientQueryMessage(query) = parse_message("$CQEGLL_N_TWR:SERVER:ATC:egll_s_twr").unwrap() else {
+    ///     panic!("expected a client query");
+    /// };
+    /// let response = ClientQueryResponseMessage::reply_to(&query, true).unwrap();
+    /// assert_eq!("$CRSERVER:EGLL_N_TWR:ATC:Y:EGLL_S_TWR", response.to_string());
+    ///
+    /// let FsdMessageType::ClientQueryResponseMessage(reparsed) = parse_message(response.to_string()).unwrap() else {
+    ///     panic!("expected a client query response");
+    /// };
+    /// let ClientResponseType::IsValidATC(subject, true) = reparsed.response_type else {
+    ///     panic!("expected a valid ATC response");
+    /// };
+    /// assert_eq!("EGLL_S_TWR", subject);
+    ///
+    /// let FsdMessageType::ClientQueryResponseMessage(short) = parse_message("$CRSERVER:egll_n_twr:ATC:N").unwrap() else {
+    ///     panic!("expected a client query response");
+    /// };
+    /// assert!(matches!(short.response_type, ClientResponseType::IsValidATC(subject, false) if subject == "EGLL_N_TWR"));
+    ///
+    /// let observer = ClientQueryMessage::is_valid_atc("EGLL_N_TWR", "SERVER", "EGLL_OBS");
+    /// let response = ClientQueryResponseMessage::reply_to(&observer, true).unwrap();
+    /// assert_eq!("$CRSERVER:EGLL_N_TWR:ATC:N:EGLL_OBS", response.to_string());
+    ///
+    /// assert!(ClientQueryResponseMessage::reply_to(&ClientQueryMessage::capabilities("EGLL_N_TWR", "SERVER"), true).is_none());
+    /// ```
+    pub fn reply_to(
+        query: &ClientQueryMessage,
+        connected_as_atc: bool,
+    ) -> Option<ClientQueryResponseMessage> {
+        match query.query_type {
+            ClientQueryType::IsValidATC(ref subject) => {
+                Some(ClientQueryResponseMessage::is_valid_atc_answer(
+                    &query.to,
+                    &query.from,
+                    subject,
+                    connected_as_atc,
+                ))
+            }
+            _ => None,
+        }
+    }
+    /// Answers an `ATC` query, treating the subject as a valid controller only if it is connected as ATC
+    /// and is not an observer, supervisor or administrator (see [`is_observer_callsign`]).
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::messages::ClientQueryResponseMessage;
+    /// let answer = ClientQueryResponseMessage::is_valid_atc_answer("SERVER", "EGLL_S_TWR", "EGLL_N_TWR", true);
+    /// assert_eq!("$CRSERVER:EGLL_S_TWR:ATC:Y:EGLL_N_TWR", answer.to_string());
+    ///
+    /// let answer = ClientQueryResponseMessage::is_valid_atc_answer("SERVER", "EGLL_S_TWR", "EGLL_OBS", true);
+    /// assert_eq!("$CRSERVER:EGLL_S_TWR:ATC:N:EGLL_OBS", answer.to_string());
+    /// ```
+    pub fn is_valid_atc_answer(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        subject: impl AsRef<str>,
+        connected_as_atc: bool,
+    ) -> ClientQueryResponseMessage {
+        let valid = connected_as_atc && !is_observer_callsign(subject.as_ref());
+        ClientQueryResponseMessage::is_valid_atc(from, to, subject, valid)
+    }
+}
+
+/// Sent by a controller to offer another controller the aircraft it is tracking
+///
+/// Callsigns are upper-cased whether the message is parsed or constructed, so that the aircraft can be compared
+/// directly against callsigns from other messages.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, FsdMessageType};
+/// let FsdMessageType::HandoffOfferMessage(handoff) = parse_message("$HOegll_n_twr:EGLL_S_TWR:baw123").unwrap() else {
+///     panic!("expected a handoff");
+/// };
+/// assert_eq!("EGLL_N_TWR", handoff.from);
+/// assert_eq!("BAW123", handoff.aircraft);
+/// ```
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HandoffOfferMessage {
     pub from: String,
     pub to: String,
@@ -2269,10 +4227,33 @@ impl HandoffOfferMessage {
             aircraft: aircraft.as_ref().to_uppercase(),
         }
     }
+
+    /// Creates the offer which hands an aircraft back to the controller that handed it off, once the handoff has
+    /// been accepted.
+    ///
+    /// The accepting controller becomes the sender and the original controller the recipient, so `from` and `to`
+    /// are swapped relative to the original offer. Both already have this orientation in the accept message.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::messages::{HandoffAcceptMessage, HandoffOfferMessage};
+    /// let offer = HandoffOfferMessage::new("EGLL_N_TWR", "EGLL_S_TWR", "BAW123");
+    /// let accept = HandoffAcceptMessage::new(&offer.to, &offer.from, &offer.aircraft);
+    /// assert_eq!("$HAEGLL_S_TWR:EGLL_N_TWR:BAW123", accept.to_string());
+    ///
+    /// let take_back = HandoffOfferMessage::reverse(&accept);
+    /// assert_eq!(offer.from, take_back.to);
+    /// assert_eq!(offer.to, take_back.from);
+    /// assert_eq!("$HOEGLL_S_TWR:EGLL_N_TWR:BAW123", take_back.to_string());
+    /// ```
+    pub fn reverse(accept: &HandoffAcceptMessage) -> HandoffOfferMessage {
+        HandoffOfferMessage::new(&accept.from, &accept.to, &accept.aircraft)
+    }
 }
 
 #[non_exhaustive]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SharedStateMessage {
     pub from: String,
     pub to: String,
@@ -2306,16 +4287,19 @@ impl TryFrom<&[&str]> for SharedStateMessage {
             ),
             "SC" => {
                 check_min_num_fields!(fields, 6);
-                SharedStateType::ScratchPad(fields[4].to_uppercase(), fields[5].to_string())
+                SharedStateType::ScratchPad(
+                    fields[4].to_uppercase(),
+                    util::assemble_with_colons(&fields[5..]),
+                )
             }
             "TA" => {
                 check_min_num_fields!(fields, 6);
-                let altitude = util::parse_altitude(fields[5])?;
+                let altitude = Level::from_feet(util::parse_altitude(fields[5])?);
                 SharedStateType::TempAltitude(fields[4].to_uppercase(), altitude)
             }
             "FA" => {
                 check_min_num_fields!(fields, 6);
-                let altitude = util::parse_altitude(fields[5])?;
+                let altitude = Level::from_feet(util::parse_altitude(fields[5])?);
                 SharedStateType::FinalAltitude(fields[4].to_uppercase(), altitude)
             }
             "VT" => {
@@ -2334,11 +4318,17 @@ impl TryFrom<&[&str]> for SharedStateMessage {
                     .ok_or(FsdMessageParseError::InvalidFieldCount(5, fields.len()))?
                     .to_uppercase(),
             ),
-            _ => {
-                return Err(FsdMessageParseError::InvalidSharedStateType(
-                    fields[3].to_string(),
-                ))
+            "GD" => {
+                check_min_num_fields!(fields, 6);
+                SharedStateType::GlobalData(
+                    fields[4].to_uppercase(),
+                    util::assemble_with_colons(&fields[5..]),
+                )
             }
+            _ => SharedStateType::Unknown {
+                token: fields[3].to_string(),
+                fields: fields[4..].iter().map(|x| x.to_string()).collect(),
+            },
         };
 
         Ok(SharedStateMessage::new(from, to, shared_state_type))
@@ -2353,6 +4343,38 @@ impl SharedStateMessage {
         }
     }
 
+    /// Opens the shared state version handshake with another controller client
+    pub fn version_query(from: impl AsRef<str>, to: impl AsRef<str>) -> SharedStateMessage {
+        SharedStateMessage::new(from, to, SharedStateType::Version)
+    }
+    /// Builds the next step of the shared state version handshake, addressed back to the sender of `query`.
+    ///
+    /// The handshake runs `VER` → `ID` → `DI`: a `VER` query is answered with `ID`, and an `ID` with `DI`. Returns
+    /// [`None`] for any other message, including `DI`, which completes the handshake.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::SharedStateMessage, SharedStateType};
+    /// let query = SharedStateMessage::version_query("EGLL_N_TWR", "EGLL_S_TWR");
+    /// assert_eq!("#PCEGLL_N_TWR:EGLL_S_TWR:CCP:VER", query.to_string());
+    ///
+    /// let id = SharedStateMessage::respond_version(&query).unwrap();
+    /// assert_eq!(SharedStateType::ID, id.shared_state_type);
+    /// assert_eq!(("EGLL_S_TWR", "EGLL_N_TWR"), (id.from.as_str(), id.to.as_str()));
+    ///
+    /// let di = SharedStateMessage::respond_version(&id).unwrap();
+    /// assert_eq!(SharedStateType::DI, di.shared_state_type);
+    /// assert_eq!(("EGLL_N_TWR", "EGLL_S_TWR"), (di.from.as_str(), di.to.as_str()));
+    ///
+    /// assert!(SharedStateMessage::respond_version(&di).is_none());
+    /// ```
+    pub fn respond_version(query: &SharedStateMessage) -> Option<SharedStateMessage> {
+        match query.shared_state_type {
+            SharedStateType::Version => Some(SharedStateMessage::id(&query.to, &query.from)),
+            SharedStateType::ID => Some(SharedStateMessage::di(&query.to, &query.from)),
+            _ => None,
+        }
+    }
     pub fn id(from: impl AsRef<str>, to: impl AsRef<str>) -> SharedStateMessage {
         SharedStateMessage::new(from, to, SharedStateType::ID)
     }
@@ -2385,18 +4407,60 @@ impl SharedStateMessage {
             ),
         )
     }
+    pub fn global_data(
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        subject: impl AsRef<str>,
+        contents: impl Into<String>,
+    ) -> SharedStateMessage {
+        SharedStateMessage::new(
+            from,
+            to,
+            SharedStateType::GlobalData(subject.as_ref().to_uppercase(), contents.into()),
+        )
+    }
     pub fn temp_altitude(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
         subject: impl AsRef<str>,
-        altitude: u32,
+        altitude: impl Into<Level>,
     ) -> SharedStateMessage {
         SharedStateMessage::new(
             from,
             to,
-            SharedStateType::TempAltitude(subject.as_ref().to_uppercase(), altitude),
+            SharedStateType::TempAltitude(subject.as_ref().to_uppercase(), altitude.into()),
         )
     }
+    /// Converts a `$CQ ... BC` beacon code query into the equivalent `#PC ... CCP:BC` shared state message, keeping
+    /// the sender and recipient. Returns [`None`] if the query is not a beacon code assignment.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{messages::{ClientQueryMessage, SharedStateMessage}, ClientQueryType, SharedStateType};
+    /// let query = ClientQueryMessage::set_beacon_code("EGKK_APP", "@94835", "BAW123", "4721".parse().unwrap());
+    /// let state = SharedStateMessage::from_beacon_query(&query).unwrap();
+    /// assert_eq!("#PCEGKK_APP:@94835:CCP:BC:BAW123:4721", state.to_string());
+    /// let SharedStateType::BeaconCode(ref subject, code) = state.shared_state_type else {
+    ///     panic!("expected a beacon code");
+    /// };
+    /// assert_eq!(("BAW123", "4721"), (subject.as_str(), code.to_string().as_str()));
+    ///
+    /// assert_eq!(query, ClientQueryMessage::from_beacon_state(&state).unwrap());
+    ///
+    /// let query = ClientQueryMessage::who_has("EGKK_APP", "@94835", "BAW123");
+    /// assert!(SharedStateMessage::from_beacon_query(&query).is_none());
+    /// ```
+    pub fn from_beacon_query(query: &ClientQueryMessage) -> Option<SharedStateMessage> {
+        match &query.query_type {
+            ClientQueryType::SetBeaconCode(subject, code) => Some(SharedStateMessage::beacon_code(
+                &query.from,
+                &query.to,
+                subject,
+                *code,
+            )),
+            _ => None,
+        }
+    }
     pub fn beacon_code(
         from: impl AsRef<str>,
         to: impl AsRef<str>,
@@ -2434,7 +4498,19 @@ impl SharedStateMessage {
     }
 }
 
-#[derive(Debug)]
+/// Sent by a controller to accept a [`HandoffOfferMessage`], whose docs describe how callsigns are handled
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, FsdMessageType};
+/// let FsdMessageType::HandoffAcceptMessage(handoff) = parse_message("$HAegll_n_twr:EGLL_S_TWR:baw123").unwrap() else {
+///     panic!("expected a handoff");
+/// };
+/// assert_eq!("EGLL_N_TWR", handoff.from);
+/// assert_eq!("BAW123", handoff.aircraft);
+/// ```
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HandoffAcceptMessage {
     pub from: String,
     pub to: String,
@@ -0,0 +1,110 @@
+use std::time::{Duration, Instant};
+
+use crate::enums::{FsdMessageType, ReplyKind};
+
+/// A request which has been sent and is awaiting a reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingRequest {
+    pub kind: ReplyKind,
+    /// The callsign which sent the request, and which the reply will be addressed to
+    pub from: String,
+    /// The callsign the request was sent to, and which the reply will come from
+    pub to: String,
+    pub sent: Instant,
+}
+
+/// Tracks requests that expect a reply, matching incoming replies to them and reporting those which go unanswered.
+///
+/// A reply matches a request when it is the kind of reply the request expects, comes from the request's recipient
+/// and is addressed to the request's sender. Requests to the same recipient for the same kind of reply are answered
+/// in the order they were sent.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, Instant};
+/// use fsd_interface::{parse_message, PendingRequests, ReplyKind};
+/// let mut pending = PendingRequests::new(Duration::from_secs(30));
+/// let sent = Instant::now();
+/// assert!(pending.send(&parse_message("$PIBAW123:SERVER:1234").unwrap(), sent));
+/// assert!(pending.send(&parse_message("$CQBAW123:EGLL_N_TWR:RN").unwrap(), sent));
+/// assert!(!pending.send(&parse_message("#TMBAW123:EGLL_N_TWR:Hello").unwrap(), sent));
+/// assert_eq!(2, pending.len());
+///
+/// // A pong from somebody else does not answer the ping
+/// assert!(pending.process(&parse_message("$POEZY38UB:BAW123:1234").unwrap()).is_none());
+/// let answered = pending.process(&parse_message("$POSERVER:BAW123:1234").unwrap()).unwrap();
+/// assert_eq!(ReplyKind::Pong, answered.kind);
+///
+/// // The real name query is never answered
+/// assert!(pending.expire(sent + Duration::from_secs(29)).is_empty());
+/// let timed_out = pending.expire(sent + Duration::from_secs(30));
+/// assert_eq!(1, timed_out.len());
+/// assert_eq!((ReplyKind::RealName, "EGLL_N_TWR"), (timed_out[0].kind, timed_out[0].to.as_str()));
+/// assert!(pending.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PendingRequests {
+    timeout: Duration,
+    requests: Vec<PendingRequest>,
+}
+
+impl PendingRequests {
+    /// Creates a tracker in which requests time out `timeout` after they were sent
+    pub fn new(timeout: Duration) -> Self {
+        PendingRequests {
+            timeout,
+            requests: Vec::new(),
+        }
+    }
+
+    /// Records a message sent at `sent` if it expects a reply, returning whether it was recorded
+    pub fn send(&mut self, message: &FsdMessageType, sent: Instant) -> bool {
+        let (Some(kind), Some(from), Some(to)) = (
+            message.expects_reply(),
+            message.sender(),
+            message.recipient(),
+        ) else {
+            return false;
+        };
+        self.requests.push(PendingRequest {
+            kind,
+            from: from.to_uppercase(),
+            to: to.to_uppercase(),
+            sent,
+        });
+        true
+    }
+
+    /// Matches a received message against the pending requests, removing and returning the request it answers
+    pub fn process(&mut self, message: &FsdMessageType) -> Option<PendingRequest> {
+        let (Some(kind), Some(from), Some(to)) =
+            (message.reply_kind(), message.sender(), message.recipient())
+        else {
+            return None;
+        };
+        let index = self.requests.iter().position(|request| {
+            request.kind == kind
+                && request.to.eq_ignore_ascii_case(from)
+                && request.from.eq_ignore_ascii_case(to)
+        })?;
+        Some(self.requests.remove(index))
+    }
+
+    /// Removes and returns every request which has gone unanswered for at least the timeout at `now`
+    pub fn expire(&mut self, now: Instant) -> Vec<PendingRequest> {
+        let timeout = self.timeout;
+        let (expired, pending) = std::mem::take(&mut self.requests)
+            .into_iter()
+            .partition(|request| now.saturating_duration_since(request.sent) >= timeout);
+        self.requests = pending;
+        expired
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
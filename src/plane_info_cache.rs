@@ -0,0 +1,149 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use crate::{enums::FsdMessageType, structs::PlaneInfo};
+
+/// Stores the latest [`PlaneInfo`] received from each aircraft, so that model-matching clients only need to send a
+/// `#SB` plane info request when nothing is known about an aircraft or what is known has gone stale.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, Instant};
+/// use fsd_interface::{parse_message, PlaneInfoCache};
+/// let mut cache = PlaneInfoCache::new(Duration::from_secs(600));
+/// let received = Instant::now();
+/// assert!(cache.needs_request("BAW123", received));
+///
+/// let response = parse_message("#SBBAW123:EZY38UB:PI:GEN:EQUIPMENT=B738:AIRLINE=BAW").unwrap();
+/// assert!(cache.process(&response, received));
+/// assert_eq!(Some("B738"), cache.get("baw123").and_then(|info| info.aircraft_type()));
+/// assert!(!cache.needs_request("BAW123", received + Duration::from_secs(599)));
+///
+/// // Entries go stale once they reach the maximum age
+/// let later = received + Duration::from_secs(600);
+/// assert!(cache.needs_request("BAW123", later));
+/// assert_eq!(1, cache.evict_stale(later));
+/// assert!(cache.is_empty());
+///
+/// // Entries are removed when the aircraft disconnects
+/// cache.process(&response, received);
+/// cache.process(&parse_message("#DPBAW123:1234567").unwrap(), received);
+/// assert!(cache.get("BAW123").is_none());
+/// assert!(cache.needs_request("BAW123", received));
+///
+/// // Aircraft seen sending position updates are listed until plane info is received from them
+/// cache.process(&parse_message("@N:EZY38UB:7000:1:51.47000:-0.45000:83:0:4290769920:-8").unwrap(), received);
+/// cache.process(&parse_message("@N:BAW456:7000:1:51.47000:-0.45000:83:0:4290769920:-8").unwrap(), received);
+/// assert_eq!(vec!["BAW456", "EZY38UB"], cache.callsigns_needing_request(received));
+/// cache.process(&parse_message("#SBEZY38UB:BAW456:PI:GEN:EQUIPMENT=A320:AIRLINE=EZY").unwrap(), received);
+/// assert_eq!(vec!["BAW456"], cache.callsigns_needing_request(received));
+/// cache.process(&parse_message("#DPBAW456:1234567").unwrap(), received);
+/// assert!(cache.callsigns_needing_request(received).is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PlaneInfoCache {
+    max_age: Duration,
+    entries: HashMap<String, (PlaneInfo, Instant)>,
+    connected: HashSet<String>,
+}
+
+impl PlaneInfoCache {
+    /// Creates an empty cache whose entries become stale `max_age` after they were received
+    pub fn new(max_age: Duration) -> Self {
+        PlaneInfoCache {
+            max_age,
+            entries: HashMap::new(),
+            connected: HashSet::new(),
+        }
+    }
+
+    /// Updates the cache from a message received from the network at `received`: plane info responses are stored
+    /// against the aircraft that sent them, pilot registrations and position updates mark the aircraft as connected, and pilot
+    /// deregistrations remove the aircraft's entry.
+    ///
+    /// Returns whether the cache was changed.
+    pub fn process(&mut self, message: &FsdMessageType, received: Instant) -> bool {
+        match message {
+            FsdMessageType::PlaneInfoResponseMessage(m) => {
+                self.insert(&m.from, m.plane_info.clone(), received);
+                true
+            }
+            FsdMessageType::PilotRegisterMessage(m) => self.mark_connected(&m.from),
+            FsdMessageType::PilotPositionUpdateMessage(m) => self.mark_connected(&m.callsign),
+            FsdMessageType::VelocityPositionStoppedMessage(m) => self.mark_connected(&m.from),
+            FsdMessageType::VelocityPositionSlowMessage(m) => self.mark_connected(&m.from),
+            FsdMessageType::VelocityPositionFastMessage(m) => self.mark_connected(&m.from),
+            FsdMessageType::PilotDeregisterMessage(m) => {
+                let disconnected = self.connected.remove(&m.from.to_uppercase());
+                self.remove(&m.from).is_some() || disconnected
+            }
+            _ => false,
+        }
+    }
+
+    fn mark_connected(&mut self, callsign: &str) -> bool {
+        if self.connected.contains(callsign) {
+            false
+        } else {
+            self.connected.insert(callsign.to_uppercase())
+        }
+    }
+
+    pub fn insert(&mut self, callsign: impl AsRef<str>, plane_info: PlaneInfo, received: Instant) {
+        self.entries
+            .insert(callsign.as_ref().to_uppercase(), (plane_info, received));
+    }
+
+    /// The latest plane info received from an aircraft, stale or not
+    pub fn get(&self, callsign: impl AsRef<str>) -> Option<&PlaneInfo> {
+        self.entries
+            .get(&callsign.as_ref().to_uppercase())
+            .map(|(plane_info, _)| plane_info)
+    }
+
+    pub fn remove(&mut self, callsign: impl AsRef<str>) -> Option<PlaneInfo> {
+        self.entries
+            .remove(&callsign.as_ref().to_uppercase())
+            .map(|(plane_info, _)| plane_info)
+    }
+
+    /// Whether a plane info request should be sent to an aircraft at `now`, because there is no entry for it or its
+    /// entry is stale
+    pub fn needs_request(&self, callsign: impl AsRef<str>, now: Instant) -> bool {
+        match self.entries.get(&callsign.as_ref().to_uppercase()) {
+            Some((_, received)) => now.saturating_duration_since(*received) >= self.max_age,
+            None => true,
+        }
+    }
+
+    /// The connected aircraft, in alphabetical order, for which [`needs_request`][Self::needs_request] is true
+    pub fn callsigns_needing_request(&self, now: Instant) -> Vec<&str> {
+        let mut callsigns: Vec<&str> = self
+            .connected
+            .iter()
+            .map(String::as_str)
+            .filter(|callsign| self.needs_request(callsign, now))
+            .collect();
+        callsigns.sort_unstable();
+        callsigns
+    }
+
+    /// Removes every entry which is stale at `now`, returning how many were removed
+    pub fn evict_stale(&mut self, now: Instant) -> usize {
+        let before = self.entries.len();
+        let max_age = self.max_age;
+        self.entries
+            .retain(|_, (_, received)| now.saturating_duration_since(*received) < max_age);
+        before - self.entries.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{
+    enums::{FsdMessageType, ParseMode, ProtocolRevision},
+    errors::{FsdMessageParseError, SessionError},
+};
+
+/// The stage of the connection lifecycle a client has reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// Connected, but the client has not yet sent its `$ID` handshake
+    AwaitingHandshake,
+    /// The client has sent its `$ID` handshake but has not yet registered
+    Handshaken,
+    /// The client has registered with `#AA` / `#AP` and may send position updates
+    Registered,
+    /// The client has deregistered with `#DA` / `#DP`
+    Deregistered,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LifecycleEvent {
+    Handshake,
+    Register(ProtocolRevision),
+    PositionUpdate,
+    Deregister,
+}
+
+impl LifecycleEvent {
+    fn from_message(message: &FsdMessageType) -> Option<(&str, LifecycleEvent)> {
+        match message {
+            FsdMessageType::InitialClientHandshakeMessage(m) => {
+                Some((&m.from, LifecycleEvent::Handshake))
+            }
+            FsdMessageType::AtcRegisterMessage(m) => {
+                Some((&m.from, LifecycleEvent::Register(m.protocol)))
+            }
+            FsdMessageType::PilotRegisterMessage(m) => {
+                Some((&m.from, LifecycleEvent::Register(m.protocol)))
+            }
+            FsdMessageType::AtcPositionUpdateMessage(m) => {
+                Some((&m.callsign, LifecycleEvent::PositionUpdate))
+            }
+            FsdMessageType::AtcSecondaryVisCentreMessage(m) => {
+                Some((&m.callsign, LifecycleEvent::PositionUpdate))
+            }
+            FsdMessageType::PilotPositionUpdateMessage(m) => {
+                Some((&m.callsign, LifecycleEvent::PositionUpdate))
+            }
+            FsdMessageType::VelocityPositionStoppedMessage(m) => {
+                Some((&m.from, LifecycleEvent::PositionUpdate))
+            }
+            FsdMessageType::VelocityPositionSlowMessage(m) => {
+                Some((&m.from, LifecycleEvent::PositionUpdate))
+            }
+            FsdMessageType::VelocityPositionFastMessage(m) => {
+                Some((&m.from, LifecycleEvent::PositionUpdate))
+            }
+            FsdMessageType::AtcDeregisterMessage(m) => Some((&m.from, LifecycleEvent::Deregister)),
+            FsdMessageType::PilotDeregisterMessage(m) => {
+                Some((&m.from, LifecycleEvent::Deregister))
+            }
+            _ => None,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            LifecycleEvent::Handshake => "handshake",
+            LifecycleEvent::Register(_) => "registration",
+            LifecycleEvent::PositionUpdate => "position update",
+            LifecycleEvent::Deregister => "deregistration",
+        }
+    }
+}
+
+/// Tracks the connection lifecycle of a single client (handshake → register → position updates → deregister)
+/// and detects messages sent out of order.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, ClientSession, SessionState};
+/// let mut session = ClientSession::new("BAW123");
+/// let lifecycle = [
+///     "$IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890",
+///     "#APBAW123:SERVER:1234567:password:1:101:9:Joe Bloggs",
+///     "@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8",
+///     "#DPBAW123:1234567",
+/// ];
+/// for line in lifecycle {
+///     session.process(&parse_message(line).unwrap()).unwrap();
+/// }
+/// assert_eq!(SessionState::Deregistered, session.state());
+///
+/// // A position update before registration is a protocol violation
+/// let mut session = ClientSession::new("BAW123");
+/// let position = parse_message("@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8").unwrap();
+/// assert!(session.process(&position).is_err());
+/// assert_eq!(SessionState::AwaitingHandshake, session.state());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientSession {
+    callsign: String,
+    state: SessionState,
+    protocol: Option<ProtocolRevision>,
+}
+
+impl ClientSession {
+    pub fn new(callsign: impl AsRef<str>) -> Self {
+        ClientSession {
+            callsign: callsign.as_ref().to_uppercase(),
+            state: SessionState::AwaitingHandshake,
+            protocol: None,
+        }
+    }
+
+    pub fn callsign(&self) -> &str {
+        &self.callsign
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    /// The protocol revision the client registered with, or `None` before it has registered
+    pub fn protocol(&self) -> Option<ProtocolRevision> {
+        self.protocol
+    }
+
+    /// Parses a message received on this client's connection, reading pilot position updates according to the
+    /// protocol revision it registered with. Until the client has registered, they are read as
+    /// [`ProtocolRevision::Vatsim2022`], as [`parse_message`][crate::parse_message] does.
+    ///
+    /// The session is not advanced; pass the parsed message to [`ClientSession::process`] for that.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{ClientSession, FsdMessageType, ProtocolRevision};
+    /// let mut session = ClientSession::new("BAW123");
+    /// for line in [
+    ///     "$IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890",
+    ///     "#APBAW123:SERVER:1234567:password:1:9:1:Joe Bloggs",
+    /// ] {
+    ///     let message = session.parse_message(line).unwrap();
+    ///     session.process(&message).unwrap();
+    /// }
+    /// assert_eq!(Some(ProtocolRevision::Classic), session.protocol());
+    ///
+    /// // A classic client sends no pressure altitude difference
+    /// let position = session.parse_message("@N:BAW123:7000:1:51.47000:-0.45000:1200:140:4290769920").unwrap();
+    /// let FsdMessageType::PilotPositionUpdateMessage(position) = position else {
+    ///     panic!("expected a position update");
+    /// };
+    /// assert_eq!(1200.0, position.pressure_altitude);
+    /// ```
+    pub fn parse_message(
+        &self,
+        message: impl AsRef<str>,
+    ) -> Result<FsdMessageType, FsdMessageParseError> {
+        FsdMessageType::identify_with_protocol(
+            message.as_ref(),
+            ParseMode::default(),
+            self.protocol.unwrap_or(ProtocolRevision::Vatsim2022),
+        )
+    }
+
+    /// Advances the session with a message received from the network.
+    ///
+    /// Messages sent by other callsigns, and messages which play no part in the lifecycle, are ignored.
+    /// If the message is not valid in the current state, a [`SessionError`] is returned and the state is left unchanged.
+    pub fn process(&mut self, message: &FsdMessageType) -> Result<SessionState, SessionError> {
+        let event = match LifecycleEvent::from_message(message) {
+            Some((callsign, event)) if callsign == self.callsign => event,
+            _ => return Ok(self.state),
+        };
+
+        self.state = match (self.state, event) {
+            (SessionState::AwaitingHandshake, LifecycleEvent::Handshake) => {
+                SessionState::Handshaken
+            }
+            (SessionState::Handshaken, LifecycleEvent::Register(protocol)) => {
+                self.protocol = Some(protocol);
+                SessionState::Registered
+            }
+            (SessionState::Registered, LifecycleEvent::PositionUpdate) => SessionState::Registered,
+            (SessionState::Registered, LifecycleEvent::Deregister) => SessionState::Deregistered,
+            (state, event) => {
+                return Err(SessionError::UnexpectedMessage(
+                    event.description(),
+                    self.callsign.clone(),
+                    state,
+                ))
+            }
+        };
+        Ok(self.state)
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A running digest over the wire form of a sequence of messages, used to detect recorded sessions
+/// which have been truncated or corrupted.
+///
+/// The digest uses 64-bit FNV-1a, so it is stable between runs and builds. Messages are folded in
+/// their re-serialised form, so two recordings of the same session produce the same digest even if
+/// the raw text differed in ways the parser normalises (such as callsign case).
+///
+/// Only what a message re-serialises to is covered. The FSInn plane information messages
+/// ([`FsdMessageType::FSInnPlaneInformationRequestMessage`] and
+/// [`FsdMessageType::FSInnPlaneInformationResponseMessage`]) carry no fields and serialise as just
+/// `FSIPIR` or `FSIPI`, so corruption in the rest of those lines is not detected.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{parse_message, SessionDigest};
+/// let recording = [
+///     "$IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890",
+///     "#APBAW123:SERVER:1234567:password:1:101:9:Joe Bloggs",
+///     "@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8",
+///     "#DPBAW123:1234567",
+/// ];
+///
+/// let mut original = SessionDigest::new();
+/// let mut replay = SessionDigest::new();
+/// for line in recording {
+///     original.update(&parse_message(line).unwrap());
+///     replay.update(&parse_message(line).unwrap());
+/// }
+/// assert_eq!(original, replay);
+/// assert_eq!(4, original.message_count());
+///
+/// // A truncated recording does not produce the same digest
+/// let mut truncated = SessionDigest::new();
+/// for line in &recording[..3] {
+///     truncated.update(&parse_message(line).unwrap());
+/// }
+/// assert_ne!(original.value(), truncated.value());
+///
+/// // Neither does a recording with a corrupted line
+/// let mut corrupted = SessionDigest::new();
+/// for line in recording {
+///     let line = line.replace("51.47000", "51.74000");
+///     corrupted.update(&parse_message(line).unwrap());
+/// }
+/// assert_eq!(original.message_count(), corrupted.message_count());
+/// assert_ne!(original.value(), corrupted.value());
+///
+/// // FSInn plane information lines are only covered as far as their token
+/// let mut request = SessionDigest::new();
+/// request.update(&parse_message("#SBEZY38UB:BAW123:FSIPIR").unwrap());
+/// let mut mangled = SessionDigest::new();
+/// mangled.update(&parse_message("#SBEZY38UB:BAW124:FSIPIR").unwrap());
+/// assert_eq!(request, mangled);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionDigest {
+    hash: u64,
+    message_count: usize,
+}
+
+impl Default for SessionDigest {
+    fn default() -> Self {
+        SessionDigest::new()
+    }
+}
+
+impl SessionDigest {
+    pub fn new() -> Self {
+        SessionDigest {
+            hash: FNV_OFFSET_BASIS,
+            message_count: 0,
+        }
+    }
+
+    /// Folds a message into the digest
+    pub fn update(&mut self, message: &FsdMessageType) {
+        // Each message is terminated with a newline so that message boundaries contribute to the digest
+        for byte in message.to_string().bytes().chain(std::iter::once(b'\n')) {
+            self.hash ^= u64::from(byte);
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+        self.message_count += 1;
+    }
+
+    pub fn value(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.message_count
+    }
+}
@@ -1,10 +1,23 @@
-use std::{fmt::Display, str::FromStr};
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
 use bevy_reflect::Reflect;
 
-use crate::{enums::FlightRules, errors::FsdMessageParseError, util::parse_altitude};
+use crate::{
+    enums::{
+        AdvertisedCapability, ClientCapability, FlightPlanFormat, FlightPlanWarning, FlightRules,
+        ParseMode,
+    },
+    errors::FsdMessageParseError,
+    util::{assemble_with_colons, parse_altitude},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
 pub struct TransponderCode(u16);
 impl TryFrom<u16> for TransponderCode {
     type Error = FsdMessageParseError;
@@ -29,6 +42,17 @@ impl TryFrom<u16> for TransponderCode {
         }
     }
 }
+impl TryFrom<String> for TransponderCode {
+    type Error = FsdMessageParseError;
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        code.parse()
+    }
+}
+impl From<TransponderCode> for String {
+    fn from(code: TransponderCode) -> Self {
+        code.to_string()
+    }
+}
 impl FromStr for TransponderCode {
     type Err = FsdMessageParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -44,19 +68,348 @@ impl Display for TransponderCode {
     }
 }
 
+impl TransponderCode {
+    /// Codes with a reserved meaning, which are never assigned to an aircraft: 0000 and the unlawful interference,
+    /// radio failure and emergency codes
+    pub const RESERVED: [TransponderCode; 4] = [
+        TransponderCode(0),
+        TransponderCode(7500),
+        TransponderCode(7600),
+        TransponderCode(7700),
+    ];
+
+    pub fn is_reserved(&self) -> bool {
+        TransponderCode::RESERVED.contains(self)
+    }
+
+    /// The code as the octal number it represents, from 0 to 4095
+    fn octal_value(&self) -> u16 {
+        let digits = [
+            self.0 / 1000,
+            self.0 / 100 % 10,
+            self.0 / 10 % 10,
+            self.0 % 10,
+        ];
+        digits.into_iter().fold(0, |value, digit| value * 8 + digit)
+    }
+
+    fn from_octal_value(value: u16) -> TransponderCode {
+        TransponderCode(
+            (value >> 9) * 1000 + (value >> 6 & 7) * 100 + (value >> 3 & 7) * 10 + (value & 7),
+        )
+    }
+
+    /// The code packed into the 12 bits its four octal digits need, so 7700 is `0o7700`, or 4032. Unlike
+    /// [`TryFrom<u16>`], which takes the digits of the code as written, this is the compact form meant for storage.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::TransponderCode;
+    /// let code = |s: &str| s.parse::<TransponderCode>().unwrap();
+    /// assert_eq!(0, code("0000").as_u16());
+    /// assert_eq!(0o7700, code("7700").as_u16());
+    /// assert_eq!(4095, code("7777").as_u16());
+    /// for s in ["0000", "7700", "7777"] {
+    ///     assert_eq!(code(s), TransponderCode::from_u16(code(s).as_u16()).unwrap());
+    /// }
+    /// ```
+    pub fn as_u16(&self) -> u16 {
+        self.octal_value()
+    }
+
+    /// Unpacks a code stored with [`TransponderCode::as_u16`]. Values from `0o10000` upwards would need a fifth
+    /// octal digit, and are rejected.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::TransponderCode;
+    /// assert_eq!("1234", TransponderCode::from_u16(0o1234).unwrap().to_string());
+    /// assert!(TransponderCode::from_u16(0o10000).is_err());
+    /// assert!(TransponderCode::from_u16(u16::MAX).is_err());
+    /// ```
+    pub fn from_u16(value: u16) -> Result<TransponderCode, FsdMessageParseError> {
+        if value > 0o7777 {
+            return Err(FsdMessageParseError::InvalidTransponderCode(format!(
+                "{:o}",
+                value
+            )));
+        }
+        Ok(TransponderCode::from_octal_value(value))
+    }
+
+    /// The code after this one, counting in octal and wrapping round from 7777 to 0000. Reserved codes are not
+    /// skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::TransponderCode;
+    /// let code = |s: &str| s.parse::<TransponderCode>().unwrap();
+    /// assert_eq!(code("1235"), code("1234").next());
+    /// assert_eq!(code("1240"), code("1237").next());
+    /// assert_eq!(code("7700"), code("7677").next());
+    /// assert_eq!(code("0000"), code("7777").next());
+    /// ```
+    pub fn next(&self) -> TransponderCode {
+        TransponderCode::from_octal_value((self.octal_value() + 1) % 4096)
+    }
+
+    /// Every code from `start` to `end` inclusive that may be assigned to an aircraft, skipping the
+    /// [`RESERVED`][TransponderCode::RESERVED] codes. If `end` comes before `start`, the block wraps round from
+    /// 7777 to 0000.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::TransponderCode;
+    /// let code = |s: &str| s.parse::<TransponderCode>().unwrap();
+    /// let block: Vec<_> = TransponderCode::block_iter(code("7675"), code("7702")).map(|c| c.to_string()).collect();
+    /// assert_eq!(vec!["7675", "7676", "7677", "7701", "7702"], block);
+    ///
+    /// let wrapped: Vec<_> = TransponderCode::block_iter(code("7776"), code("0002")).map(|c| c.to_string()).collect();
+    /// assert_eq!(vec!["7776", "7777", "0001", "0002"], wrapped);
+    ///
+    /// // The first free code in a block
+    /// let assigned = [code("4610"), code("4611")];
+    /// let free = TransponderCode::block_iter(code("4610"), code("4677")).find(|c| !assigned.contains(c));
+    /// assert_eq!(Some(code("4612")), free);
+    /// ```
+    pub fn block_iter(
+        start: TransponderCode,
+        end: TransponderCode,
+    ) -> impl Iterator<Item = TransponderCode> {
+        let length = (end.octal_value() + 4096 - start.octal_value()) % 4096 + 1;
+        std::iter::successors(Some(start), |code| Some(code.next()))
+            .take(length as usize)
+            .filter(|code| !code.is_reserved())
+    }
+}
+
+/// An assigned altitude, such as a controller's temporary or final altitude for an aircraft, held in feet.
+///
+/// The wire convention for these fields is a plain number of feet with no prefix, so FL350 is written `35000` and
+/// 5000 ft is written `5000`. [`Display`] produces exactly that form, and it is used wherever a level is written.
+/// Fields read from the wire are taken as feet, with an `FL` prefix also accepted since some clients send flight
+/// levels in that form.
+///
+/// [`FromStr`] is meant for levels typed by a controller, who writes `350` for FL350 but `5000` for 5000 ft. A plain
+/// number up to [`Level::MAX_SHORTHAND_FLIGHT_LEVEL`] is therefore read as a flight level, and anything larger as
+/// feet. `FL350` is always a flight level.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{messages::ClientQueryMessage, parse_message, ClientQueryType, FsdMessageType, Level};
+/// assert_eq!("35000", Level::from_flight_level(350).to_string());
+/// assert_eq!("5000", Level::from_feet(5000).to_string());
+/// assert_eq!(Level::from_flight_level(350), "FL350".parse().unwrap());
+/// assert_eq!(Level::from_flight_level(350), "350".parse().unwrap());
+/// assert_eq!(Level::from_flight_level(100), "FL100".parse().unwrap());
+/// assert_eq!(Level::from_feet(5000), "5000".parse().unwrap());
+/// assert_eq!(Level::from_feet(10000), "10000".parse().unwrap());
+///
+/// let message = ClientQueryMessage::set_temp_altitude("EGLL_APP", "@94835", "BAW123", Level::from_flight_level(70));
+/// assert_eq!("$CQEGLL_APP:@94835:TA:BAW123:7000", message.to_string());
+/// let FsdMessageType::ClientQueryMessage(reparsed) = parse_message(&message.to_string()).unwrap() else {
+///     panic!("expected a client query");
+/// };
+/// let ClientQueryType::SetTempAltitude(_, level) = reparsed.query_type else {
+///     panic!("expected a temporary altitude");
+/// };
+/// assert_eq!(Level::from_feet(7000), level);
+/// assert_eq!(70, level.flight_level());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Level(u32);
+
+impl Level {
+    /// The largest plain number that [`FromStr`] reads as a flight level rather than as feet
+    pub const MAX_SHORTHAND_FLIGHT_LEVEL: u32 = 600;
+
+    pub fn from_feet(feet: u32) -> Level {
+        Level(feet)
+    }
+
+    pub fn from_flight_level(flight_level: u32) -> Level {
+        Level(flight_level * 100)
+    }
+
+    pub fn feet(&self) -> u32 {
+        self.0
+    }
+
+    /// The level in hundreds of feet, rounded down
+    pub fn flight_level(&self) -> u32 {
+        self.0 / 100
+    }
+
+    /// Whether an actual altitude in feet, such as the pressure altitude of a
+    /// [`PilotPositionUpdateMessage`][crate::messages::PilotPositionUpdateMessage], is within `tolerance_ft` of this
+    /// level. Levels are held in feet however they were entered, so FL350 is compared against 35,000 ft.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::Level;
+    /// let cleared: Level = "350".parse().unwrap();
+    /// assert!(cleared.matches_altitude(35000.0, 200));
+    /// assert!(cleared.matches_altitude(35180.0, 200));
+    /// assert!(cleared.matches_altitude(34800.0, 200));
+    /// assert!(!cleared.matches_altitude(35250.0, 200));
+    /// assert!(!cleared.matches_altitude(34700.0, 200));
+    /// ```
+    pub fn matches_altitude(&self, altitude_ft: f64, tolerance_ft: u32) -> bool {
+        (altitude_ft - f64::from(self.0)).abs() <= f64::from(tolerance_ft)
+    }
+}
+
+impl From<u32> for Level {
+    fn from(feet: u32) -> Self {
+        Level(feet)
+    }
+}
+
+/// Reads a level typed by a controller. This is not the inverse of [`Display`]: a level of up to 600 ft is written
+/// as a plain number of feet, which this reads back as a flight level. Levels read from the wire are taken as feet
+/// instead.
+///
+/// # Example
+/// ```
+/// use fsd_interface::Level;
+/// let level = Level::from_feet(500);
+/// assert_eq!("500", level.to_string());
+/// assert_eq!(Level::from_flight_level(500), level.to_string().parse().unwrap());
+/// assert_ne!(level, level.to_string().parse().unwrap());
+///
+/// // Above the shorthand range, the two agree
+/// let level = Level::from_feet(5000);
+/// assert_eq!(level, level.to_string().parse().unwrap());
+/// ```
+impl FromStr for Level {
+    type Err = FsdMessageParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let level = parse_altitude(s)?;
+        if level <= Self::MAX_SHORTHAND_FLIGHT_LEVEL && !s.to_uppercase().starts_with("FL") {
+            Ok(Level::from_flight_level(level))
+        } else {
+            Ok(Level(level))
+        }
+    }
+}
+
+/// Writes the level in feet, as sent on the wire. The output is meant for the wire and not for [`FromStr`], which
+/// reads levels of up to 600 ft back as flight levels.
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The elevation of a point on the ground, in feet above mean sea level, such as the reference elevation sent by
+/// an ATC client in its position updates.
+///
+/// [`Elevation::try_new`] rejects values that no point on Earth could have: more than a little below the shore of
+/// the Dead Sea, or above the summit of Everest. Parsing and [`From<i32>`] accept any value, so that a client
+/// reporting an odd elevation doesn't prevent its position update from being read.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{errors::FsdMessageParseError, Elevation};
+/// assert_eq!(-2000, Elevation::try_new(-2000).unwrap().feet());
+/// assert_eq!(30000, Elevation::try_new(30000).unwrap().feet());
+/// assert!(matches!(Elevation::try_new(-2001), Err(FsdMessageParseError::InvalidAltitude(_))));
+/// assert!(matches!(Elevation::try_new(30001), Err(FsdMessageParseError::InvalidAltitude(_))));
+///
+/// let heathrow = Elevation::from(83);
+/// assert!((heathrow.meters() - 25.3).abs() < 0.01);
+/// assert_eq!(Elevation::from(50000), "50000".parse().unwrap());
+/// assert_eq!("83", heathrow.to_string());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Elevation(i32);
+
+impl Elevation {
+    /// The lowest accepted elevation, allowing a margin below the Dead Sea shore (about -1,410 ft)
+    pub const MIN_FEET: i32 = -2000;
+    /// The highest accepted elevation, allowing a margin above the summit of Everest (about 29,032 ft)
+    pub const MAX_FEET: i32 = 30000;
+
+    pub fn try_new(feet: i32) -> Result<Elevation, FsdMessageParseError> {
+        if (Elevation::MIN_FEET..=Elevation::MAX_FEET).contains(&feet) {
+            Ok(Elevation(feet))
+        } else {
+            Err(FsdMessageParseError::InvalidAltitude(feet.to_string()))
+        }
+    }
+
+    pub fn feet(&self) -> i32 {
+        self.0
+    }
+
+    pub fn meters(&self) -> f64 {
+        self.0 as f64 * 0.3048
+    }
+}
+
+impl From<i32> for Elevation {
+    fn from(feet: i32) -> Self {
+        Elevation(feet)
+    }
+}
+
+impl FromStr for Elevation {
+    type Err = FsdMessageParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Elevation)
+            .map_err(|_| FsdMessageParseError::InvalidAltitude(s.to_string()))
+    }
+}
+
+impl Display for Elevation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// Represents a VHF, airband radio frequenxy from 118.000 MHz to 137.000 MHz.
 ///
-/// Stored internally as the left part and the right part. For example, 118.3MHz is `RadioFrequency(118, 300)`.
+/// Stored internally as the left part and the right part: the whole MHz, and the kHz above that from 0 to 999. For
+/// example, 118.3MHz is `RadioFrequency(118, 300)`. Frequencies are held as the channel name a pilot would dial,
+/// which for 8.33 kHz channels is not quite the actual frequency (see [`RadioFrequency::canonical_channel`]).
+///
+/// Two frequencies outside the airband are also accepted: `RadioFrequency(199, 998)`, sent by ATC clients with no
+/// primary frequency such as observers, and `RadioFrequency(149, 999)`, the channel ATC clients use to send each other
+/// text (see [`ATC_TEXT_CHANNEL_FREQUENCY`][crate::messages::ATC_TEXT_CHANNEL_FREQUENCY]).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "(u16, u16)", into = "(u16, u16)"))]
 pub struct RadioFrequency(pub(crate) u16, pub(crate) u16);
+/// Checks the two parts in the same way as [`RadioFrequency::new`]
+///
+/// # Example
+/// ```
+/// use fsd_interface::RadioFrequency;
+/// assert_eq!(RadioFrequency::new(118, 300).unwrap(), (118, 300).try_into().unwrap());
+/// assert!(RadioFrequency::try_from((108, 0)).is_err());
+/// ```
+impl TryFrom<(u16, u16)> for RadioFrequency {
+    type Error = FsdMessageParseError;
+    fn try_from((left, right): (u16, u16)) -> Result<Self, Self::Error> {
+        RadioFrequency::new(left, right)
+    }
+}
+impl From<RadioFrequency> for (u16, u16) {
+    fn from(frequency: RadioFrequency) -> Self {
+        frequency.frequency()
+    }
+}
 impl RadioFrequency {
     /// Creates a new [`RadioFrequency`] from two parts
     ///
     /// # Example
     /// ```
-    /// use fsd_messages::util::RadioFrequency;
+    /// use fsd_interface::RadioFrequency;
     /// let freq = RadioFrequency::new(118, 300).unwrap();
-    /// assert_eq!(RadioFrequency(118, 300), freq);
+    /// assert_eq!((118, 300), freq.frequency());
     /// ```
     pub fn new(left: u16, right: u16) -> Result<RadioFrequency, FsdMessageParseError> {
         if !((118..=137).contains(&left)
@@ -71,6 +424,38 @@ impl RadioFrequency {
         Ok(RadioFrequency(left, right))
     }
 
+    /// Creates a new [`RadioFrequency`] from a value in MHz, rounded to the nearest kHz. Values outside the range
+    /// accepted by [`RadioFrequency::new`] are rejected.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::RadioFrequency;
+    /// let freq = RadioFrequency::from_mhz(121.5).unwrap();
+    /// assert_eq!((121, 500), freq.frequency());
+    /// assert_eq!(121.5, freq.as_mhz());
+    /// assert_eq!("21500", freq.to_string());
+    /// assert_eq!(freq, freq.to_string().parse().unwrap());
+    /// assert_eq!(freq, RadioFrequency::try_from_human_readable_string(freq.to_human_readable_string()).unwrap());
+    ///
+    /// assert_eq!((132, 340), RadioFrequency::from_mhz(132.3399999).unwrap().frequency());
+    /// assert_eq!((122, 0), RadioFrequency::from_mhz(121.9996).unwrap().frequency());
+    /// assert!(RadioFrequency::from_mhz(108.0).is_err());
+    /// assert!(RadioFrequency::from_mhz(f64::NAN).is_err());
+    /// ```
+    pub fn from_mhz(mhz: f64) -> Result<RadioFrequency, FsdMessageParseError> {
+        let khz = (mhz * 1000.0).round();
+        if !(0.0..=f64::from(u16::MAX) * 1000.0).contains(&khz) {
+            return Err(FsdMessageParseError::InvalidFrequency(mhz.to_string()));
+        }
+        let khz = khz as u32;
+        RadioFrequency::new((khz / 1000) as u16, (khz % 1000) as u16)
+    }
+
+    /// The frequency in MHz
+    pub fn as_mhz(&self) -> f64 {
+        f64::from(self.0) + f64::from(self.1) / 1000.0
+    }
+
     pub fn frequency(&self) -> (u16, u16) {
         (self.0, self.1)
     }
@@ -79,7 +464,7 @@ impl RadioFrequency {
     ///
     /// # Example
     /// ```
-    /// use fsd_messages::util::RadioFrequency;
+    /// use fsd_interface::RadioFrequency;
     /// let freq = RadioFrequency::new(133, 175).unwrap();
     /// let human_readable = freq.to_human_readable_string();
     /// assert_eq!(human_readable, String::from("133.175"));
@@ -87,6 +472,171 @@ impl RadioFrequency {
     pub fn to_human_readable_string(&self) -> String {
         format!("{}.{:03}", self.0, self.1)
     }
+
+    /// Returns the frequency in the form XXX.YYY, alongside [`to_plain_string`][RadioFrequency::to_plain_string] and
+    /// [`to_symbol_string`][RadioFrequency::to_symbol_string]. This is the same as
+    /// [`to_human_readable_string`][RadioFrequency::to_human_readable_string].
+    pub fn to_human(&self) -> String {
+        self.to_human_readable_string()
+    }
+
+    /// Returns the frequency in the short form used in position updates, e.g. `33175` for 133.175 MHz.
+    /// This is the same as the [`Display`] implementation.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::RadioFrequency;
+    /// let freq = RadioFrequency::new(133, 175).unwrap();
+    /// assert_eq!(freq.to_plain_string(), String::from("33175"));
+    /// assert_eq!(freq.to_symbol_string(), String::from("@33175"));
+    /// assert_eq!(freq.to_human(), String::from("133.175"));
+    /// ```
+    pub fn to_plain_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns the frequency in the short form prefixed with `@`, as used for the recipient of frequency text messages, e.g. `@33175`
+    pub fn to_symbol_string(&self) -> String {
+        format!("@{}", self)
+    }
+
+    /// Maps the frequency to the name of the radio channel it is on, so that frequencies reported by different
+    /// clients for the same channel compare equal.
+    ///
+    /// With 8.33 kHz channel spacing, each 25 kHz block holds three channels whose names differ from their actual
+    /// frequencies. For the block starting at 121.300 MHz:
+    ///
+    /// | Channel name        | Actual frequency |
+    /// |---------------------|------------------|
+    /// | 121.300 and 121.305 | 121.300 MHz      |
+    /// | 121.310             | 121.30833 MHz    |
+    /// | 121.315             | 121.31667 MHz    |
+    ///
+    /// 121.300 is the name of the 25 kHz channel and 121.305 that of the 8.33 kHz channel on the same frequency, so
+    /// both become 121.300. Some simulators report the actual frequency truncated to whole kHz, such as 121.308 or
+    /// 121.316, and these become the name of the channel on that frequency. Any other value becomes the name of the
+    /// channel nearest to it. The two frequencies outside the airband, 149.999 for ATC text and 199.998 for no
+    /// primary frequency, are returned unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::RadioFrequency;
+    /// let channel = |freq: &str| {
+    ///     RadioFrequency::try_from_human_readable_string(freq)
+    ///         .unwrap()
+    ///         .canonical_channel()
+    ///         .to_human_readable_string()
+    /// };
+    /// assert_eq!("121.300", channel("121.300"));
+    /// assert_eq!("121.300", channel("121.305"));
+    /// assert_eq!("121.310", channel("121.310"));
+    /// assert_eq!("121.310", channel("121.308"));
+    /// assert_eq!("121.315", channel("121.315"));
+    /// assert_eq!("121.315", channel("121.316"));
+    /// assert_eq!("121.325", channel("121.322"));
+    /// assert_eq!("122.000", channel("121.998"));
+    /// assert_eq!("149.999", channel("149.999"));
+    /// assert_eq!("199.998", channel("199.998"));
+    ///
+    /// let pilot = RadioFrequency::try_from_human_readable_string("132.308").unwrap();
+    /// let controller = RadioFrequency::try_from_human_readable_string("132.310").unwrap();
+    /// assert_ne!(pilot, controller);
+    /// assert_eq!(pilot.canonical_channel(), controller.canonical_channel());
+    /// ```
+    pub fn canonical_channel(&self) -> RadioFrequency {
+        const CHANNEL_NAME_OFFSETS: [u16; 4] = [0, 10, 15, 25];
+
+        if !(118..=137).contains(&self.0) {
+            return *self;
+        }
+        let block = self.1 / 25 * 25;
+        let offset = self.1 % 25;
+        // Channel names, then the nearest of the frequencies in thirds of a kHz
+        let channel = match offset {
+            0 | 5 => 0,
+            10 => 1,
+            15 => 2,
+            _ => ((offset * 3 + 12) / 25) as usize,
+        };
+        let khz = block + CHANNEL_NAME_OFFSETS[channel];
+        RadioFrequency(self.0 + khz / 1000, khz % 1000)
+    }
+
+    /// Whether the frequency is the name of a channel with 25 kHz spacing, from 118.000 to 136.975 MHz in steps of
+    /// 25 kHz
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::RadioFrequency;
+    /// assert!(RadioFrequency::new(118, 0).unwrap().is_valid_25khz());
+    /// assert!(RadioFrequency::new(121, 375).unwrap().is_valid_25khz());
+    /// assert!(RadioFrequency::new(136, 975).unwrap().is_valid_25khz());
+    /// assert!(!RadioFrequency::new(121, 380).unwrap().is_valid_25khz());
+    /// assert!(!RadioFrequency::new(137, 0).unwrap().is_valid_25khz());
+    /// assert!(!RadioFrequency::new(199, 998).unwrap().is_valid_25khz());
+    /// ```
+    pub fn is_valid_25khz(&self) -> bool {
+        (118..=136).contains(&self.0) && self.1.is_multiple_of(25)
+    }
+
+    /// Whether the frequency is the name of a channel with 8.33 kHz spacing, from 118.005 to 136.990 MHz. Each 25 kHz
+    /// block holds three such channels, whose names end 5, 10 and 15 kHz into the block: .005, .010, .015, .030,
+    /// .035, .040 and so on.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::RadioFrequency;
+    /// assert!(RadioFrequency::new(118, 5).unwrap().is_valid_8_33());
+    /// assert!(RadioFrequency::new(132, 340).unwrap().is_valid_8_33());
+    /// assert!(RadioFrequency::new(136, 990).unwrap().is_valid_8_33());
+    /// assert!(!RadioFrequency::new(132, 325).unwrap().is_valid_8_33());
+    /// assert!(!RadioFrequency::new(132, 320).unwrap().is_valid_8_33());
+    /// assert!(!RadioFrequency::new(132, 308).unwrap().is_valid_8_33());
+    /// ```
+    pub fn is_valid_8_33(&self) -> bool {
+        (118..=136).contains(&self.0) && matches!(self.1 % 25, 5 | 10 | 15)
+    }
+
+    /// The name of the legal channel, with either 25 kHz or 8.33 kHz spacing, closest to this frequency. Frequencies
+    /// outside the airband are moved to its nearest end, except for the special frequencies, which are returned
+    /// unchanged. A frequency exactly between two channels snaps to the lower.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::RadioFrequency;
+    /// let nearest = |left, right| RadioFrequency::new(left, right).unwrap().nearest_valid().to_human_readable_string();
+    /// assert_eq!("121.300", nearest(121, 301));
+    /// assert_eq!("121.305", nearest(121, 304));
+    /// assert_eq!("121.310", nearest(121, 308));
+    /// assert_eq!("121.315", nearest(121, 320));
+    /// assert_eq!("121.325", nearest(121, 321));
+    /// assert_eq!("122.000", nearest(121, 999));
+    /// assert_eq!("136.990", nearest(137, 0));
+    /// assert_eq!("199.998", nearest(199, 998));
+    /// assert_eq!("149.999", nearest(149, 999));
+    /// ```
+    pub fn nearest_valid(&self) -> RadioFrequency {
+        if matches!((self.0, self.1), (199, 998) | (149, 999)) {
+            return *self;
+        }
+        if self.0 < 118 {
+            return RadioFrequency(118, 0);
+        }
+        if self.0 >= 137 || self.0 == 136 && self.1 > 990 {
+            return RadioFrequency(136, 990);
+        }
+        let block = self.1 / 25 * 25;
+        let offset = match self.1 % 25 {
+            0..=2 => 0,
+            3..=7 => 5,
+            8..=12 => 10,
+            13..=20 => 15,
+            _ => 25,
+        };
+        let khz = block + offset;
+        RadioFrequency(self.0 + khz / 1000, khz % 1000)
+    }
+
     pub fn try_from_human_readable_string(
         input: impl AsRef<str>,
     ) -> Result<RadioFrequency, FsdMessageParseError> {
@@ -112,7 +662,7 @@ impl RadioFrequency {
 impl FromStr for RadioFrequency {
     type Err = FsdMessageParseError;
     fn from_str(short_form: &str) -> Result<Self, Self::Err> {
-        if short_form.len() != 5 {
+        if short_form.len() != 5 || !short_form.is_ascii() {
             return Err(FsdMessageParseError::InvalidFrequency(
                 short_form.to_string(),
             ));
@@ -133,7 +683,223 @@ impl Display for RadioFrequency {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+/// The result of the best-effort plausibility checks made by
+/// [`PilotPositionUpdateMessage::sanity_flags`][crate::messages::PilotPositionUpdateMessage::sanity_flags].
+///
+/// Each flag is `true` when the check passed. A position passing every check is not guaranteed to be correct;
+/// the checks only catch values that are obviously impossible, such as those produced by clients sending
+/// swapped or garbage coordinates. Whether a position is over land or sea is not checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionSanity {
+    /// The latitude is within ±90° and the longitude within ±180°
+    pub coordinates_in_range: bool,
+    /// The position is not at or next to 0°N 0°E, which uninitialised clients commonly report
+    pub away_from_null_island: bool,
+    /// The true altitude is between the lowest and highest altitudes at which an aircraft could plausibly be
+    pub altitude_plausible: bool,
+    /// An aircraft reporting that it is on the ground is below the highest aerodrome and at a taxi or take-off speed
+    pub ground_state_plausible: bool,
+    /// The pressure altitude is within the range of the true altitude that atmospheric pressure can account for
+    pub pressure_altitude_plausible: bool,
+}
+
+impl PositionSanity {
+    /// Whether every check passed
+    pub fn is_plausible(&self) -> bool {
+        self.coordinates_in_range
+            && self.away_from_null_island
+            && self.altitude_plausible
+            && self.ground_state_plausible
+            && self.pressure_altitude_plausible
+    }
+}
+
+/// Whether the on ground flag of a velocity position update agrees with its height above ground, as reported by
+/// [`VelocityPositionSlowMessage::ground_consistency`][crate::messages::VelocityPositionSlowMessage::ground_consistency]
+/// and its counterparts for stopped and fast updates.
+///
+/// An aircraft is taken to be near the ground when its height above ground is at most
+/// [`GroundConsistency::NEAR_GROUND_AGL_FT`], allowing for the height of its centre of gravity. When the two
+/// disagree the flag is preferred, unless the height strongly contradicts it: an aircraft flagged as on the ground
+/// but more than [`GroundConsistency::AIRBORNE_AGL_FT`] up is airborne, and one flagged as airborne but at or below
+/// ground level is on the ground.
+///
+/// # Example
+/// ```
+/// use fsd_interface::GroundConsistency;
+/// let taxiing = GroundConsistency::assess(true, 6.0);
+/// assert!(taxiing.agrees && taxiing.on_ground);
+///
+/// let climbing = GroundConsistency::assess(false, 1500.0);
+/// assert!(climbing.agrees && !climbing.on_ground);
+///
+/// // Flaring just above the runway: the flag is trusted
+/// let flaring = GroundConsistency::assess(false, 3.0);
+/// assert!(!flaring.agrees && !flaring.on_ground);
+///
+/// // Flagged as on the ground while well above it
+/// let stuck_flag = GroundConsistency::assess(true, 2500.0);
+/// assert!(!stuck_flag.agrees && !stuck_flag.on_ground);
+///
+/// // Flagged as airborne while below ground level
+/// let sunk = GroundConsistency::assess(false, -1.0);
+/// assert!(!sunk.agrees && sunk.on_ground);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroundConsistency {
+    /// The on ground flag and the height above ground agree
+    pub agrees: bool,
+    /// Whether the aircraft should be treated as on the ground
+    pub on_ground: bool,
+}
+
+impl GroundConsistency {
+    /// The greatest height above ground, in feet, at which an aircraft is considered to be near the ground
+    pub const NEAR_GROUND_AGL_FT: f64 = 10.0;
+    /// The height above ground, in feet, above which an aircraft is airborne whatever its on ground flag says
+    pub const AIRBORNE_AGL_FT: f64 = 100.0;
+
+    pub fn assess(on_ground_flag: bool, altitude_agl: f64) -> GroundConsistency {
+        let near_ground = altitude_agl <= Self::NEAR_GROUND_AGL_FT;
+        let on_ground = if on_ground_flag {
+            altitude_agl <= Self::AIRBORNE_AGL_FT
+        } else {
+            altitude_agl <= 0.0
+        };
+        GroundConsistency {
+            agrees: on_ground_flag == near_ground,
+            on_ground,
+        }
+    }
+}
+
+/// The capabilities a client advertised in its `CAPS` response, with the commonly checked ones broken out as
+/// flags.
+///
+/// Every capability, flagged or not and whether supported or not, is also kept in
+/// [`advertised`][CapabilitySet::advertised] in the order it was advertised, so that the set can be turned back into
+/// the list it was built from. ACARS is not advertised as a capability, so has no flag.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{messages::ClientQueryResponseMessage, parse_message, CapabilitySet, ClientCapability, ClientResponseType, FsdMessageType};
+/// let caps = "$CREGKK_APP:EGLL_N_TWR:CAPS:VERSION=1:ATCINFO=1:MODELDESC=1:ACCONFIG=1:FASTPOS=1:STEALTH=0";
+/// let FsdMessageType::ClientQueryResponseMessage(response) = parse_message(caps).unwrap() else {
+///     panic!("expected a client query response");
+/// };
+/// let ClientResponseType::Capabilities(capabilities) = response.response_type else {
+///     panic!("expected a capabilities response");
+/// };
+///
+/// let set = CapabilitySet::from(capabilities.as_slice());
+/// assert!(set.fast_positions);
+/// assert!(set.model_matching);
+/// assert!(set.atc_info);
+/// assert!(!set.stealth);
+/// assert!(set.contains(ClientCapability::ACConfig));
+/// assert!(!set.contains(ClientCapability::Teamspeak));
+///
+/// let reply = ClientQueryResponseMessage::capabilities_from_set("EGKK_APP", "EGLL_N_TWR", &set);
+/// assert_eq!(caps, reply.to_string());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapabilitySet {
+    /// [`ClientCapability::FastPos`]
+    pub fast_positions: bool,
+    /// [`ClientCapability::ModelDesc`]
+    pub model_matching: bool,
+    /// [`ClientCapability::ATCInfo`]
+    pub atc_info: bool,
+    /// [`ClientCapability::Stealth`]
+    pub stealth: bool,
+    /// Every advertised capability, in the order it was advertised. Where a capability has a flag, the flag takes
+    /// precedence.
+    pub advertised: Vec<AdvertisedCapability>,
+}
+
+impl CapabilitySet {
+    pub fn contains(&self, capability: ClientCapability) -> bool {
+        self.flag(capability).unwrap_or_else(|| {
+            self.advertised
+                .contains(&AdvertisedCapability::Known(capability, true))
+        })
+    }
+
+    fn flag(&self, capability: ClientCapability) -> Option<bool> {
+        match capability {
+            ClientCapability::FastPos => Some(self.fast_positions),
+            ClientCapability::ModelDesc => Some(self.model_matching),
+            ClientCapability::ATCInfo => Some(self.atc_info),
+            ClientCapability::Stealth => Some(self.stealth),
+            _ => None,
+        }
+    }
+
+    /// The capabilities in the set, suitable for a `CAPS` response. They are listed in the order they were
+    /// advertised, with flagged capabilities supported or not according to their flag, followed by any whose flag has
+    /// been set but which were not advertised.
+    pub fn to_vec(&self) -> Vec<AdvertisedCapability> {
+        let mut capabilities: Vec<AdvertisedCapability> = self
+            .advertised
+            .iter()
+            .map(|advertised| match *advertised {
+                AdvertisedCapability::Known(capability, supported) => AdvertisedCapability::Known(
+                    capability,
+                    self.flag(capability).unwrap_or(supported),
+                ),
+                _ => advertised.clone(),
+            })
+            .collect();
+        for capability in [
+            ClientCapability::ATCInfo,
+            ClientCapability::ModelDesc,
+            ClientCapability::FastPos,
+            ClientCapability::Stealth,
+        ] {
+            if self.contains(capability)
+                && !capabilities
+                    .iter()
+                    .any(|advertised| advertised.capability() == Some(capability))
+            {
+                capabilities.push(AdvertisedCapability::from(capability));
+            }
+        }
+        capabilities
+    }
+}
+
+impl From<&[AdvertisedCapability]> for CapabilitySet {
+    fn from(capabilities: &[AdvertisedCapability]) -> Self {
+        let mut set = CapabilitySet::default();
+        for advertised in capabilities {
+            if let AdvertisedCapability::Known(capability, supported) = *advertised {
+                match capability {
+                    ClientCapability::FastPos => set.fast_positions = supported,
+                    ClientCapability::ModelDesc => set.model_matching = supported,
+                    ClientCapability::ATCInfo => set.atc_info = supported,
+                    ClientCapability::Stealth => set.stealth = supported,
+                    _ => {}
+                }
+            }
+            set.advertised.push(advertised.clone());
+        }
+        set
+    }
+}
+
+impl From<&[ClientCapability]> for CapabilitySet {
+    fn from(capabilities: &[ClientCapability]) -> Self {
+        let advertised: Vec<AdvertisedCapability> = capabilities
+            .iter()
+            .map(|capability| AdvertisedCapability::from(*capability))
+            .collect();
+        CapabilitySet::from(advertised.as_slice())
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlaneInfo {
     pub equipment: Option<String>,
     pub airline: Option<String>,
@@ -208,9 +974,104 @@ impl PlaneInfo {
             livery,
         }
     }
+
+    fn non_empty(value: &Option<String>) -> Option<&str> {
+        value.as_deref().map(str::trim).filter(|x| !x.is_empty())
+    }
+
+    /// The ICAO aircraft type designator, taken from the `EQUIPMENT` key
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message, FsdMessageType};
+    /// let message = "#SBBAW123:EZY38UB:PI:GEN:EQUIPMENT=B738:AIRLINE=BAW:LIVERY=BA2020";
+    /// let FsdMessageType::PlaneInfoResponseMessage(response) = parse_message(message).unwrap() else {
+    ///     panic!("expected a plane info response");
+    /// };
+    /// assert_eq!(Some("B738"), response.plane_info.aircraft_type());
+    /// assert_eq!(Some("BAW"), response.plane_info.airline());
+    /// assert_eq!(Some("BA2020"), response.plane_info.livery());
+    /// assert_eq!(Some(String::from("B738/BAW")), response.plane_info.model_match_key());
+    ///
+    /// // General aviation aircraft often send only their type
+    /// let message = "#SBN123AB:EZY38UB:PI:GEN:EQUIPMENT=C172:AIRLINE=";
+    /// let FsdMessageType::PlaneInfoResponseMessage(response) = parse_message(message).unwrap() else {
+    ///     panic!("expected a plane info response");
+    /// };
+    /// assert_eq!(None, response.plane_info.airline());
+    /// assert_eq!(None, response.plane_info.livery());
+    /// assert_eq!(Some(String::from("C172")), response.plane_info.model_match_key());
+    ///
+    /// let message = "#SBN123AB:EZY38UB:PI:GEN:LIVERY=RED";
+    /// let FsdMessageType::PlaneInfoResponseMessage(response) = parse_message(message).unwrap() else {
+    ///     panic!("expected a plane info response");
+    /// };
+    /// assert_eq!(None, response.plane_info.aircraft_type());
+    /// assert_eq!(None, response.plane_info.model_match_key());
+    /// ```
+    pub fn aircraft_type(&self) -> Option<&str> {
+        Self::non_empty(&self.equipment)
+    }
+
+    /// The ICAO airline designator, taken from the `AIRLINE` key
+    pub fn airline(&self) -> Option<&str> {
+        Self::non_empty(&self.airline)
+    }
+
+    /// The livery code, taken from the `LIVERY` key
+    pub fn livery(&self) -> Option<&str> {
+        Self::non_empty(&self.livery)
+    }
+
+    /// A key for choosing a CSL model: the upper-cased aircraft type, followed by `/` and the airline if one was sent.
+    /// Returns `None` if no aircraft type was sent.
+    pub fn model_match_key(&self) -> Option<String> {
+        let aircraft_type = self.aircraft_type()?.to_uppercase();
+        match self.airline() {
+            Some(airline) => Some(format!("{}/{}", aircraft_type, airline.to_uppercase())),
+            None => Some(aircraft_type),
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Reflect)]
+/// A filed flight plan, as carried by `$FP` and `$AM` messages.
+///
+/// Equality and hashing are semantic rather than field-by-field, so that the same plan filed twice compares equal.
+/// Both are based on a canonical form of the plan in which the text fields (aircraft type, aerodromes,
+/// remarks and route) are trimmed, upper-cased and have runs of whitespace collapsed to a single space. The route is
+/// compared using [`normalized_route`][FlightPlan::normalized_route], so direct markers also compare equal.
+/// All numeric fields are compared exactly.
+///
+/// This replaces the field-by-field comparison of earlier versions, so plans which differ only in case or spacing
+/// now compare equal. Compare their [`Display`] output to check whether two plans would be sent identically.
+///
+/// # Example
+/// ```
+/// use std::hash::{DefaultHasher, Hash, Hasher};
+/// use fsd_interface::{FlightPlan, FlightRules};
+///
+/// fn hash(plan: &FlightPlan) -> u64 {
+///     let mut hasher = DefaultHasher::new();
+///     plan.hash(&mut hasher);
+///     hasher.finish()
+/// }
+///
+/// let plan = FlightPlan::new(
+///     FlightRules::IFR, "B738", 450, "EGKK", 1200, 0, 36000, "LEMG", 2, 30, 4, 0,
+///     "LEZL", "PBN/A1B1C1D1O1S1", "MIMFO Y312 DVR UL9 KONAN",
+/// );
+/// let mut duplicate = plan.clone();
+/// duplicate.route = String::from("mimfo  Y312 DVR UL9   KONAN ");
+/// assert_eq!(plan, duplicate);
+/// assert_eq!(hash(&plan), hash(&duplicate));
+///
+/// let mut amended = plan.clone();
+/// amended.route = String::from("MIMFO Y312 DVR UL9 KOK");
+/// assert_ne!(plan, amended);
+/// assert_ne!(hash(&plan), hash(&amended));
+/// ```
+#[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FlightPlan {
     pub flight_rules: FlightRules,
     pub ac_type: String,
@@ -229,6 +1090,390 @@ pub struct FlightPlan {
     pub route: String,
 }
 
+impl FlightPlan {
+    /// The remarks field. When the plan was read with [`parse_message_bytes`][crate::parse_message_bytes], any
+    /// remarks not sent as UTF-8 have already been decoded as Windows-1252, so a stray byte never causes the plan to
+    /// be dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{parse_message_bytes, errors::FsdMessageParseError, FsdMessageType};
+    /// let line = b"$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LIRF:2:20:4:0:LIRA:/V/ OAT -5\xB0C:MIMFO Y312 DVR";
+    /// let FsdMessageType::FlightPlanMessage(message) = parse_message_bytes(line).unwrap() else {
+    ///     panic!("expected a flight plan");
+    /// };
+    /// assert_eq!("/V/ OAT -5°C", message.flight_plan.remarks());
+    /// assert_eq!("MIMFO Y312 DVR", message.flight_plan.route);
+    ///
+    /// // Outside the free text fields, the line must still be valid UTF-8
+    /// let line = b"$FPBAW123:*A:I:B738:450:EGLL:1200:0:35000:LI\xB0F:2:20:4:0:LIRA:/V/:MIMFO Y312 DVR";
+    /// assert!(matches!(parse_message_bytes(line), Err(FsdMessageParseError::InvalidEncoding(9))));
+    /// ```
+    pub fn remarks(&self) -> &str {
+        &self.remarks
+    }
+
+    /// Detects whether the aircraft type field is written with ICAO equipment codes or a legacy FAA equipment
+    /// suffix.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{FlightPlan, FlightPlanFormat, FlightRules};
+    /// let mut plan = FlightPlan::new(
+    ///     FlightRules::IFR, "B738/M-SDE2E3FGIJ1RWXY/LB1", 450, "EGLL", 1200, 0, 35000, "LIRF", 2, 20, 4, 0, "LIRA", "", "DCT",
+    /// );
+    /// assert_eq!(FlightPlanFormat::Icao, plan.format_kind());
+    /// plan.ac_type = String::from("H/B744/L");
+    /// assert_eq!(FlightPlanFormat::Faa, plan.format_kind());
+    /// plan.ac_type = String::from("B738");
+    /// assert_eq!(FlightPlanFormat::Unknown, plan.format_kind());
+    /// ```
+    pub fn format_kind(&self) -> FlightPlanFormat {
+        let parts: Vec<&str> = self.ac_type.split('/').collect();
+        if parts.iter().any(|part| part.contains('-')) {
+            FlightPlanFormat::Icao
+        } else if parts.len() >= 2 && Self::faa_equipment(parts[parts.len() - 1]).is_some() {
+            FlightPlanFormat::Faa
+        } else {
+            FlightPlanFormat::Unknown
+        }
+    }
+
+    /// Converts a plan filed with a legacy FAA equipment suffix to ICAO equipment codes, on a best-effort basis.
+    /// Other plans are returned unchanged, and the original plan is left as filed.
+    ///
+    /// The FAA suffix says nothing of wake category, so it is taken from an `H/` or `J/` prefix and is otherwise
+    /// assumed to be medium. Mode C is assumed for any transponder with altitude reporting. Where the suffix implies
+    /// RNAV capability, the matching `PBN/` indicator is added to the start of the remarks unless one is already
+    /// there.
+    ///
+    /// | FAA | ICAO equipment | Surveillance | PBN |
+    /// |---|---|---|---|
+    /// | `X` | `S` | `N` | |
+    /// | `T` | `S` | `A` | |
+    /// | `U` | `S` | `C` | |
+    /// | `D` | `SD` | `N` | |
+    /// | `B` | `SD` | `A` | |
+    /// | `A` | `SD` | `C` | |
+    /// | `G` | `SDGR` | `C` | `B2C2D2` |
+    /// | `W` | `SDW` | `C` | |
+    /// | `Z` | `SDRW` | `C` | `B4C4D4` |
+    /// | `L` | `SDGRW` | `C` | `B2C2D2` |
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{FlightPlan, FlightPlanFormat, FlightRules};
+    /// let plan = FlightPlan::new(
+    ///     FlightRules::IFR, "H/B744/L", 490, "KJFK", 2300, 0, 35000, "EGLL", 6, 40, 8, 0, "EGKK", "/V/", "DCT",
+    /// );
+    /// let icao = plan.to_icao();
+    /// assert_eq!("B744/H-SDGRW/C", icao.ac_type);
+    /// assert_eq!("PBN/B2C2D2 /V/", icao.remarks);
+    /// assert_eq!(FlightPlanFormat::Icao, icao.format_kind());
+    /// assert_eq!("H/B744/L", plan.ac_type);
+    ///
+    /// let plan = FlightPlan::new(
+    ///     FlightRules::VFR, "C172/U", 110, "EGKB", 1000, 0, 2400, "EGKA", 0, 45, 3, 0, "", "", "VFR",
+    /// );
+    /// assert_eq!("C172/M-S/C", plan.to_icao().ac_type);
+    /// assert_eq!("", plan.to_icao().remarks);
+    /// ```
+    pub fn to_icao(&self) -> FlightPlan {
+        let mut plan = self.clone();
+        if self.format_kind() != FlightPlanFormat::Faa {
+            return plan;
+        }
+
+        let parts: Vec<&str> = self.ac_type.split('/').collect();
+        let (wake, ac_type) = match parts.as_slice() {
+            [wake @ ("H" | "J"), ac_type, _] => (*wake, *ac_type),
+            [.., ac_type, _] => ("M", *ac_type),
+            _ => return plan,
+        };
+        let Some((equipment, surveillance, pbn)) = Self::faa_equipment(parts[parts.len() - 1])
+        else {
+            return plan;
+        };
+
+        plan.ac_type = format!("{}/{}-{}/{}", ac_type, wake, equipment, surveillance);
+        if let Some(pbn) = pbn {
+            if !self.remarks.to_uppercase().contains("PBN/") {
+                plan.remarks = format!("PBN/{} {}", pbn, self.remarks)
+                    .trim_end()
+                    .to_string();
+            }
+        }
+        plan
+    }
+
+    /// The ICAO equipment codes, surveillance codes and PBN capability equivalent to an FAA equipment suffix
+    fn faa_equipment(suffix: &str) -> Option<(&'static str, &'static str, Option<&'static str>)> {
+        match suffix {
+            "X" => Some(("S", "N", None)),
+            "T" => Some(("S", "A", None)),
+            "U" => Some(("S", "C", None)),
+            "D" => Some(("SD", "N", None)),
+            "B" => Some(("SD", "A", None)),
+            "A" => Some(("SD", "C", None)),
+            "G" => Some(("SDGR", "C", Some("B2C2D2"))),
+            "W" => Some(("SDW", "C", None)),
+            "Z" => Some(("SDRW", "C", Some("B4C4D4"))),
+            "L" => Some(("SDGRW", "C", Some("B2C2D2"))),
+            _ => None,
+        }
+    }
+
+    /// The highest cruise level, in feet, considered plausible for a filed flight plan
+    const MAX_PLAUSIBLE_CRUISE_LEVEL: u32 = 60000;
+    /// The highest cruise level, in feet, at which VFR flight is permitted anywhere, being the base of class A
+    /// airspace in much of Europe
+    const MAX_VFR_CRUISE_LEVEL: u32 = 19500;
+    /// The lowest cruise level, in feet, considered plausible for an IFR flight
+    const MIN_IFR_CRUISE_LEVEL: u32 = 1000;
+
+    /// Whether the cruise level has been filed, is within the range of any civil aircraft and is allowed by the
+    /// flight rules: no higher than FL195 for VFR flights, SVFR and DVFR included, and no lower than 1000 ft for IFR.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{FlightPlan, FlightPlanWarning, FlightRules};
+    /// let mut plan = FlightPlan::new(
+    ///     FlightRules::VFR, "C172", 110, "EGKB", 1000, 0, 2400, "EGKA", 0, 45, 3, 0, "", "", "VFR",
+    /// );
+    /// assert!(plan.cruise_plausible());
+    /// assert!(plan.validate().is_empty());
+    ///
+    /// plan.cruise_level = 35000;
+    /// assert!(!plan.cruise_plausible());
+    /// assert_eq!(
+    ///     vec![FlightPlanWarning::CruiseLevelOutsideFlightRules(FlightRules::VFR, 35000)],
+    ///     plan.validate()
+    /// );
+    ///
+    /// plan.flight_rules = FlightRules::IFR;
+    /// assert!(plan.cruise_plausible());
+    /// plan.cruise_level = 500;
+    /// assert!(!plan.cruise_plausible());
+    /// assert_eq!(
+    ///     vec![FlightPlanWarning::CruiseLevelOutsideFlightRules(FlightRules::IFR, 500)],
+    ///     plan.validate()
+    /// );
+    /// ```
+    pub fn cruise_plausible(&self) -> bool {
+        self.cruise_level != 0
+            && self.cruise_level <= Self::MAX_PLAUSIBLE_CRUISE_LEVEL
+            && self.cruise_level_within_flight_rules()
+    }
+
+    fn cruise_level_within_flight_rules(&self) -> bool {
+        match self.flight_rules {
+            FlightRules::IFR => self.cruise_level >= Self::MIN_IFR_CRUISE_LEVEL,
+            FlightRules::VFR | FlightRules::SVFR | FlightRules::DVFR => {
+                self.cruise_level <= Self::MAX_VFR_CRUISE_LEVEL
+            }
+        }
+    }
+
+    /// Checks the plan for internal inconsistencies which do not prevent it being parsed, but which a controller may
+    /// want to query. Fields left empty on the wire are parsed as zero, so a zero time enroute or fuel endurance is
+    /// treated as missing. The departure time is not checked, since a departure at 0000 cannot be told apart from an
+    /// empty field.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{FlightPlan, FlightPlanWarning, FlightRules};
+    /// let plan = FlightPlan::new(
+    ///     FlightRules::IFR, "B738", 450, "EGLL", 1200, 0, 99000, "EGLL", 1, 30, 1, 0, "EGKK", "", "DCT",
+    /// );
+    /// assert_eq!(
+    ///     vec![
+    ///         FlightPlanWarning::ImplausibleCruiseLevel(99000),
+    ///         FlightPlanWarning::DepartureIsDestination(String::from("EGLL")),
+    ///         FlightPlanWarning::InsufficientFuel,
+    ///     ],
+    ///     plan.validate()
+    /// );
+    ///
+    /// let plan = FlightPlan::new(
+    ///     FlightRules::IFR, "B738", 450, "EGLL", 1200, 0, 35000, "LIRF", 2, 20, 4, 0, "LIRA", "", "DCT",
+    /// );
+    /// assert!(plan.validate().is_empty());
+    ///
+    /// // Departing at midnight
+    /// let plan = FlightPlan::new(
+    ///     FlightRules::IFR, "B738", 450, "EGLL", 0, 0, 35000, "LIRF", 2, 20, 4, 0, "LIRA", "", "DCT",
+    /// );
+    /// assert!(plan.validate().is_empty());
+    /// ```
+    pub fn validate(&self) -> Vec<FlightPlanWarning> {
+        let mut warnings = Vec::new();
+
+        if self.cruise_level == 0 {
+            warnings.push(FlightPlanWarning::MissingCruiseLevel);
+        } else if self.cruise_level > Self::MAX_PLAUSIBLE_CRUISE_LEVEL {
+            warnings.push(FlightPlanWarning::ImplausibleCruiseLevel(self.cruise_level));
+        } else if !self.cruise_level_within_flight_rules() {
+            warnings.push(FlightPlanWarning::CruiseLevelOutsideFlightRules(
+                self.flight_rules,
+                self.cruise_level,
+            ));
+        }
+
+        let origin = Self::canonical_text(&self.origin);
+        if !origin.is_empty() && origin == Self::canonical_text(&self.destination) {
+            warnings.push(FlightPlanWarning::DepartureIsDestination(origin));
+        }
+
+        let enroute_mins = u16::from(self.hours_enroute) * 60 + u16::from(self.mins_enroute);
+        let fuel_mins = u16::from(self.hours_fuel) * 60 + u16::from(self.mins_fuel);
+        if enroute_mins == 0 {
+            warnings.push(FlightPlanWarning::MissingEnrouteTime);
+        }
+        if fuel_mins == 0 {
+            warnings.push(FlightPlanWarning::MissingFuelTime);
+        } else if fuel_mins < enroute_mins {
+            warnings.push(FlightPlanWarning::InsufficientFuel);
+        }
+
+        warnings
+    }
+
+    /// The route in a canonical form for storage and comparison: upper-cased, with runs of whitespace collapsed to
+    /// a single space and every direct marker (`DCT`, `DIRECT`, `.` or `..`) written as `DCT`. Dots are direct markers
+    /// wherever they appear, so `MERIT..HFD` becomes `MERIT DCT HFD`, and consecutive direct markers become one. The
+    /// [`route`][FlightPlan::route] field itself is left as filed.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{FlightPlan, FlightRules};
+    /// let mut plan = FlightPlan::new(
+    ///     FlightRules::IFR, "B738", 450, "EGKK", 1200, 0, 36000, "LEMG", 2, 30, 4, 0, "LEZL", "", "",
+    /// );
+    /// for route in [
+    ///     "MIMFO DCT DVR UL9 KONAN",
+    ///     "  mimfo   dct DVR\tUL9 konan ",
+    ///     "MIMFO . DVR UL9 KONAN",
+    ///     "MIMFO .. DVR UL9 KONAN",
+    ///     "MIMFO direct DVR UL9 KONAN",
+    ///     "MIMFO..DVR UL9 KONAN",
+    ///     "MIMFO.DCT DVR UL9 KONAN",
+    ///     "MIMFO. DVR UL9 KONAN",
+    /// ] {
+    ///     plan.route = String::from(route);
+    ///     assert_eq!("MIMFO DCT DVR UL9 KONAN", plan.normalized_route());
+    ///     assert_eq!(route, plan.route);
+    /// }
+    ///
+    /// plan.route = String::from("KJFK.DCT MERIT..HFD");
+    /// assert_eq!("KJFK DCT MERIT DCT HFD", plan.normalized_route());
+    /// ```
+    pub fn normalized_route(&self) -> String {
+        self.normalized_route_chars().collect()
+    }
+
+    /// The characters of [`normalized_route`][FlightPlan::normalized_route], produced without allocating
+    fn normalized_route_chars(&self) -> impl Iterator<Item = char> + '_ {
+        let elements = self
+            .route
+            .split_whitespace()
+            .flat_map(|token| {
+                // A dot anywhere in a token separates the elements either side of it with a direct marker
+                token.split('.').enumerate().flat_map(|(i, element)| {
+                    (i > 0)
+                        .then_some("DCT")
+                        .into_iter()
+                        .chain(std::iter::once(element))
+                })
+            })
+            .filter(|element| !element.is_empty())
+            .map(|element| {
+                if element.eq_ignore_ascii_case("DCT") || element.eq_ignore_ascii_case("DIRECT") {
+                    "DCT"
+                } else {
+                    element
+                }
+            })
+            .scan(false, |previous_direct, element| {
+                let direct = element == "DCT";
+                let repeated = direct && *previous_direct;
+                *previous_direct = direct;
+                Some((!repeated).then_some(element))
+            })
+            .flatten();
+        Self::words_with_spaces(elements)
+    }
+
+    fn canonical_text(text: &str) -> String {
+        Self::canonical_chars(text).collect()
+    }
+
+    /// The characters of [`canonical_text`][FlightPlan::canonical_text], produced without allocating
+    fn canonical_chars(text: &str) -> impl Iterator<Item = char> + '_ {
+        Self::words_with_spaces(text.split_whitespace())
+    }
+
+    fn words_with_spaces<'a>(
+        words: impl Iterator<Item = &'a str> + 'a,
+    ) -> impl Iterator<Item = char> + 'a {
+        words
+            .enumerate()
+            .flat_map(|(i, word)| (i > 0).then_some(' ').into_iter().chain(word.chars()))
+            .flat_map(char::to_uppercase)
+    }
+
+    fn text_fields(&self) -> [&str; 5] {
+        [
+            &self.ac_type,
+            &self.origin,
+            &self.destination,
+            &self.alternate,
+            &self.remarks,
+        ]
+    }
+
+    fn numeric_fields(&self) -> ([u16; 3], u32, [u8; 4]) {
+        (
+            [self.filed_tas, self.etd, self.atd],
+            self.cruise_level,
+            [
+                self.hours_enroute,
+                self.mins_enroute,
+                self.hours_fuel,
+                self.mins_fuel,
+            ],
+        )
+    }
+}
+
+impl PartialEq for FlightPlan {
+    fn eq(&self, other: &Self) -> bool {
+        self.flight_rules == other.flight_rules
+            && self.numeric_fields() == other.numeric_fields()
+            && self
+                .text_fields()
+                .into_iter()
+                .zip(other.text_fields())
+                .all(|(a, b)| Self::canonical_chars(a).eq(Self::canonical_chars(b)))
+            && self
+                .normalized_route_chars()
+                .eq(other.normalized_route_chars())
+    }
+}
+impl Eq for FlightPlan {}
+
+impl Hash for FlightPlan {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.flight_rules.hash(state);
+        self.numeric_fields().hash(state);
+        for text in self.text_fields() {
+            Self::canonical_chars(text).for_each(|c| c.hash(state));
+            // Ends each field, so that characters moving from one field to the next change the hash
+            state.write_u8(0xff);
+        }
+        self.normalized_route_chars().for_each(|c| c.hash(state));
+    }
+}
+
 impl Display for FlightPlan {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -256,9 +1501,43 @@ impl Display for FlightPlan {
 impl TryFrom<&[&str]> for FlightPlan {
     type Error = FsdMessageParseError;
     fn try_from(fields: &[&str]) -> Result<Self, Self::Error> {
-        if fields.len() != 15 {
-            return Err(FsdMessageParseError::InvalidFieldCount(15, fields.len()));
+        FlightPlan::parse(fields, ParseMode::Strict)
+    }
+}
+
+impl FlightPlan {
+    /// Parses the 15 fields of a flight plan, from the flight rules to the route.
+    ///
+    /// In [`ParseMode::Strict`] there must be exactly 15 fields. In [`ParseMode::Lenient`] only the fields up to and
+    /// including the destination are needed: any missing after it are read as empty, and any beyond the route are
+    /// taken to be colons within it.
+    ///
+    /// # Example
+    /// ```
+    /// use fsd_interface::{FlightPlan, ParseMode};
+    /// let short: Vec<&str> = "I:B738:450:EGLL:1200:0:35000:LIRF".split(':').collect();
+    /// let plan = FlightPlan::parse(&short, ParseMode::Lenient).unwrap();
+    /// assert_eq!("LIRF", plan.destination);
+    /// assert!(plan.alternate.is_empty() && plan.route.is_empty());
+    /// assert!(FlightPlan::parse(&short, ParseMode::Strict).is_err());
+    /// assert!(FlightPlan::parse(&short[..7], ParseMode::Lenient).is_err());
+    /// ```
+    pub fn parse(fields: &[&str], mode: ParseMode) -> Result<FlightPlan, FsdMessageParseError> {
+        match mode {
+            ParseMode::Strict if fields.len() != 15 => {
+                return Err(FsdMessageParseError::InvalidFieldCount(15, fields.len()));
+            }
+            ParseMode::Lenient if fields.len() < 8 => {
+                return Err(FsdMessageParseError::InvalidFieldCount(8, fields.len()));
+            }
+            _ => {}
         }
+        // The fields after the destination may be missing in lenient mode
+        let field = |i: usize| fields.get(i).copied().unwrap_or_default();
+        let route = fields
+            .get(14..)
+            .map(assemble_with_colons)
+            .unwrap_or_default();
 
         let filed_tas = if fields[2].is_empty() {
             0
@@ -281,39 +1560,39 @@ impl TryFrom<&[&str]> for FlightPlan {
                 .parse()
                 .map_err(|_| FsdMessageParseError::InvalidTime(fields[5].to_string()))?
         };
-        let hours_enroute = if fields[8].is_empty() {
+        let hours_enroute = if field(8).is_empty() {
             0
         } else {
-            fields[8]
+            field(8)
                 .parse()
-                .map_err(|_| FsdMessageParseError::InvalidTime(fields[8].to_string()))?
+                .map_err(|_| FsdMessageParseError::InvalidTime(field(8).to_string()))?
         };
-        let hours_fuel = if fields[10].is_empty() {
+        let hours_fuel = if field(10).is_empty() {
             0
         } else {
-            fields[10]
+            field(10)
                 .parse()
-                .map_err(|_| FsdMessageParseError::InvalidTime(fields[10].to_string()))?
+                .map_err(|_| FsdMessageParseError::InvalidTime(field(10).to_string()))?
         };
-        let mins_enroute = if fields[9].is_empty() {
+        let mins_enroute = if field(9).is_empty() {
             0
         } else {
-            let mins = fields[9]
+            let mins = field(9)
                 .parse()
-                .map_err(|_| FsdMessageParseError::InvalidTime(fields[9].to_string()))?;
+                .map_err(|_| FsdMessageParseError::InvalidTime(field(9).to_string()))?;
             if mins > 59 {
-                return Err(FsdMessageParseError::InvalidMinute(fields[9].to_string()));
+                return Err(FsdMessageParseError::InvalidMinute(field(9).to_string()));
             }
             mins
         };
-        let mins_fuel = if fields[11].is_empty() {
+        let mins_fuel = if field(11).is_empty() {
             0
         } else {
-            let mins = fields[11]
+            let mins = field(11)
                 .parse()
-                .map_err(|_| FsdMessageParseError::InvalidTime(fields[11].to_string()))?;
+                .map_err(|_| FsdMessageParseError::InvalidTime(field(11).to_string()))?;
             if mins > 59 {
-                return Err(FsdMessageParseError::InvalidMinute(fields[11].to_string()));
+                return Err(FsdMessageParseError::InvalidMinute(field(11).to_string()));
             }
             mins
         };
@@ -331,14 +1610,15 @@ impl TryFrom<&[&str]> for FlightPlan {
             mins_enroute,
             hours_fuel,
             mins_fuel,
-            fields[12],
-            fields[13],
-            fields[14],
+            field(12),
+            field(13),
+            route,
         ))
     }
 }
 
 impl FlightPlan {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         flight_rules: FlightRules,
         ac_type: impl AsRef<str>,
@@ -0,0 +1,100 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::enums::FsdMessageType;
+
+/// A value, usually a message, paired with the time it was sent or received
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timestamped<T> {
+    pub time: SystemTime,
+    pub message: T,
+}
+
+impl<T> Timestamped<T> {
+    pub fn new(time: SystemTime, message: T) -> Self {
+        Timestamped { time, message }
+    }
+}
+
+/// Formats a recorded session as an aligned table with one row per message, giving the UTC time of day, the
+/// [`MessageCategory`][crate::MessageCategory], the sender and recipient and the type of message.
+///
+/// Messages with no recipient show only the sender.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use fsd_interface::{format_session, parse_message, Timestamped};
+/// let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let session = [
+///     (0, "#APBAW123:SERVER:1234567:password:1:101:9:Joe Bloggs"),
+///     (1500, "@N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8"),
+///     (2250, "#TMEGLL_N_TWR:BAW123:Contact London on 118.5"),
+/// ]
+/// .map(|(ms, line)| Timestamped::new(start + Duration::from_millis(ms), parse_message(line).unwrap()));
+///
+/// assert_eq!(
+///     "\
+/// TIME          CATEGORY  SENDER → RECIPIENT   MESSAGE
+/// 22:13:20.000  Session   BAW123 → SERVER      PilotRegisterMessage
+/// 22:13:21.500  Position  BAW123               PilotPositionUpdateMessage
+/// 22:13:22.250  Text      EGLL_N_TWR → BAW123  TextMessage
+/// ",
+///     format_session(&session)
+/// );
+/// ```
+pub fn format_session(messages: &[Timestamped<FsdMessageType>]) -> String {
+    let rows: Vec<[String; 4]> = messages
+        .iter()
+        .map(|entry| {
+            let kind = entry.message.kind();
+            let route = match (entry.message.sender(), entry.message.recipient()) {
+                (Some(sender), Some(recipient)) => format!("{} → {}", sender, recipient),
+                (Some(sender), None) => sender.to_string(),
+                (None, Some(recipient)) => format!("→ {}", recipient),
+                (None, None) => String::new(),
+            };
+            [
+                format_time_of_day(entry.time),
+                format!("{:?}", kind.category),
+                route,
+                kind.name.to_string(),
+            ]
+        })
+        .collect();
+
+    let header = [
+        String::from("TIME"),
+        String::from("CATEGORY"),
+        String::from("SENDER → RECIPIENT"),
+        String::from("MESSAGE"),
+    ];
+    let mut widths = [0; 3];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.chars().count());
+        }
+    }
+
+    let mut output = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, column) in widths.iter().zip(row) {
+            output.push_str(column);
+            output.extend(std::iter::repeat_n(' ', width - column.chars().count() + 2));
+        }
+        output.push_str(&row[3]);
+        output.push('\n');
+    }
+    output
+}
+
+fn format_time_of_day(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs() % 86400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        since_epoch.subsec_millis()
+    )
+}
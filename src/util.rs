@@ -1,70 +1,254 @@
-use crate::{enums::ClientCapability, errors::FsdMessageParseError, structs::RadioFrequency};
-use std::str::FromStr;
+use crate::{enums::AdvertisedCapability, errors::FsdMessageParseError, structs::RadioFrequency};
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-pub fn encode_pitch_bank_heading(pitch: f64, bank: f64, heading: f64, on_ground: bool) -> u32 {
-    let mut p = pitch / -360.0;
-    if p < 0.0 {
-        p += 1.0;
+/// The pitch, bank, heading and on-ground flag of an aircraft, packed into the single number carried by pilot
+/// position updates.
+///
+/// Bits 22 to 31 hold the pitch, bits 12 to 21 the bank and bits 2 to 11 the heading, each in 1024ths of a full
+/// circle (pitch and bank are negated). Bit 1 is set when the aircraft is on the ground.
+///
+/// # Example
+/// ```
+/// use fsd_interface::util::{decode_pitch_bank_heading, PitchBankHeading};
+/// let raw = 4290769920;
+/// let pbh = PitchBankHeading::from(raw);
+/// let (pitch, bank, heading, on_ground) = decode_pitch_bank_heading(raw);
+/// assert_eq!(pitch, pbh.pitch());
+/// assert_eq!(bank, pbh.bank());
+/// assert_eq!(heading, pbh.heading());
+/// assert_eq!(on_ground, pbh.on_ground());
+/// assert_eq!((1022, 1023, 256), (pbh.pitch_bits(), pbh.bank_bits(), pbh.heading_bits()));
+///
+/// let pbh = PitchBankHeading::new(0.0, 0.0, 90.0, true);
+/// assert_eq!((0, 0, 256), (pbh.pitch_bits(), pbh.bank_bits(), pbh.heading_bits()));
+/// assert!(pbh.on_ground());
+/// assert_eq!(1026, u32::from(pbh));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PitchBankHeading {
+    pub raw: u32,
+}
+
+impl PitchBankHeading {
+    pub fn new(pitch: f64, bank: f64, heading: f64, on_ground: bool) -> Self {
+        let mut p = pitch / -360.0;
+        if p < 0.0 {
+            p += 1.0;
+        }
+        p *= 1024.0;
+
+        let mut b = bank / -360.0;
+        if b < 0.0 {
+            b += 1.0;
+        }
+        b *= 1024.0;
+
+        let h = heading / 360.0 * 1024.0;
+
+        let raw =
+            ((p as u32) << 22) | ((b as u32) << 12) | ((h as u32) << 2) | ((on_ground as u32) << 1);
+        PitchBankHeading { raw }
     }
-    p *= 1024.0;
 
-    let mut b = bank / -360.0;
-    if b < 0.0 {
-        b += 1.0;
+    pub fn pitch_bits(&self) -> u32 {
+        self.raw >> 22
     }
-    b *= 1024.0;
 
-    let h = heading / 360.0 * 1024.0;
+    pub fn bank_bits(&self) -> u32 {
+        (self.raw >> 12) & 1023
+    }
 
-    ((p as u32) << 22) | ((b as u32) << 12) | ((h as u32) << 2) | ((on_ground as u32) << 1)
-}
+    pub fn heading_bits(&self) -> u32 {
+        (self.raw >> 2) & 1023
+    }
 
-pub fn decode_pitch_bank_heading(input: u32) -> (f64, f64, f64, bool) {
-    let on_ground = (input & 2) == 1;
+    pub fn on_ground(&self) -> bool {
+        (self.raw & 2) != 0
+    }
+
+    /// Pitch in degrees, from -180 to 180
+    pub fn pitch(&self) -> f64 {
+        let mut pitch = self.pitch_bits() as f64 / 1024.0 * -360.0;
+        if pitch > 180.0 {
+            pitch -= 360.0;
+        } else if pitch <= -180.0 {
+            pitch += 360.0;
+        }
+        pitch
+    }
 
-    let mut input = input >> 2;
-    let mut heading = (input & 1023) as f64;
-    input >>= 10;
-    let mut bank = (input & 1023) as f64;
-    input >>= 10;
-    let mut pitch = input as f64;
+    /// Bank in degrees, from -180 to 180
+    pub fn bank(&self) -> f64 {
+        let mut bank = self.bank_bits() as f64 / 1024.0 * -360.0;
+        if bank > 180.0 {
+            bank -= 360.0;
+        } else if bank <= -180.0 {
+            bank += 360.0;
+        }
+        bank
+    }
 
-    pitch = pitch / 1024.0 * -360.0;
-    if pitch > 180.0 {
-        pitch -= 360.0;
-    } else if pitch <= -180.0 {
-        pitch += 360.0;
+    /// Heading in degrees, from 0 to 360
+    pub fn heading(&self) -> f64 {
+        let mut heading = self.heading_bits() as f64 / 1024.0 * 360.0;
+        if heading < 0.0 {
+            heading += 360.0;
+        } else if heading >= 360.0 {
+            heading -= 360.0;
+        }
+        heading
     }
+}
 
-    bank = bank / 1024.0 * -360.0;
-    if bank > 180.0 {
-        bank -= 360.0;
-    } else if bank <= -180.0 {
-        bank += 360.0;
+impl From<u32> for PitchBankHeading {
+    fn from(raw: u32) -> Self {
+        PitchBankHeading { raw }
     }
+}
 
-    heading = heading / 1024.0 * 360.0;
-    if heading < 0.0 {
-        heading += 360.0;
-    } else if heading >= 360.0 {
-        heading -= 360.0;
+impl From<PitchBankHeading> for u32 {
+    fn from(pbh: PitchBankHeading) -> Self {
+        pbh.raw
     }
+}
+
+pub fn encode_pitch_bank_heading(pitch: f64, bank: f64, heading: f64, on_ground: bool) -> u32 {
+    PitchBankHeading::new(pitch, bank, heading, on_ground).into()
+}
+
+pub fn decode_pitch_bank_heading(input: u32) -> (f64, f64, f64, bool) {
+    let pbh = PitchBankHeading::from(input);
+    (pbh.pitch(), pbh.bank(), pbh.heading(), pbh.on_ground())
+}
+
+/// Computes the pressure altitude difference sent in pilot position updates, in whole feet.
+///
+/// Both altitudes are rounded to the nearest foot before taking the difference, as the true altitude is itself sent
+/// rounded, so that adding the difference to the true altitude sent gives the pressure altitude to the nearest foot.
+/// Rounding the difference of the unrounded altitudes instead can be a foot out.
+///
+/// # Example
+/// ```
+/// use fsd_interface::util::{decode_altitude_difference, encode_altitude_difference};
+/// assert_eq!(0, encode_altitude_difference(1000.0, 999.6));
+/// assert_eq!(-1, encode_altitude_difference(1000.0, 999.4));
+/// assert_eq!(0, encode_altitude_difference(1000.4, 999.6));
+/// assert_eq!(-10, encode_altitude_difference(1199.6, 1190.0));
+/// assert_eq!(1450, encode_altitude_difference(-50.0, 1400.0));
+///
+/// let diff = encode_altitude_difference(35001.4, 34499.5);
+/// assert_eq!(34500.0, decode_altitude_difference(35001.4_f64.round(), diff as f64));
+/// ```
+pub fn encode_altitude_difference(true_altitude: f64, pressure_altitude: f64) -> i32 {
+    (pressure_altitude.round() - true_altitude.round()) as i32
+}
 
-    (pitch, bank, heading, on_ground)
+/// Recovers the pressure altitude from the true altitude and the pressure altitude difference sent in pilot
+/// position updates
+pub fn decode_altitude_difference(true_altitude: f64, altitude_difference: f64) -> f64 {
+    true_altitude + altitude_difference
+}
+
+const EARTH_RADIUS_NM: f64 = 3440.065;
+
+/// Great-circle distance in nautical miles between two lat / long coordinates in degrees
+pub(crate) fn great_circle_distance_nm(lat_1: f64, lon_1: f64, lat_2: f64, lon_2: f64) -> f64 {
+    let (lat_1, lat_2) = (lat_1.to_radians(), lat_2.to_radians());
+    let d_lat = lat_2 - lat_1;
+    let d_lon = (lon_2 - lon_1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat_1.cos() * lat_2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_NM * a.sqrt().asin()
+}
+
+/// The lat / long coordinate in degrees reached by travelling the given distance in nautical miles from a
+/// starting coordinate, along the great circle with the given initial bearing in degrees
+#[cfg(feature = "geojson")]
+pub(crate) fn destination_point(lat: f64, lon: f64, bearing: f64, distance_nm: f64) -> (f64, f64) {
+    let (lat, lon, bearing) = (lat.to_radians(), lon.to_radians(), bearing.to_radians());
+    let angular_distance = distance_nm / EARTH_RADIUS_NM;
+    let dest_lat = (lat.sin() * angular_distance.cos()
+        + lat.cos() * angular_distance.sin() * bearing.cos())
+    .asin();
+    let dest_lon = lon
+        + (bearing.sin() * angular_distance.sin() * lat.cos())
+            .atan2(angular_distance.cos() - lat.sin() * dest_lat.sin());
+    let dest_lon = (dest_lon.to_degrees() + 540.0) % 360.0 - 180.0;
+    (dest_lat.to_degrees(), dest_lon)
+}
+
+/// The lat / long coordinate in degrees at fraction `t` of the way along the great circle between two coordinates
+pub(crate) fn interpolate_great_circle(
+    lat_1: f64,
+    lon_1: f64,
+    lat_2: f64,
+    lon_2: f64,
+    t: f64,
+) -> (f64, f64) {
+    let to_vector = |lat: f64, lon: f64| {
+        let (lat, lon) = (lat.to_radians(), lon.to_radians());
+        [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+    };
+    let (a, b) = (to_vector(lat_1, lon_1), to_vector(lat_2, lon_2));
+    let angle = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2])
+        .clamp(-1.0, 1.0)
+        .acos();
+    if angle < 1e-12 {
+        return (lat_1 + (lat_2 - lat_1) * t, lon_1 + (lon_2 - lon_1) * t);
+    }
+    let (weight_a, weight_b) = (
+        ((1.0 - t) * angle).sin() / angle.sin(),
+        (t * angle).sin() / angle.sin(),
+    );
+    let [x, y, z] = [0, 1, 2].map(|i| weight_a * a[i] + weight_b * b[i]);
+    (
+        z.atan2((x * x + y * y).sqrt()).to_degrees(),
+        y.atan2(x).to_degrees(),
+    )
+}
+
+/// The angle in degrees at fraction `t` of the way from `from` to `to`, turning the shorter way round
+pub(crate) fn interpolate_angle(from: f64, to: f64, t: f64) -> f64 {
+    let difference = (to - from + 540.0).rem_euclid(360.0) - 180.0;
+    from + difference * t
 }
 
 pub fn split_frequencies(input: &str) -> Vec<RadioFrequency> {
-    input
-        .split(['&', '@'])
-        .filter_map(|x| RadioFrequency::from_str(x).ok())
-        .collect()
+    parse_frequencies_checked(input).0
+}
+
+/// Parses a `&`-separated list of frequencies, such as `@22800&@24550`, returning the frequencies that parsed
+/// alongside the raw entries that did not, so that a malformed list can be reported rather than silently shortened.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{util::parse_frequencies_checked, RadioFrequency};
+/// let (frequencies, invalid) = parse_frequencies_checked("@22800&@2455X&@21800&8330&@");
+/// assert_eq!(
+///     vec![RadioFrequency::new(122, 800).unwrap(), RadioFrequency::new(121, 800).unwrap()],
+///     frequencies
+/// );
+/// assert_eq!(vec!["2455X", "8330"], invalid);
+/// ```
+pub fn parse_frequencies_checked(input: &str) -> (Vec<RadioFrequency>, Vec<String>) {
+    let mut frequencies = Vec::new();
+    let mut invalid = Vec::new();
+    for entry in input.split(['&', '@']).filter(|x| !x.is_empty()) {
+        match RadioFrequency::from_str(entry) {
+            Ok(frequency) => frequencies.push(frequency),
+            Err(_) => invalid.push(entry.to_string()),
+        }
+    }
+    (frequencies, invalid)
 }
 
 pub(crate) fn group_frequencies_without_symbol(frequencies: &[RadioFrequency]) -> String {
-    let mut freqs_string = String::with_capacity(6 * frequencies.len() - 1);
+    let mut freqs_string = String::with_capacity((6 * frequencies.len()).saturating_sub(1));
     let mut freqs = frequencies.iter().peekable();
     while let Some(freq) = freqs.next() {
-        freqs_string.push_str(&freq.to_string());
+        freqs_string.push_str(&freq.to_plain_string());
         if freqs.peek().is_some() {
             freqs_string.push('&');
         }
@@ -73,11 +257,10 @@ pub(crate) fn group_frequencies_without_symbol(frequencies: &[RadioFrequency]) -
 }
 
 pub(crate) fn group_frequencies_with_symbol(frequencies: &[RadioFrequency]) -> String {
-    let mut freqs_string = String::with_capacity(6 * frequencies.len() - 1);
+    let mut freqs_string = String::with_capacity((6 * frequencies.len()).saturating_sub(1));
     let mut freqs = frequencies.iter().peekable();
     while let Some(freq) = freqs.next() {
-        freqs_string.push('@');
-        freqs_string.push_str(&freq.to_string());
+        freqs_string.push_str(&freq.to_symbol_string());
         if freqs.peek().is_some() {
             freqs_string.push('&');
         }
@@ -85,6 +268,54 @@ pub(crate) fn group_frequencies_with_symbol(frequencies: &[RadioFrequency]) -> S
     freqs_string
 }
 
+/// Whether `text` can be split at byte offset `index` without breaking up a user-perceived character.
+///
+/// This approximates extended grapheme cluster boundaries: text is never split inside a UTF-8 sequence, before a
+/// combining mark, variation selector, emoji modifier or tag, on either side of a zero width joiner, or between the
+/// two regional indicators of a flag.
+pub(crate) fn is_cluster_boundary(text: &str, index: usize) -> bool {
+    if index == 0 || index >= text.len() {
+        return true;
+    }
+    if !text.is_char_boundary(index) {
+        return false;
+    }
+    let (before, after) = text.split_at(index);
+    let (Some(previous), Some(next)) = (before.chars().next_back(), after.chars().next()) else {
+        return true;
+    };
+    if previous == '\u{200D}' || extends_cluster(next) {
+        return false;
+    }
+    if is_regional_indicator(previous) && is_regional_indicator(next) {
+        let preceding = before
+            .chars()
+            .rev()
+            .take_while(|c| is_regional_indicator(*c))
+            .count();
+        return preceding % 2 == 0;
+    }
+    true
+}
+
+fn extends_cluster(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200D}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
 pub(crate) fn parse_altitude(input: &str) -> Result<u32, FsdMessageParseError> {
     if input.is_empty() {
         Ok(0)
@@ -138,6 +369,127 @@ pub(crate) fn parse_new_atis(
 
 // $CQESSA_A_ATIS:@94835:NEWATIS:ATIS N:  31016KT - Q986
 
+/// The characters represented by bytes 0x80 to 0x9F in Windows-1252. Undefined bytes map to themselves, as they do
+/// in Windows' own conversion.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// Decodes text as Windows-1252, the encoding older clients commonly send free text in. Every byte sequence is
+/// valid Windows-1252, so this never fails.
+///
+/// # Example
+/// ```
+/// use fsd_interface::util::decode_windows_1252;
+/// assert_eq!("RMK/12°C", decode_windows_1252(b"RMK/12\xB0C"));
+/// assert_eq!("€5", decode_windows_1252(b"\x805"));
+/// ```
+pub fn decode_windows_1252(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9F => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+            _ => b as char,
+        })
+        .collect()
+}
+
+/// Parses a UTC time in the `YYYYMMDDHHMMSS` form used by `SIMTIME` queries
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use fsd_interface::util::{format_sim_time, parse_sim_time};
+/// let time = parse_sim_time("20240229235959").unwrap();
+/// assert_eq!(UNIX_EPOCH + Duration::from_secs(1709251199), time);
+/// assert_eq!("20240229235959", format_sim_time(time));
+/// assert_eq!("19691231235959", format_sim_time(parse_sim_time("19691231235959").unwrap()));
+///
+/// assert!(parse_sim_time("20230229120000").is_err());
+/// assert!(parse_sim_time("20231231235960").is_err());
+/// assert!(parse_sim_time("2023123123595").is_err());
+/// ```
+pub fn parse_sim_time(input: &str) -> Result<SystemTime, FsdMessageParseError> {
+    let invalid = || FsdMessageParseError::InvalidTime(input.to_string());
+    if input.len() != 14 || !input.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let part = |range: std::ops::Range<usize>| input[range].parse::<u32>().map_err(|_| invalid());
+    let (year, month, day) = (part(0..4)? as i64, part(4..6)?, part(6..8)?);
+    let (hour, minute, second) = (part(8..10)?, part(10..12)?, part(12..14)?);
+
+    let leap_year = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap_year => 29,
+        2 => 28,
+        _ => return Err(invalid()),
+    };
+    if day == 0 || day > days_in_month || hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
+    }
+
+    let seconds =
+        days_from_civil(year, month, day) * 86400 + (hour * 3600 + minute * 60 + second) as i64;
+    Ok(if seconds >= 0 {
+        UNIX_EPOCH + Duration::from_secs(seconds as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+    })
+}
+
+/// Formats a time, truncated to the whole second, in the `YYYYMMDDHHMMSS` UTC form used by `SIMTIME` queries
+pub fn format_sim_time(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        // Round towards the earlier second, as for times after the epoch
+        Err(before) => {
+            let before = before.duration();
+            -(before.as_secs() as i64) - i64::from(before.subsec_nanos() > 0)
+        }
+    };
+    let (days, time_of_day) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+// Conversions between proleptic Gregorian dates and days since 1970-01-01, from
+// https://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
 #[inline]
 pub(crate) fn assemble_with_colons(slice: &[&str]) -> String {
     let mut buffer = String::new();
@@ -151,8 +503,8 @@ pub(crate) fn assemble_with_colons(slice: &[&str]) -> String {
     buffer
 }
 
-pub fn read_capabilities(caps_str: &[&str]) -> Vec<ClientCapability> {
-    let mut capabilities: Vec<ClientCapability> = Vec::with_capacity(caps_str.len() / 2);
+pub fn read_capabilities(caps_str: &[&str]) -> Vec<AdvertisedCapability> {
+    let mut capabilities: Vec<AdvertisedCapability> = Vec::with_capacity(caps_str.len());
     if caps_str.is_empty() {
         return capabilities;
     }
@@ -169,11 +521,15 @@ pub fn read_capabilities(caps_str: &[&str]) -> Vec<ClientCapability> {
             None => continue,
         };
 
-        if let Ok(capability) = k.to_uppercase().as_str().parse() {
-            if v == "1" {
-                capabilities.push(capability);
-            }
-        }
+        let supported = match v.as_str() {
+            "1" => true,
+            "0" => false,
+            _ => continue,
+        };
+        capabilities.push(match k.parse() {
+            Ok(capability) => AdvertisedCapability::Known(capability, supported),
+            Err(_) => AdvertisedCapability::Unknown(k.to_string(), supported),
+        });
     }
     capabilities
 }
@@ -0,0 +1,82 @@
+use crate::{
+    enums::{FsdMessageType, ParseMode},
+    errors::FsdMessageParseError,
+};
+
+/// The outcome of parsing every line of a recorded session, for auditing a capture rather than acting on it.
+///
+/// Unlike parsing line by line and stopping at the first failure, every line is parsed and every failure is kept
+/// alongside the number of the line it occurred on. Lines are numbered from 1 and may end in `\n` or `\r\n`. Blank
+/// lines are skipped, but still counted towards the line numbers.
+///
+/// # Example
+/// ```
+/// use fsd_interface::{errors::FsdMessageParseError, ParseMode, ValidationReport};
+/// let capture = "$DISERVER:CLIENT:VATSIM FSD V3.43:de1e\r\n\
+///     $IDBAW123:SERVER:de1e:vPilot:3:8:1234567:1234567890\r\n\
+///     #APBAW123:SERVER:1234567:password:1:101:9:Joe Bloggs\r\n\
+///     @N:BAW123:7000:1:51.47000:-0.45000:83:0:4290769920:-8\r\n\
+///     \r\n\
+///     @N:BAW123:7000:1:north:-0.45000:83:0:4290769920:-8\r\n\
+///     #TMBAW123:EGLL_N_TWR:Request startup\r\n\
+///     ?garbage\r\n";
+/// let report = ValidationReport::new(capture, ParseMode::Lenient);
+/// assert_eq!(7, report.total());
+/// assert_eq!(5, report.parsed_count());
+/// assert_eq!(2, report.error_count());
+/// assert!(!report.is_clean());
+///
+/// let errors: Vec<_> = report.errors().collect();
+/// assert_eq!(6, errors[0].0);
+/// assert!(matches!(errors[0].1, FsdMessageParseError::InvalidCoordinate(_)));
+/// assert_eq!(8, errors[1].0);
+/// assert!(matches!(errors[1].1, FsdMessageParseError::UnknownMessageType(_)));
+///
+/// assert_eq!(vec![1, 2, 3, 4, 6, 7, 8], report.results.iter().map(|(line, _)| *line).collect::<Vec<_>>());
+/// ```
+#[derive(Debug)]
+pub struct ValidationReport {
+    /// The line number and parse result of each non-blank line, in order
+    pub results: Vec<(usize, Result<FsdMessageType, FsdMessageParseError>)>,
+}
+
+impl ValidationReport {
+    /// Parses every line of `buffer` with the given [`ParseMode`]
+    pub fn new(buffer: &str, mode: ParseMode) -> Self {
+        let results = buffer
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| (i + 1, FsdMessageType::identify(line, mode)))
+            .collect();
+        ValidationReport { results }
+    }
+
+    /// The number of lines parsed, successfully or not
+    pub fn total(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn parsed_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .count()
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.total() - self.parsed_count()
+    }
+
+    /// Whether every line parsed successfully
+    pub fn is_clean(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// Each failure along with the number of the line it occurred on
+    pub fn errors(&self) -> impl Iterator<Item = (usize, &FsdMessageParseError)> {
+        self.results
+            .iter()
+            .filter_map(|(line, result)| result.as_ref().err().map(|error| (*line, error)))
+    }
+}